@@ -80,9 +80,10 @@ fn main() -> Result<(), Box<dyn Error>> {
     });
     let after_failure = fs::read(&path)?;
     let parsed_data: MemoStoreV2 = serde_json::from_slice(&after_failure)?;
-    let visible_files_after_failure = fs::read_dir(directory.path())?
+    let mut visible_files_after_failure = fs::read_dir(directory.path())?
         .map(|entry| entry.map(|value| value.file_name().to_string_lossy().into_owned()))
         .collect::<Result<Vec<_>, _>>()?;
+    visible_files_after_failure.sort();
 
     let passed = moved
         && before_success != after_success
@@ -98,7 +99,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             .trash
             .first()
             .is_some_and(|entry| entry.id == "move-me")
-        && visible_files_after_failure == ["memo_store.json"];
+        && visible_files_after_failure == ["memo_store.json", "memo_store.json.lock"];
 
     let report = QaReport {
         before_success_sha256: sha256(&before_success),
@@ -0,0 +1,300 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chacha20poly1305::aead::OsRng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::memo_store::{AtomicFileCommit, CommitAdapter, CommitError};
+
+pub const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 5 * 60;
+const PIN_LENGTH: std::ops::RangeInclusive<usize> = 4..=12;
+const FREE_ATTEMPTS: u32 = 3;
+const BASE_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+const FINAL_SAVE_GRACE: Duration = Duration::from_secs(3);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct AppLockConfig {
+    pin_hash: Option<String>,
+    #[serde(default = "default_idle_timeout_secs")]
+    idle_timeout_secs: u64,
+    #[serde(default)]
+    failed_attempts: u32,
+}
+
+impl Default for AppLockConfig {
+    fn default() -> Self {
+        Self {
+            pin_hash: None,
+            idle_timeout_secs: DEFAULT_IDLE_TIMEOUT_SECS,
+            failed_attempts: 0,
+        }
+    }
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    DEFAULT_IDLE_TIMEOUT_SECS
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct AppLockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub idle_timeout_secs: u64,
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum AppLockError {
+    #[error("app is locked")]
+    Locked,
+    #[error("wrong PIN")]
+    WrongPin,
+    #[error("too many wrong PINs, retry in {retry_after_secs} seconds")]
+    TooManyAttempts { retry_after_secs: u64 },
+    #[error("PIN must be {min} to {max} digits", min = PIN_LENGTH.start(), max = PIN_LENGTH.end())]
+    InvalidPin,
+    #[error("app lock is not enabled")]
+    NotEnabled,
+    #[error("failed to hash PIN: {0}")]
+    Hash(String),
+    #[error("failed to read app lock config {path}: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("app lock config {path} is malformed: {source}")]
+    Malformed {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("failed to serialize app lock config: {0}")]
+    Serialize(#[source] serde_json::Error),
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+}
+
+struct LockState {
+    config: AppLockConfig,
+    locked: bool,
+    locked_at: Option<Instant>,
+    last_activity: Instant,
+    retry_at: Option<Instant>,
+}
+
+pub struct AppLock {
+    path: PathBuf,
+    state: Mutex<LockState>,
+}
+
+impl AppLock {
+    pub fn open(path: impl Into<PathBuf>, now: Instant) -> Result<Self, AppLockError> {
+        let path = path.into();
+        let config = read_config(&path)?;
+        Ok(Self {
+            path,
+            state: Mutex::new(LockState {
+                locked: config.pin_hash.is_some(),
+                locked_at: None,
+                retry_at: backoff_after(config.failed_attempts).map(|delay| now + delay),
+                config,
+                last_activity: now,
+            }),
+        })
+    }
+
+    pub fn status(&self) -> AppLockStatus {
+        let state = self.state();
+        AppLockStatus {
+            enabled: state.config.pin_hash.is_some(),
+            locked: state.locked,
+            idle_timeout_secs: state.config.idle_timeout_secs,
+        }
+    }
+
+    pub fn ensure_unlocked(&self, now: Instant) -> Result<(), AppLockError> {
+        let mut state = self.state();
+        engage_if_idle(&mut state, now);
+        if state.locked {
+            return Err(AppLockError::Locked);
+        }
+        state.last_activity = now;
+        Ok(())
+    }
+
+    pub fn ensure_writable(&self, now: Instant) -> Result<(), AppLockError> {
+        let mut state = self.state();
+        engage_if_idle(&mut state, now);
+        let in_grace = state
+            .locked_at
+            .is_some_and(|locked_at| now.saturating_duration_since(locked_at) <= FINAL_SAVE_GRACE);
+        if state.locked && !in_grace {
+            return Err(AppLockError::Locked);
+        }
+        Ok(())
+    }
+
+    pub fn touch(&self, now: Instant) {
+        let mut state = self.state();
+        if !engage_if_idle(&mut state, now) && !state.locked {
+            state.last_activity = now;
+        }
+    }
+
+    pub fn lock(&self, now: Instant) -> bool {
+        let mut state = self.state();
+        let engaged = state.config.pin_hash.is_some() && !state.locked;
+        if engaged {
+            state.locked = true;
+            state.locked_at = Some(now);
+        }
+        engaged
+    }
+
+    pub fn expire(&self, now: Instant) -> bool {
+        engage_if_idle(&mut self.state(), now)
+    }
+
+    pub fn unlock(&self, pin: &str, now: Instant) -> Result<(), AppLockError> {
+        let mut state = self.state();
+        let hash = state
+            .config
+            .pin_hash
+            .as_deref()
+            .ok_or(AppLockError::NotEnabled)?;
+        if let Some(retry_at) = state.retry_at.filter(|retry_at| *retry_at > now) {
+            let remaining = retry_at.saturating_duration_since(now);
+            return Err(AppLockError::TooManyAttempts {
+                retry_after_secs: remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0),
+            });
+        }
+
+        if let Err(error) = verify_pin(hash, pin) {
+            let failed_attempts = state.config.failed_attempts.saturating_add(1);
+            state.retry_at = backoff_after(failed_attempts).map(|delay| now + delay);
+            self.write_config(&mut state, |config| {
+                config.failed_attempts = failed_attempts
+            })?;
+            return Err(error);
+        }
+
+        if state.config.failed_attempts > 0 {
+            self.write_config(&mut state, |config| config.failed_attempts = 0)?;
+        }
+        state.retry_at = None;
+        state.locked = false;
+        state.locked_at = None;
+        state.last_activity = now;
+        Ok(())
+    }
+
+    pub fn set_pin(&self, current_pin: Option<&str>, new_pin: &str) -> Result<(), AppLockError> {
+        if !new_pin.chars().all(|c| c.is_ascii_digit()) || !PIN_LENGTH.contains(&new_pin.len()) {
+            return Err(AppLockError::InvalidPin);
+        }
+
+        let mut state = self.state();
+        if let Some(hash) = state.config.pin_hash.as_deref() {
+            verify_pin(hash, current_pin.unwrap_or_default())?;
+        }
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default()
+            .hash_password(new_pin.as_bytes(), &salt)
+            .map_err(|error| AppLockError::Hash(error.to_string()))?
+            .to_string();
+        self.write_config(&mut state, |config| config.pin_hash = Some(hash))
+    }
+
+    pub fn disable(&self, pin: &str) -> Result<(), AppLockError> {
+        let mut state = self.state();
+        let hash = state
+            .config
+            .pin_hash
+            .as_deref()
+            .ok_or(AppLockError::NotEnabled)?;
+        verify_pin(hash, pin)?;
+        self.write_config(&mut state, |config| {
+            config.pin_hash = None;
+            config.failed_attempts = 0;
+        })?;
+        state.retry_at = None;
+        state.locked = false;
+        Ok(())
+    }
+
+    pub fn set_idle_timeout(&self, idle_timeout_secs: u64) -> Result<(), AppLockError> {
+        let mut state = self.state();
+        self.write_config(&mut state, |config| {
+            config.idle_timeout_secs = idle_timeout_secs
+        })
+    }
+
+    fn write_config(
+        &self,
+        state: &mut LockState,
+        update: impl FnOnce(&mut AppLockConfig),
+    ) -> Result<(), AppLockError> {
+        let mut config = state.config.clone();
+        update(&mut config);
+        let bytes = serde_json::to_vec_pretty(&config).map_err(AppLockError::Serialize)?;
+        AtomicFileCommit.commit(&self.path, &bytes)?;
+        state.config = config;
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, LockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn engage_if_idle(state: &mut LockState, now: Instant) -> bool {
+    let idle_timeout = Duration::from_secs(state.config.idle_timeout_secs);
+    let idle = state.config.idle_timeout_secs > 0
+        && now.saturating_duration_since(state.last_activity) >= idle_timeout;
+    let engaged = state.config.pin_hash.is_some() && !state.locked && idle;
+    if engaged {
+        state.locked = true;
+        state.locked_at = Some(now);
+    }
+    engaged
+}
+
+fn backoff_after(failed_attempts: u32) -> Option<Duration> {
+    let doublings = failed_attempts.checked_sub(FREE_ATTEMPTS)?;
+    Some(
+        BASE_BACKOFF
+            .checked_mul(2u32.saturating_pow(doublings))
+            .map_or(MAX_BACKOFF, |delay| delay.min(MAX_BACKOFF)),
+    )
+}
+
+fn verify_pin(hash: &str, pin: &str) -> Result<(), AppLockError> {
+    let hash = PasswordHash::new(hash).map_err(|error| AppLockError::Hash(error.to_string()))?;
+    Argon2::default()
+        .verify_password(pin.as_bytes(), &hash)
+        .map_err(|_| AppLockError::WrongPin)
+}
+
+fn read_config(path: &Path) -> Result<AppLockConfig, AppLockError> {
+    match fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(|source| AppLockError::Malformed {
+            path: path.to_path_buf(),
+            source,
+        }),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(AppLockConfig::default()),
+        Err(source) => Err(AppLockError::Read {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
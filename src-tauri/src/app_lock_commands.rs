@@ -0,0 +1,139 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+use upmemo::app_lock::{AppLock, AppLockError, AppLockStatus};
+
+use crate::shutdown::ShutdownState;
+
+pub const APP_LOCKED_EVENT: &str = "app-locked";
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn open_app_lock(app: &AppHandle) -> Result<AppLock, Box<dyn std::error::Error>> {
+    let path = app.path().app_data_dir()?.join("app_lock.json");
+    Ok(AppLock::open(path, Instant::now())?)
+}
+
+pub fn ensure_app_unlocked(app: &AppHandle) -> Result<(), String> {
+    match app.try_state::<AppLock>() {
+        Some(lock) => lock
+            .ensure_unlocked(Instant::now())
+            .map_err(map_app_lock_error),
+        None => Ok(()),
+    }
+}
+
+pub fn ensure_app_writable(app: &AppHandle) -> Result<(), String> {
+    if app
+        .try_state::<ShutdownState>()
+        .is_some_and(|state| state.is_flushing())
+    {
+        return Ok(());
+    }
+    match app.try_state::<AppLock>() {
+        Some(lock) => lock
+            .ensure_writable(Instant::now())
+            .map_err(map_app_lock_error),
+        None => Ok(()),
+    }
+}
+
+pub fn engage_app_lock(app: &AppHandle) {
+    if app
+        .try_state::<AppLock>()
+        .is_some_and(|lock| lock.lock(Instant::now()))
+    {
+        notify_locked(app);
+    }
+}
+
+pub fn spawn_idle_locker(app: &AppHandle) {
+    let app_handle = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(IDLE_POLL_INTERVAL);
+        if app_handle.state::<AppLock>().expire(Instant::now()) {
+            notify_locked(&app_handle);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_app_lock_status(app_handle: AppHandle) -> AppLockStatus {
+    let lock = app_handle.state::<AppLock>();
+    if lock.expire(Instant::now()) {
+        notify_locked(&app_handle);
+    }
+    lock.status()
+}
+
+#[tauri::command]
+pub fn report_app_activity(app_handle: AppHandle) {
+    let lock = app_handle.state::<AppLock>();
+    lock.touch(Instant::now());
+}
+
+#[tauri::command]
+pub fn lock_app(app_handle: AppHandle) {
+    engage_app_lock(&app_handle);
+}
+
+#[tauri::command]
+pub async fn unlock_app(app_handle: AppHandle, pin: String) -> Result<(), String> {
+    app_handle
+        .state::<AppLock>()
+        .unlock(&pin, Instant::now())
+        .map_err(map_app_lock_error)
+}
+
+#[tauri::command]
+pub async fn set_app_lock_pin(
+    app_handle: AppHandle,
+    current_pin: Option<String>,
+    new_pin: String,
+) -> Result<(), String> {
+    ensure_app_unlocked(&app_handle)?;
+    app_handle
+        .state::<AppLock>()
+        .set_pin(current_pin.as_deref(), &new_pin)
+        .map_err(map_app_lock_error)
+}
+
+#[tauri::command]
+pub async fn disable_app_lock(app_handle: AppHandle, pin: String) -> Result<(), String> {
+    ensure_app_unlocked(&app_handle)?;
+    app_handle
+        .state::<AppLock>()
+        .disable(&pin)
+        .map_err(map_app_lock_error)
+}
+
+#[tauri::command]
+pub fn set_app_lock_idle_timeout(
+    app_handle: AppHandle,
+    idle_timeout_secs: u64,
+) -> Result<(), String> {
+    ensure_app_unlocked(&app_handle)?;
+    app_handle
+        .state::<AppLock>()
+        .set_idle_timeout(idle_timeout_secs)
+        .map_err(map_app_lock_error)
+}
+
+fn notify_locked(app: &AppHandle) {
+    if let Err(error) = app.emit(APP_LOCKED_EVENT, ()) {
+        eprintln!("無法通知 UpMemo 已鎖定: {}", error);
+    }
+}
+
+fn map_app_lock_error(error: AppLockError) -> String {
+    match error {
+        AppLockError::Locked => "UpMemo 已鎖定，請輸入 PIN 解鎖".to_string(),
+        AppLockError::WrongPin => "PIN 錯誤".to_string(),
+        AppLockError::TooManyAttempts { retry_after_secs } => {
+            format!("PIN 錯誤次數過多，請於 {retry_after_secs} 秒後再試")
+        }
+        AppLockError::InvalidPin => "PIN 必須為 4 到 12 位數字".to_string(),
+        AppLockError::NotEnabled => "尚未設定 PIN".to_string(),
+        error => format!("App 鎖定操作失敗: {error}"),
+    }
+}
@@ -0,0 +1,444 @@
+use std::fs;
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::clock::get_timestamp;
+use crate::markdown_export::ATTACHMENTS_DIR;
+use crate::memo_import::{insert_by_timestamp, spill_history_overflow};
+use crate::memo_store::{
+    AtomicFileCommit, CommitAdapter, CommitError, MemoCollection, MemoStoreV2,
+};
+
+pub const BUNDLE_EXTENSION: &str = "upmemo";
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+const BUNDLE_MANIFEST: &str = "manifest.json";
+const BUNDLE_STORE: &str = "memo_store.json";
+const SETTINGS_FILES: [&str; 2] = ["font_config.json", "settings.json"];
+const BACKUP_DIR: &str = "bundle";
+const MAX_MANIFEST_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleImportMode {
+    Replace,
+    Merge,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BundleFile {
+    pub filename: String,
+    pub bytes: usize,
+    pub sha256: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub exported_at_unix: u64,
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Bundle {
+    pub manifest: BundleManifest,
+    pub store: MemoStoreV2,
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct BundleMergeSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BundleError {
+    #[error("{operation} failed for {path}: {source}")]
+    Io {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("bundle archive is invalid: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("bundle entry {filename} is not valid JSON: {source}")]
+    Json {
+        filename: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("bundle format version {0} is not supported")]
+    UnsupportedVersion(u32),
+    #[error("bundle is missing {0}")]
+    MissingFile(String),
+    #[error("bundle contains unexpected entry {0}")]
+    UnexpectedFile(String),
+    #[error("checksum mismatch for {0}")]
+    ChecksumMismatch(String),
+    #[error("bundle entry {0} is too large")]
+    TooLarge(String),
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+}
+
+pub fn write_bundle(
+    destination: &Path,
+    store: &MemoStoreV2,
+    data_dir: &Path,
+) -> Result<BundleManifest, BundleError> {
+    let mut files = vec![(BUNDLE_STORE.to_string(), to_json(BUNDLE_STORE, store)?)];
+    for name in SETTINGS_FILES {
+        let path = data_dir.join(name);
+        if path.is_file() {
+            files.push((name.to_string(), read(&path)?));
+        }
+    }
+    files.extend(attachment_files(data_dir)?);
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at_unix: get_timestamp(),
+        files: files
+            .iter()
+            .map(|(filename, bytes)| BundleFile {
+                filename: filename.clone(),
+                bytes: bytes.len(),
+                sha256: sha256_hex(bytes),
+            })
+            .collect(),
+    };
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    writer.start_file(BUNDLE_MANIFEST, options)?;
+    write_entry(
+        &mut writer,
+        &to_json(BUNDLE_MANIFEST, &manifest)?,
+        destination,
+    )?;
+    for (filename, bytes) in &files {
+        writer.start_file(filename.as_str(), options)?;
+        write_entry(&mut writer, bytes, destination)?;
+    }
+    let archive = writer.finish()?.into_inner();
+
+    AtomicFileCommit.commit(destination, &archive)?;
+    Ok(manifest)
+}
+
+pub fn read_bundle(path: &Path) -> Result<Bundle, BundleError> {
+    let file = fs::File::open(path).map_err(|source| BundleError::Io {
+        operation: "open bundle",
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut archive = ZipArchive::new(file)?;
+
+    let manifest_bytes = match archive.by_name(BUNDLE_MANIFEST) {
+        Ok(entry) => read_entry(entry, MAX_MANIFEST_BYTES, path, BUNDLE_MANIFEST)?,
+        Err(zip::result::ZipError::FileNotFound) => {
+            return Err(BundleError::MissingFile(BUNDLE_MANIFEST.into()))
+        }
+        Err(error) => return Err(error.into()),
+    };
+    if manifest_bytes.len() > MAX_MANIFEST_BYTES {
+        return Err(BundleError::TooLarge(BUNDLE_MANIFEST.into()));
+    }
+    let manifest = from_json::<BundleManifest>(BUNDLE_MANIFEST, &manifest_bytes)?;
+    if manifest.format_version != BUNDLE_FORMAT_VERSION {
+        return Err(BundleError::UnsupportedVersion(manifest.format_version));
+    }
+
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index)?;
+        if entry.is_dir() || entry.name() == BUNDLE_MANIFEST {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let listed = manifest
+            .files
+            .iter()
+            .find(|file| file.filename == name)
+            .filter(|_| is_bundle_entry(&name))
+            .filter(|_| !entries.iter().any(|(seen, _)| *seen == name))
+            .ok_or_else(|| BundleError::UnexpectedFile(name.clone()))?;
+        let bytes = read_entry(entry, listed.bytes, path, &name)?;
+        if listed.bytes != bytes.len() || listed.sha256 != sha256_hex(&bytes) {
+            return Err(BundleError::ChecksumMismatch(name));
+        }
+        entries.push((name, bytes));
+    }
+    if let Some(missing) = manifest
+        .files
+        .iter()
+        .find(|file| !entries.iter().any(|(name, _)| *name == file.filename))
+    {
+        return Err(BundleError::MissingFile(missing.filename.clone()));
+    }
+
+    let position = entries
+        .iter()
+        .position(|(name, _)| name == BUNDLE_STORE)
+        .ok_or_else(|| BundleError::MissingFile(BUNDLE_STORE.into()))?;
+    let (_, store_bytes) = entries.remove(position);
+    let store = from_json(BUNDLE_STORE, &store_bytes)?;
+
+    Ok(Bundle {
+        manifest,
+        store,
+        files: entries,
+    })
+}
+
+pub fn back_up_before_import(
+    data_dir: &Path,
+    store: &MemoStoreV2,
+    committer: &impl CommitAdapter,
+) -> Result<PathBuf, BundleError> {
+    let backup = data_dir
+        .join("backups")
+        .join(BACKUP_DIR)
+        .join(format!("bundle-import-{}", get_timestamp()));
+
+    let mut files = vec![
+        (
+            "history.json".to_string(),
+            to_json("history.json", &store.history)?,
+        ),
+        (
+            "archive.json".to_string(),
+            to_json("archive.json", &store.archive)?,
+        ),
+        (
+            "trash.json".to_string(),
+            to_json("trash.json", &store.trash)?,
+        ),
+        (
+            "note.txt".to_string(),
+            store.current_content.clone().into_bytes(),
+        ),
+    ];
+    for name in SETTINGS_FILES {
+        let path = data_dir.join(name);
+        if path.is_file() {
+            files.push((name.to_string(), read(&path)?));
+        }
+    }
+    files.extend(attachment_files(data_dir)?);
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at_unix: get_timestamp(),
+        files: files
+            .iter()
+            .map(|(filename, bytes)| BundleFile {
+                filename: filename.clone(),
+                bytes: bytes.len(),
+                sha256: sha256_hex(bytes),
+            })
+            .collect(),
+    };
+    for (filename, bytes) in &files {
+        committer.commit(&backup.join(filename), bytes)?;
+    }
+    committer.commit(
+        &backup.join(BUNDLE_MANIFEST),
+        &to_json(BUNDLE_MANIFEST, &manifest)?,
+    )?;
+    Ok(backup)
+}
+
+pub fn apply_bundle_store(
+    store: &mut MemoStoreV2,
+    mut incoming: MemoStoreV2,
+    mode: BundleImportMode,
+) -> BundleMergeSummary {
+    match mode {
+        BundleImportMode::Replace => {
+            incoming.encoding = store.encoding;
+            let summary = BundleMergeSummary {
+                added: incoming.history.len() + incoming.archive.len() + incoming.trash.len(),
+                ..BundleMergeSummary::default()
+            };
+            *store = incoming;
+            summary
+        }
+        BundleImportMode::Merge => merge_by_id(store, incoming),
+    }
+}
+
+pub fn restore_bundle_files(
+    data_dir: &Path,
+    files: &[(String, Vec<u8>)],
+    mode: BundleImportMode,
+) -> Result<Vec<String>, BundleError> {
+    let mut restored = Vec::new();
+    for (name, bytes) in files {
+        let target = data_dir.join(name);
+        let is_setting = SETTINGS_FILES.contains(&name.as_str());
+        let overwrite = is_setting && mode == BundleImportMode::Replace;
+        if target.exists() && !overwrite {
+            continue;
+        }
+        AtomicFileCommit.commit(&target, bytes)?;
+        restored.push(name.clone());
+    }
+    Ok(restored)
+}
+
+fn merge_by_id(store: &mut MemoStoreV2, incoming: MemoStoreV2) -> BundleMergeSummary {
+    let mut summary = BundleMergeSummary::default();
+    let incoming_collections = [
+        (incoming.history, MemoCollection::History),
+        (incoming.archive, MemoCollection::Archive),
+        (incoming.trash, MemoCollection::Trash),
+    ];
+
+    for (entries, target) in incoming_collections {
+        for entry in entries {
+            let existing = [&store.history, &store.archive, &store.trash]
+                .into_iter()
+                .flatten()
+                .find(|existing| existing.id == entry.id)
+                .map(|existing| existing.timestamp);
+            match existing {
+                Some(timestamp) if timestamp >= entry.timestamp => {
+                    summary.unchanged += 1;
+                    continue;
+                }
+                Some(_) => summary.updated += 1,
+                None => summary.added += 1,
+            }
+
+            for collection in [&mut store.history, &mut store.archive, &mut store.trash] {
+                collection.retain(|existing| existing.id != entry.id);
+            }
+            let collection = match target {
+                MemoCollection::History => &mut store.history,
+                MemoCollection::Archive => &mut store.archive,
+                MemoCollection::Trash => &mut store.trash,
+            };
+            insert_by_timestamp(collection, entry);
+        }
+    }
+
+    spill_history_overflow(store);
+    summary
+}
+
+fn is_bundle_entry(name: &str) -> bool {
+    if name == BUNDLE_STORE || SETTINGS_FILES.contains(&name) {
+        return true;
+    }
+    name.strip_prefix(ATTACHMENTS_DIR)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .is_some_and(|file| {
+            !file.is_empty()
+                && !file.starts_with('.')
+                && file.chars().all(|character| {
+                    character.is_ascii_alphanumeric() || matches!(character, '.' | '-' | '_')
+                })
+        })
+}
+
+fn attachment_files(data_dir: &Path) -> Result<Vec<(String, Vec<u8>)>, BundleError> {
+    let mut files = Vec::new();
+    for path in attachment_paths(&data_dir.join(ATTACHMENTS_DIR))? {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        files.push((format!("{ATTACHMENTS_DIR}/{name}"), read(&path)?));
+    }
+    Ok(files)
+}
+
+fn attachment_paths(directory: &Path) -> Result<Vec<PathBuf>, BundleError> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(source) if source.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => {
+            return Err(BundleError::Io {
+                operation: "read attachments directory",
+                path: directory.to_path_buf(),
+                source,
+            })
+        }
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| is_bundle_entry(&format!("{ATTACHMENTS_DIR}/{name}")))
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn write_entry(
+    writer: &mut ZipWriter<Cursor<Vec<u8>>>,
+    bytes: &[u8],
+    destination: &Path,
+) -> Result<(), BundleError> {
+    writer.write_all(bytes).map_err(|source| BundleError::Io {
+        operation: "write bundle entry",
+        path: destination.to_path_buf(),
+        source,
+    })
+}
+
+fn read_entry(
+    entry: impl Read,
+    limit: usize,
+    bundle: &Path,
+    name: &str,
+) -> Result<Vec<u8>, BundleError> {
+    let mut bytes = Vec::new();
+    entry
+        .take(limit as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|source| BundleError::Io {
+            operation: "read bundle entry",
+            path: bundle.join(name),
+            source,
+        })?;
+    Ok(bytes)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, BundleError> {
+    fs::read(path).map_err(|source| BundleError::Io {
+        operation: "read file",
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn to_json(filename: &str, value: &impl Serialize) -> Result<Vec<u8>, BundleError> {
+    serde_json::to_vec_pretty(value).map_err(|source| BundleError::Json {
+        filename: filename.to_string(),
+        source,
+    })
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(filename: &str, bytes: &[u8]) -> Result<T, BundleError> {
+    serde_json::from_slice(bytes).map_err(|source| BundleError::Json {
+        filename: filename.to_string(),
+        source,
+    })
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
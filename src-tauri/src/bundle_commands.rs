@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+use upmemo::bundle::{
+    apply_bundle_store, back_up_before_import, read_bundle, restore_bundle_files, write_bundle,
+    BundleError, BundleImportMode, BundleManifest, BundleMergeSummary, BUNDLE_EXTENSION,
+};
+use upmemo::private_memo::PrivateMemoSessions;
+
+use crate::memo_store_state::memo_store;
+use crate::note_commands::map_memo_store_error;
+
+const SETTINGS_STORE: &str = "settings.json";
+
+#[derive(Serialize)]
+pub struct BundleImportReport {
+    backup_path: PathBuf,
+    summary: BundleMergeSummary,
+    restored_files: Vec<String>,
+}
+
+#[tauri::command]
+pub fn export_bundle(app_handle: AppHandle, path: String) -> Result<BundleManifest, String> {
+    let mut destination = PathBuf::from(path);
+    if destination
+        .extension()
+        .is_none_or(|extension| extension != BUNDLE_EXTENSION)
+    {
+        destination.set_extension(BUNDLE_EXTENSION);
+    }
+
+    let repository = memo_store(&app_handle)?;
+    let store = repository.snapshot().map_err(map_memo_store_error)?;
+    write_bundle(&destination, &store, &data_dir(&app_handle)?).map_err(map_bundle_error)
+}
+
+#[tauri::command]
+pub fn import_bundle(
+    app_handle: AppHandle,
+    path: String,
+    mode: BundleImportMode,
+) -> Result<BundleImportReport, String> {
+    let bundle = read_bundle(&PathBuf::from(path)).map_err(map_bundle_error)?;
+    let data_dir = data_dir(&app_handle)?;
+    let repository = memo_store(&app_handle)?;
+
+    let current = repository.snapshot().map_err(map_memo_store_error)?;
+    let backup_path = back_up_before_import(&data_dir, &current, repository.committer())
+        .map_err(map_bundle_error)?;
+
+    let sessions = app_handle.state::<PrivateMemoSessions>();
+    for entry in [
+        &bundle.store.history,
+        &bundle.store.archive,
+        &bundle.store.trash,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        sessions.relock(&entry.id);
+    }
+
+    let summary = repository
+        .transact(|store| Ok(apply_bundle_store(store, bundle.store, mode)))
+        .map_err(map_memo_store_error)?;
+    repository.flush().map_err(map_memo_store_error)?;
+
+    let restored_files =
+        restore_bundle_files(&data_dir, &bundle.files, mode).map_err(map_bundle_error)?;
+    if restored_files.iter().any(|name| name == SETTINGS_STORE) {
+        if let Some(settings) = app_handle.get_store(SETTINGS_STORE) {
+            settings
+                .reload()
+                .map_err(|error| format!("無法重新載入設定: {error}"))?;
+        }
+    }
+
+    Ok(BundleImportReport {
+        backup_path,
+        summary,
+        restored_files,
+    })
+}
+
+fn data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))
+}
+
+fn map_bundle_error(error: BundleError) -> String {
+    match error {
+        BundleError::ChecksumMismatch(filename) => format!("備份檔已損毀：{filename} 校驗失敗"),
+        error => format!("備份檔處理失敗: {error}"),
+    }
+}
@@ -1,48 +1,52 @@
-use upmemo::models::AppState;
-use tauri::Manager;
+use crate::draft_commands;
+use crate::memo_store_state::memo_store;
+use crate::note_commands::map_memo_store_error;
+use crate::private_memo_commands::{readable_content, reveal_entries};
 
-use upmemo::memo_store::{MemoCollection, MemoStoreError, MemoStoreRepository};
+use tauri::Manager;
+use upmemo::memo_store::MemoCollection;
 use upmemo::models::MemoEntry;
+use upmemo::private_memo::PrivateMemoSessions;
 
 #[tauri::command]
 pub fn get_history(app_handle: tauri::AppHandle) -> Result<Vec<MemoEntry>, String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
+    let repository = memo_store(&app_handle)?;
 
     repository
         .snapshot()
-        .map(|store| store.history)
+        .map(|store| reveal_entries(&app_handle, store.history))
         .map_err(map_memo_store_error)
 }
 
 #[tauri::command]
 pub fn load_history_item(app_handle: tauri::AppHandle, id: String) -> Result<String, String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
-    let store = repository
-        .snapshot()
-        .map_err(map_memo_store_error)?;
-
-    let content = store
-        .history
-        .iter()
-        .find(|entry| entry.id == id)
-        .map(|entry| entry.content.clone())
+    let repository = memo_store(&app_handle)?;
+    let sessions = app_handle.state::<PrivateMemoSessions>();
+    let content = repository
+        .transact(|store| {
+            let Some(content) = store
+                .history
+                .iter()
+                .find(|entry| entry.id == id)
+                .map(|entry| entry.content.clone())
+            else {
+                return Ok(None);
+            };
+            let readable = readable_content(&sessions, &id, &content)?;
+            store.current_memo_id = Some(id.clone());
+            store.current_content = content;
+            Ok(Some(readable))
+        })
+        .map_err(map_memo_store_error)?
         .ok_or_else(|| "找不到該歷史記錄".to_string())?;
-
-    let state = app_handle.state::<AppState>();
-    match state.current_memo_id.lock() {
-        Ok(mut current_id) => {
-            *current_id = Some(id);
-        }
-        Err(_) => return Err("無法鎖定當前便條狀態".to_string()),
-    }
+    draft_commands::switch_draft_session(&app_handle)?;
 
     Ok(content)
 }
 
 #[tauri::command]
 pub fn delete_history_item(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
-    app_handle
-        .state::<MemoStoreRepository>()
+    memo_store(&app_handle)?
         .move_entry(&id, MemoCollection::History, MemoCollection::Trash)
         .map(|_| ())
         .map_err(map_memo_store_error)
@@ -50,8 +54,7 @@ pub fn delete_history_item(app_handle: tauri::AppHandle, id: String) -> Result<(
 
 #[tauri::command]
 pub fn archive_history_item(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
-    app_handle
-        .state::<MemoStoreRepository>()
+    memo_store(&app_handle)?
         .move_entry(&id, MemoCollection::History, MemoCollection::Archive)
         .map(|_| ())
         .map_err(map_memo_store_error)
@@ -59,18 +62,17 @@ pub fn archive_history_item(app_handle: tauri::AppHandle, id: String) -> Result<
 
 #[tauri::command]
 pub fn get_trash(app_handle: tauri::AppHandle) -> Result<Vec<MemoEntry>, String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
+    let repository = memo_store(&app_handle)?;
 
     repository
         .snapshot()
-        .map(|store| store.trash)
+        .map(|store| reveal_entries(&app_handle, store.trash))
         .map_err(map_memo_store_error)
 }
 
 #[tauri::command]
 pub fn restore_from_trash(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
-    app_handle
-        .state::<MemoStoreRepository>()
+    memo_store(&app_handle)?
         .move_entry(&id, MemoCollection::Trash, MemoCollection::History)
         .map(|_| ())
         .map_err(map_memo_store_error)
@@ -81,7 +83,7 @@ pub fn permanently_delete_trash_item(
     app_handle: tauri::AppHandle,
     id: String,
 ) -> Result<(), String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
+    let repository = memo_store(&app_handle)?;
 
     repository
         .transact(|store| {
@@ -93,7 +95,7 @@ pub fn permanently_delete_trash_item(
 
 #[tauri::command]
 pub fn empty_trash(app_handle: tauri::AppHandle) -> Result<(), String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
+    let repository = memo_store(&app_handle)?;
 
     repository
         .transact(|store| {
@@ -105,18 +107,17 @@ pub fn empty_trash(app_handle: tauri::AppHandle) -> Result<(), String> {
 
 #[tauri::command]
 pub fn get_archive(app_handle: tauri::AppHandle) -> Result<Vec<MemoEntry>, String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
+    let repository = memo_store(&app_handle)?;
 
     repository
         .snapshot()
-        .map(|store| store.archive)
+        .map(|store| reveal_entries(&app_handle, store.archive))
         .map_err(map_memo_store_error)
 }
 
 #[tauri::command]
 pub fn restore_from_archive(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
-    app_handle
-        .state::<MemoStoreRepository>()
+    memo_store(&app_handle)?
         .move_entry(&id, MemoCollection::Archive, MemoCollection::History)
         .map(|_| ())
         .map_err(map_memo_store_error)
@@ -127,7 +128,7 @@ pub fn permanently_delete_archive_item(
     app_handle: tauri::AppHandle,
     id: String,
 ) -> Result<(), String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
+    let repository = memo_store(&app_handle)?;
 
     repository
         .transact(|store| {
@@ -140,7 +141,3 @@ pub fn permanently_delete_archive_item(
 fn retain_without_id(entries: &mut Vec<MemoEntry>, id: &str) {
     entries.retain(|entry| entry.id != id);
 }
-
-fn map_memo_store_error(error: MemoStoreError) -> String {
-    format!("memo_store 操作失敗: {error}")
-}
@@ -0,0 +1,199 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager, State};
+use upmemo::draft_journal::{DraftJournal, DraftJournalError, RecoveredDraft};
+use upmemo::edit_session::EditSessionToken;
+use upmemo::memo_store::MemoStoreV2;
+use upmemo::private_memo;
+
+use crate::app_lock_commands;
+use crate::clock::get_timestamp;
+use crate::memo_store_state::memo_store;
+use crate::note_commands::{map_memo_store_error, update_or_save_to_history_with_id};
+
+pub struct DraftState {
+    journal: DraftJournal,
+    active_token: Mutex<EditSessionToken>,
+    recovered: Mutex<Vec<RecoveredDraft>>,
+}
+
+impl DraftState {
+    pub fn open(journal: DraftJournal, store: &MemoStoreV2) -> Result<Self, DraftJournalError> {
+        let mut recovered = Vec::new();
+        for draft in journal.recover()? {
+            if draft.is_newer_than(store) {
+                recovered.push(draft);
+            } else {
+                journal.discard(&draft.token)?;
+            }
+        }
+
+        Ok(Self {
+            journal,
+            active_token: Mutex::new(EditSessionToken::new()),
+            recovered: Mutex::new(recovered),
+        })
+    }
+
+    fn active_token(&self) -> Result<EditSessionToken, String> {
+        self.active_token
+            .lock()
+            .map(|token| token.clone())
+            .map_err(|_| "無法鎖定草稿工作階段".to_string())
+    }
+}
+
+pub fn switch_draft_session(app: &AppHandle) -> Result<(), String> {
+    let Some(state) = app.try_state::<DraftState>() else {
+        return Ok(());
+    };
+    let mut token = state
+        .active_token
+        .lock()
+        .map_err(|_| "無法鎖定草稿工作階段".to_string())?;
+    let previous = std::mem::replace(&mut *token, EditSessionToken::new());
+    state
+        .journal
+        .discard(&previous)
+        .map_err(map_draft_journal_error)
+}
+
+pub fn compact_active_draft(app: &AppHandle) {
+    let Some(state) = app.try_state::<DraftState>() else {
+        return;
+    };
+
+    if let Ok(token) = state.active_token() {
+        if let Err(error) = state.journal.compact(&token) {
+            eprintln!("草稿日誌壓縮失敗: {}", error);
+        }
+    }
+}
+
+pub fn discard_active_draft(app: &AppHandle) {
+    let Some(state) = app.try_state::<DraftState>() else {
+        return;
+    };
+
+    if let Ok(token) = state.active_token() {
+        if let Err(error) = state.journal.discard(&token) {
+            eprintln!("草稿日誌刪除失敗: {}", error);
+        }
+    }
+}
+
+pub fn purge_memo_drafts(
+    app: &AppHandle,
+    memo_id: &str,
+    contents: &[String],
+) -> Result<Vec<PathBuf>, String> {
+    let Some(state) = app.try_state::<DraftState>() else {
+        return Ok(Vec::new());
+    };
+    state
+        .journal
+        .purge_memo(memo_id, contents)
+        .map_err(map_draft_journal_error)
+}
+
+pub fn purge_all_drafts(app: &AppHandle) -> Result<Vec<PathBuf>, String> {
+    let Some(state) = app.try_state::<DraftState>() else {
+        return Ok(Vec::new());
+    };
+    if let Ok(mut recovered) = state.recovered.lock() {
+        recovered.clear();
+    }
+    state.journal.purge_all().map_err(map_draft_journal_error)
+}
+
+#[tauri::command]
+pub fn record_draft(app_handle: AppHandle, content: String) -> Result<(), String> {
+    let repository = memo_store(&app_handle)?;
+    if repository.committer().key().is_some() {
+        return Ok(());
+    }
+    let store = repository.snapshot().map_err(map_memo_store_error)?;
+    if private_memo::is_private(&store.current_content) {
+        return Ok(());
+    }
+    let memo_id = store.current_memo_id;
+    let Some(state) = app_handle.try_state::<DraftState>() else {
+        return Ok(());
+    };
+    let token = state.active_token()?;
+
+    state
+        .journal
+        .append(&token, memo_id.as_deref(), &content)
+        .map_err(map_draft_journal_error)
+}
+
+#[tauri::command]
+pub fn get_recoverable_draft(
+    app_handle: AppHandle,
+    state: State<'_, DraftState>,
+) -> Result<Option<RecoveredDraft>, String> {
+    app_lock_commands::ensure_app_unlocked(&app_handle)?;
+    state
+        .recovered
+        .lock()
+        .map(|recovered| recovered.first().cloned())
+        .map_err(|_| "無法鎖定草稿復原狀態".to_string())
+}
+
+#[tauri::command]
+pub fn resolve_recovered_drafts(
+    app_handle: AppHandle,
+    recover: bool,
+) -> Result<Option<String>, String> {
+    app_lock_commands::ensure_app_unlocked(&app_handle)?;
+    memo_store(&app_handle)?;
+    let Some(state) = app_handle.try_state::<DraftState>() else {
+        return Ok(None);
+    };
+    let recovered = match state.recovered.lock() {
+        Ok(mut recovered) => std::mem::take(&mut *recovered),
+        Err(_) => return Err("無法鎖定草稿復原狀態".to_string()),
+    };
+
+    let restored = match recovered.first() {
+        Some(draft) if recover => Some(restore_draft(&app_handle, draft)?),
+        _ => None,
+    };
+
+    for draft in &recovered {
+        state
+            .journal
+            .discard(&draft.token)
+            .map_err(map_draft_journal_error)?;
+    }
+
+    Ok(restored)
+}
+
+fn restore_draft(app_handle: &AppHandle, draft: &RecoveredDraft) -> Result<String, String> {
+    let memo_id = draft
+        .memo_id
+        .clone()
+        .unwrap_or_else(|| get_timestamp().to_string());
+
+    memo_store(app_handle)?
+        .transact(|store| {
+            store.current_content = draft.content.clone();
+            store.current_memo_id = Some(memo_id.clone());
+
+            if !draft.content.trim().is_empty() {
+                update_or_save_to_history_with_id(store, memo_id.clone(), &draft.content)?;
+            }
+
+            Ok(())
+        })
+        .map_err(map_memo_store_error)?;
+
+    Ok(draft.content.clone())
+}
+
+fn map_draft_journal_error(error: DraftJournalError) -> String {
+    format!("草稿日誌操作失敗: {error}")
+}
@@ -0,0 +1,302 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::edit_session::EditSessionToken;
+use crate::memo_store::MemoStoreV2;
+
+const JOURNAL_EXTENSION: &str = "jsonl";
+
+pub struct DraftJournal {
+    directory: PathBuf,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct DraftRecord {
+    memo_id: Option<String>,
+    written_at_millis: u64,
+    content: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct RecoveredDraft {
+    #[serde(skip_serializing)]
+    pub token: EditSessionToken,
+    pub memo_id: Option<String>,
+    pub written_at_millis: u64,
+    pub content: String,
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum DraftJournalError {
+    #[error("{operation} failed for {path}: {source}")]
+    Io {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("failed to serialize draft record: {0}")]
+    Serialize(#[source] serde_json::Error),
+}
+
+impl DraftJournal {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    pub fn append(
+        &self,
+        token: &EditSessionToken,
+        memo_id: Option<&str>,
+        content: &str,
+    ) -> Result<(), DraftJournalError> {
+        let record = DraftRecord {
+            memo_id: memo_id.map(str::to_string),
+            written_at_millis: now_unix_millis(),
+            content: content.to_string(),
+        };
+        let mut line = serde_json::to_vec(&record).map_err(DraftJournalError::Serialize)?;
+        line.push(b'\n');
+
+        fs::create_dir_all(&self.directory).map_err(|source| DraftJournalError::Io {
+            operation: "create draft directory",
+            path: self.directory.clone(),
+            source,
+        })?;
+        let path = self.journal_path(token);
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&line))
+            .map_err(|source| DraftJournalError::Io {
+                operation: "append draft record",
+                path,
+                source,
+            })
+    }
+
+    pub fn recover(&self) -> Result<Vec<RecoveredDraft>, DraftJournalError> {
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(source) if source.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => {
+                return Err(DraftJournalError::Io {
+                    operation: "read draft directory",
+                    path: self.directory.clone(),
+                    source,
+                });
+            }
+        };
+
+        let mut drafts = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|source| DraftJournalError::Io {
+                    operation: "read draft directory entry",
+                    path: self.directory.clone(),
+                    source,
+                })?
+                .path();
+            let Some(token) = token_from_path(&path) else {
+                continue;
+            };
+            let bytes = fs::read(&path).map_err(|source| DraftJournalError::Io {
+                operation: "read draft journal",
+                path: path.clone(),
+                source,
+            })?;
+            if let Some(record) = last_complete_record(&bytes) {
+                drafts.push(RecoveredDraft {
+                    token,
+                    memo_id: record.memo_id,
+                    written_at_millis: record.written_at_millis,
+                    content: record.content,
+                });
+            }
+        }
+        drafts.sort_by_key(|draft| std::cmp::Reverse(draft.written_at_millis));
+        Ok(drafts)
+    }
+
+    pub fn compact(&self, token: &EditSessionToken) -> Result<(), DraftJournalError> {
+        let path = self.journal_path(token);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(source) if source.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(source) => {
+                return Err(DraftJournalError::Io {
+                    operation: "read draft journal",
+                    path,
+                    source,
+                });
+            }
+        };
+        let Some(record) = last_complete_record(&bytes) else {
+            return self.discard(token);
+        };
+        let mut line = serde_json::to_vec(&record).map_err(DraftJournalError::Serialize)?;
+        line.push(b'\n');
+
+        let mut temporary =
+            NamedTempFile::new_in(&self.directory).map_err(|source| DraftJournalError::Io {
+                operation: "create temporary draft journal",
+                path: self.directory.clone(),
+                source,
+            })?;
+        temporary
+            .write_all(&line)
+            .map_err(|source| DraftJournalError::Io {
+                operation: "write temporary draft journal",
+                path: temporary.path().to_path_buf(),
+                source,
+            })?;
+        temporary
+            .persist(&path)
+            .map(|_| ())
+            .map_err(|error| DraftJournalError::Io {
+                operation: "replace draft journal",
+                path,
+                source: error.error,
+            })
+    }
+
+    pub fn discard(&self, token: &EditSessionToken) -> Result<(), DraftJournalError> {
+        let path = self.journal_path(token);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(source) if source.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(DraftJournalError::Io {
+                operation: "remove draft journal",
+                path,
+                source,
+            }),
+        }
+    }
+
+    pub fn purge_memo(
+        &self,
+        memo_id: &str,
+        contents: &[String],
+    ) -> Result<Vec<PathBuf>, DraftJournalError> {
+        let mut purged = Vec::new();
+        for draft in self.recover()? {
+            let path = self.journal_path(&draft.token);
+            let bytes = fs::read(&path).map_err(|source| DraftJournalError::Io {
+                operation: "read draft journal",
+                path: path.clone(),
+                source,
+            })?;
+            let mentions_memo = bytes
+                .split(|byte| *byte == b'\n')
+                .filter_map(|line| serde_json::from_slice::<DraftRecord>(line).ok())
+                .any(|record| match record.memo_id.as_deref() {
+                    Some(id) => id == memo_id,
+                    None => shares_content(&record.content, contents),
+                });
+            if mentions_memo {
+                self.discard(&draft.token)?;
+                purged.push(path);
+            }
+        }
+        Ok(purged)
+    }
+
+    pub fn purge_all(&self) -> Result<Vec<PathBuf>, DraftJournalError> {
+        let entries = match fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(source) if source.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => {
+                return Err(DraftJournalError::Io {
+                    operation: "read draft directory",
+                    path: self.directory.clone(),
+                    source,
+                });
+            }
+        };
+
+        let mut purged = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|source| DraftJournalError::Io {
+                    operation: "read draft directory entry",
+                    path: self.directory.clone(),
+                    source,
+                })?
+                .path();
+            if let Some(token) = token_from_path(&path) {
+                self.discard(&token)?;
+                purged.push(path);
+            }
+        }
+        Ok(purged)
+    }
+
+    fn journal_path(&self, token: &EditSessionToken) -> PathBuf {
+        self.directory
+            .join(format!("{}.{JOURNAL_EXTENSION}", token.as_uuid()))
+    }
+}
+
+impl RecoveredDraft {
+    pub fn is_newer_than(&self, store: &MemoStoreV2) -> bool {
+        if self.memo_id.is_some() && self.memo_id == store.current_memo_id {
+            return self.content != store.current_content;
+        }
+
+        let mut committed = store
+            .history
+            .iter()
+            .chain(&store.archive)
+            .chain(&store.trash);
+        let Some(memo_id) = self.memo_id.as_deref() else {
+            return !self.content.trim().is_empty()
+                && self.content != store.current_content
+                && !committed.any(|entry| entry.content == self.content);
+        };
+        match committed.find(|entry| entry.id == memo_id) {
+            Some(entry) => {
+                entry.content != self.content && self.written_at_millis / 1000 >= entry.timestamp
+            }
+            None => !self.content.trim().is_empty(),
+        }
+    }
+}
+
+fn shares_content(draft: &str, contents: &[String]) -> bool {
+    let draft = draft.trim();
+    !draft.is_empty() && contents.iter().any(|content| content.trim() == draft)
+}
+
+fn token_from_path(path: &Path) -> Option<EditSessionToken> {
+    if path.extension()? != JOURNAL_EXTENSION {
+        return None;
+    }
+
+    let stem = path.file_stem()?.to_str()?;
+    Uuid::parse_str(stem).ok().map(EditSessionToken::from_uuid)
+}
+
+fn last_complete_record(bytes: &[u8]) -> Option<DraftRecord> {
+    bytes
+        .split(|byte| *byte == b'\n')
+        .rev()
+        .find_map(|line| serde_json::from_slice(line).ok())
+}
+
+fn now_unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or(0)
+}
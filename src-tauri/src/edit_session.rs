@@ -23,6 +23,14 @@ impl Default for MemoId {
 pub struct EditSessionToken(Uuid);
 
 impl EditSessionToken {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    pub(crate) fn from_uuid(token: Uuid) -> Self {
+        Self(token)
+    }
+
     fn issue(memo_id: &MemoId) -> Self {
         loop {
             let token = Uuid::new_v4();
@@ -37,6 +45,12 @@ impl EditSessionToken {
     }
 }
 
+impl Default for EditSessionToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct EditSession {
     memo_id: MemoId,
@@ -0,0 +1,508 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use md5::Md5;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use sha2::{Digest, Sha256};
+
+use crate::markdown_export::ATTACHMENTS_DIR;
+use crate::memo_import::{
+    append_tags, end_block, failure, modified_secs, parse_iso8601, resolve_xml_reference,
+    start_line, tidy, ImportFailureKind, ImportedMemo, PreparedImport,
+};
+use crate::memo_store::{CommitAdapter, MemoCollection};
+use crate::models::MemoEntry;
+
+const ENEX_EXTENSION: &str = "enex";
+const ATTACHMENT_MARKER: &str = "📎";
+const ENCRYPTED_PLACEHOLDER: &str = "[加密內容]";
+const INLINE_IMAGE_EXTENSIONS: [&str; 3] = [".png", ".jpg", ".gif"];
+const SKIPPED_ELEMENTS: [&str; 4] = ["en-crypt", "head", "script", "style"];
+
+#[derive(Debug, Default)]
+struct EnexNote {
+    title: String,
+    content: String,
+    created: Option<u64>,
+    updated: Option<u64>,
+    tags: Vec<String>,
+    media: Vec<EnexMedia>,
+}
+
+#[derive(Debug, Default)]
+struct PendingResource {
+    data: String,
+    mime: String,
+    file_name: Option<String>,
+}
+
+#[derive(Debug)]
+struct EnexResource {
+    mime: String,
+    file_name: Option<String>,
+    bytes: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct EnexMedia {
+    hash: String,
+    markup: String,
+}
+
+struct ImportTarget<'a, C> {
+    attachments: &'a Path,
+    committer: &'a C,
+}
+
+pub fn prepare_enex_import(
+    source: &Path,
+    attachments: &Path,
+    committer: &impl CommitAdapter,
+) -> PreparedImport {
+    let mut prepared = PreparedImport::default();
+    let paths = if source.is_dir() {
+        match fs::read_dir(source) {
+            Ok(entries) => {
+                let mut paths: Vec<PathBuf> = entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| {
+                        path.is_file()
+                            && path.extension().is_some_and(|extension| {
+                                extension.eq_ignore_ascii_case(ENEX_EXTENSION)
+                            })
+                    })
+                    .collect();
+                paths.sort();
+                paths
+            }
+            Err(error) => {
+                prepared
+                    .failures
+                    .push(failure(ImportFailureKind::Unreadable, source, error));
+                return prepared;
+            }
+        }
+    } else {
+        vec![source.to_path_buf()]
+    };
+
+    let target = ImportTarget {
+        attachments,
+        committer,
+    };
+    for path in paths {
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(error) => {
+                prepared
+                    .failures
+                    .push(failure(ImportFailureKind::Unreadable, &path, error));
+                continue;
+            }
+        };
+        if let Err(error) = stream_enex_file(&path, file, &target, &mut prepared) {
+            prepared
+                .failures
+                .push(failure(ImportFailureKind::InvalidXml, &path, error));
+        }
+    }
+
+    prepared
+}
+
+fn stream_enex_file<C: CommitAdapter>(
+    path: &Path,
+    file: File,
+    target: &ImportTarget<'_, C>,
+    prepared: &mut PreparedImport,
+) -> Result<(), quick_xml::Error> {
+    let notebook = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    let mut buffer = Vec::new();
+    let mut elements: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut note: Option<EnexNote> = None;
+    let mut note_index = 0;
+    let mut resource: Option<PendingResource> = None;
+
+    loop {
+        buffer.clear();
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(element) => {
+                let name = element_name(&element);
+                match name.as_str() {
+                    "note" => note = Some(EnexNote::default()),
+                    "resource" if note.is_some() => resource = Some(PendingResource::default()),
+                    _ => {}
+                }
+                field.clear();
+                elements.push(name);
+            }
+            Event::Text(text) => field.push_str(&text.xml_content()?),
+            Event::CData(text) => field.push_str(&text.decode()?),
+            Event::GeneralRef(reference) => field.push_str(&resolve_xml_reference(&reference)?),
+            Event::End(_) => {
+                let name = elements.pop().unwrap_or_default();
+                let parent = elements.last().map(String::as_str);
+                let value = std::mem::take(&mut field);
+                if let Some(pending) = resource.as_mut() {
+                    match name.as_str() {
+                        "data" => pending.data = value,
+                        "mime" => pending.mime = value.trim().to_ascii_lowercase(),
+                        "file-name" => pending.file_name = Some(value.trim().to_string()),
+                        "resource" => {
+                            let pending = resource.take().unwrap_or_default();
+                            let encoded: String = pending
+                                .data
+                                .chars()
+                                .filter(|character| !character.is_whitespace())
+                                .collect();
+                            match STANDARD.decode(encoded) {
+                                Ok(bytes) => {
+                                    let resource = EnexResource {
+                                        mime: pending.mime,
+                                        file_name: pending.file_name,
+                                        bytes,
+                                    };
+                                    if let (Some(note), Some(media)) =
+                                        (note.as_mut(), store_resource(&resource, target, prepared))
+                                    {
+                                        note.media.push(media);
+                                    }
+                                }
+                                Err(error) => prepared.failures.push(failure(
+                                    ImportFailureKind::InvalidResource,
+                                    path,
+                                    error,
+                                )),
+                            }
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+                let Some(current) = note.as_mut() else {
+                    continue;
+                };
+                match name.as_str() {
+                    "title" if parent == Some("note") => current.title = value.trim().to_string(),
+                    "content" => current.content = value,
+                    "created" => current.created = parse_iso8601(&value),
+                    "updated" => current.updated = parse_iso8601(&value),
+                    "tag" if !value.trim().is_empty() => {
+                        current.tags.push(value.trim().to_string())
+                    }
+                    "note" => {
+                        if let Some(finished) = note.take() {
+                            finish_note(path, &notebook, note_index, finished, prepared);
+                            note_index += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn store_resource<C: CommitAdapter>(
+    resource: &EnexResource,
+    target: &ImportTarget<'_, C>,
+    prepared: &mut PreparedImport,
+) -> Option<EnexMedia> {
+    let name = attachment_name(resource);
+    let destination = target.attachments.join(&name);
+    if !destination.exists() {
+        if let Err(error) = target.committer.commit(&destination, &resource.bytes) {
+            prepared.failures.push(failure(
+                ImportFailureKind::AttachmentNotWritten,
+                &destination,
+                error,
+            ));
+            return None;
+        }
+    }
+
+    let markup = if INLINE_IMAGE_EXTENSIONS
+        .iter()
+        .any(|extension| name.ends_with(extension))
+    {
+        format!("<img data-upmemo-image=\"{name}\">")
+    } else {
+        let label = resource.file_name.as_deref().unwrap_or(&name);
+        format!("{ATTACHMENT_MARKER} {label} ({ATTACHMENTS_DIR}/{name})")
+    };
+    Some(EnexMedia {
+        hash: format!("{:x}", Md5::digest(&resource.bytes)),
+        markup,
+    })
+}
+
+fn finish_note(
+    path: &Path,
+    notebook: &str,
+    note_index: usize,
+    note: EnexNote,
+    prepared: &mut PreparedImport,
+) {
+    let mut media = HashMap::new();
+    let mut order = Vec::new();
+    for resource in note.media {
+        if !media.contains_key(&resource.hash) {
+            order.push(resource.hash.clone());
+        }
+        media.insert(resource.hash, resource.markup);
+    }
+
+    let mut used = HashSet::new();
+    let mut content = match enml_to_memo(&note.content, &media, &mut used) {
+        Ok(content) => content,
+        Err(error) => {
+            prepared.failures.push(failure(
+                ImportFailureKind::InvalidXml,
+                path,
+                format!("{}: {error}", note.title),
+            ));
+            return;
+        }
+    };
+    for hash in order.iter().filter(|hash| !used.contains(*hash)) {
+        end_block(&mut content);
+        content.push_str(&media[hash]);
+    }
+    if !note.title.is_empty() && content.lines().next() != Some(note.title.as_str()) {
+        content = format!("{}\n\n{content}", note.title);
+    }
+    let mut tags = vec![notebook.to_string()];
+    tags.extend(note.tags);
+    append_tags(&mut content, &tags);
+    let content = tidy(&content);
+    if content.trim().is_empty() {
+        return;
+    }
+
+    let fingerprint = Sha256::digest(
+        format!(
+            "{notebook}\0{note_index}\0{}\0{}",
+            note.title,
+            note.created.unwrap_or_default()
+        )
+        .as_bytes(),
+    );
+    prepared.memos.push(ImportedMemo {
+        path: path.to_path_buf(),
+        entry: MemoEntry {
+            id: format!("enex-{}", &format!("{fingerprint:x}")[..32]),
+            content,
+            timestamp: note
+                .updated
+                .or(note.created)
+                .unwrap_or_else(|| modified_secs(path)),
+        },
+        collection: MemoCollection::History,
+    });
+}
+
+fn enml_to_memo(
+    enml: &str,
+    media: &HashMap<String, String>,
+    used: &mut HashSet<String>,
+) -> Result<String, quick_xml::Error> {
+    let mut writer = EnmlWriter {
+        content: String::new(),
+        lists: Vec::new(),
+        link: None,
+        item_start: 0,
+        skipped: 0,
+        media,
+        used,
+    };
+    let mut reader = Reader::from_str(enml);
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => writer.open(&element, false),
+            Event::Empty(element) => writer.open(&element, true),
+            Event::End(element) => {
+                let name =
+                    String::from_utf8_lossy(element.local_name().as_ref()).to_ascii_lowercase();
+                writer.close(&name);
+            }
+            Event::Text(text) => writer.text(&text.xml_content()?),
+            Event::CData(text) => writer.text(&text.decode()?),
+            Event::GeneralRef(reference) => writer.text(&resolve_xml_reference(&reference)?),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(tidy(&writer.content))
+}
+
+struct EnmlWriter<'a> {
+    content: String,
+    lists: Vec<Option<u64>>,
+    link: Option<(String, usize)>,
+    item_start: usize,
+    skipped: usize,
+    media: &'a HashMap<String, String>,
+    used: &'a mut HashSet<String>,
+}
+
+impl EnmlWriter<'_> {
+    fn open(&mut self, element: &BytesStart<'_>, is_empty: bool) {
+        let name = element_name(element);
+        if self.skipped > 0 || SKIPPED_ELEMENTS.contains(&name.as_str()) {
+            if name == "en-crypt" && self.skipped == 0 {
+                self.content.push_str(ENCRYPTED_PLACEHOLDER);
+            }
+            if !is_empty {
+                self.skipped += 1;
+            }
+            return;
+        }
+
+        match name.as_str() {
+            "div" | "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "blockquote" | "pre"
+            | "table" | "tr" | "hr" => self.break_line(),
+            "br" => self.content.push('\n'),
+            "b" | "strong" => self.content.push_str("**"),
+            "i" | "em" => self.content.push('*'),
+            "s" | "strike" | "del" => self.content.push_str("~~"),
+            "ul" => self.lists.push(None),
+            "ol" => self.lists.push(Some(1)),
+            "li" => {
+                start_line(&mut self.content);
+                self.content
+                    .push_str(&"  ".repeat(self.lists.len().saturating_sub(1)));
+                match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        self.content.push_str(&format!("{number}. "));
+                        *number += 1;
+                    }
+                    _ => self.content.push_str("- "),
+                }
+                self.item_start = self.content.len();
+            }
+            "en-todo" => {
+                let checked = attribute(element, "checked").is_some_and(|value| value == "true");
+                self.content.push_str(if checked { "[x] " } else { "[ ] " });
+            }
+            "a" if !is_empty => {
+                let href = attribute(element, "href").unwrap_or_default();
+                self.link = Some((href, self.content.len()));
+            }
+            "en-media" => {
+                let hash = attribute(element, "hash").unwrap_or_default();
+                if let Some(markup) = self.media.get(&hash) {
+                    start_line(&mut self.content);
+                    self.content.push_str(markup);
+                    self.content.push('\n');
+                    self.used.insert(hash);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn break_line(&mut self) {
+        if self.content.len() != self.item_start {
+            start_line(&mut self.content);
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if self.skipped > 0 {
+            self.skipped -= 1;
+            return;
+        }
+
+        match name {
+            "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => end_block(&mut self.content),
+            "div" | "blockquote" | "pre" | "table" | "tr" | "li" => {
+                start_line(&mut self.content);
+            }
+            "td" | "th" => self.content.push('\t'),
+            "b" | "strong" => self.content.push_str("**"),
+            "i" | "em" => self.content.push('*'),
+            "s" | "strike" | "del" => self.content.push_str("~~"),
+            "ul" | "ol" => {
+                self.lists.pop();
+                start_line(&mut self.content);
+            }
+            "a" => {
+                if let Some((url, start)) = self.link.take() {
+                    let text = self.content[start..].trim();
+                    let bare = url
+                        .trim_start_matches("https://")
+                        .trim_start_matches("http://");
+                    if !url.is_empty() && text != url && text != bare {
+                        self.content.push_str(&format!(" ({url})"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skipped > 0 || (text.trim().is_empty() && text.contains('\n')) {
+            return;
+        }
+        self.content.push_str(&text.replace('\n', " "));
+    }
+}
+
+fn attachment_name(resource: &EnexResource) -> String {
+    let inline_image = match resource.mime.as_str() {
+        "image/png" => Some("png"),
+        "image/jpeg" | "image/jpg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        _ => None,
+    };
+    let extension = inline_image
+        .map(str::to_string)
+        .or_else(|| {
+            resource
+                .file_name
+                .as_deref()
+                .and_then(|file_name| Path::new(file_name).extension())
+                .and_then(|extension| extension.to_str())
+                .map(str::to_ascii_lowercase)
+                .or_else(|| {
+                    resource
+                        .mime
+                        .rsplit('/')
+                        .next()
+                        .filter(|subtype| !subtype.is_empty())
+                        .map(str::to_string)
+                })
+        })
+        .unwrap_or_else(|| "bin".to_string());
+    format!("{:x}.{extension}", Sha256::digest(&resource.bytes))
+}
+
+fn element_name(element: &BytesStart<'_>) -> String {
+    String::from_utf8_lossy(element.local_name().as_ref()).to_ascii_lowercase()
+}
+
+fn attribute(element: &BytesStart<'_>, name: &str) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.local_name().as_ref() == name.as_bytes())
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
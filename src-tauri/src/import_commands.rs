@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+use upmemo::enex_import::prepare_enex_import;
+use upmemo::keep_import::prepare_keep_import;
+use upmemo::markdown_export::ATTACHMENTS_DIR;
+use upmemo::memo_import::{
+    apply_import, preview_import, ImportPreview, ImportReport, PreparedImport,
+};
+use upmemo::memo_store::AtomicFileCommit;
+use upmemo::plum_import::prepare_plum_import;
+use upmemo::simplenote_import::prepare_simplenote_import;
+use upmemo::tomboy_import::prepare_tomboy_import;
+
+use crate::memo_store_state::memo_store;
+use crate::note_commands::map_memo_store_error;
+
+#[tauri::command]
+pub fn import_tomboy(app_handle: AppHandle, path: String) -> Result<ImportReport, String> {
+    let source = PathBuf::from(path);
+    if !source.is_dir() {
+        return Err("找不到 Tomboy / Gnote 記事資料夾".to_string());
+    }
+
+    commit_import(&app_handle, prepare_tomboy_import(&source))
+}
+
+#[tauri::command]
+pub fn import_plum_database(app_handle: AppHandle, path: String) -> Result<ImportReport, String> {
+    let database = PathBuf::from(path);
+    if !database.is_file() {
+        return Err("找不到 plum.sqlite 檔案".to_string());
+    }
+
+    commit_import(&app_handle, prepare_plum_import(&database))
+}
+
+#[tauri::command]
+pub fn preview_keep_import(app_handle: AppHandle, path: String) -> Result<ImportPreview, String> {
+    preview(&app_handle, prepare_keep_import(&keep_folder(path)?))
+}
+
+#[tauri::command]
+pub fn import_keep(app_handle: AppHandle, path: String) -> Result<ImportReport, String> {
+    commit_import(&app_handle, prepare_keep_import(&keep_folder(path)?))
+}
+
+#[tauri::command]
+pub fn preview_simplenote_import(
+    app_handle: AppHandle,
+    path: String,
+) -> Result<ImportPreview, String> {
+    preview(
+        &app_handle,
+        prepare_simplenote_import(&simplenote_export(path)?),
+    )
+}
+
+#[tauri::command]
+pub fn import_simplenote(app_handle: AppHandle, path: String) -> Result<ImportReport, String> {
+    commit_import(
+        &app_handle,
+        prepare_simplenote_import(&simplenote_export(path)?),
+    )
+}
+
+#[tauri::command]
+pub fn import_enex(app_handle: AppHandle, path: String) -> Result<ImportReport, String> {
+    let source = PathBuf::from(path);
+    if !source.exists() {
+        return Err("找不到 Evernote 匯出檔 (.enex)".to_string());
+    }
+
+    let attachments = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))?
+        .join(ATTACHMENTS_DIR);
+    commit_import(
+        &app_handle,
+        prepare_enex_import(&source, &attachments, &AtomicFileCommit),
+    )
+}
+
+fn keep_folder(path: String) -> Result<PathBuf, String> {
+    let source = PathBuf::from(path);
+    if !source.is_dir() {
+        return Err("找不到 Google Keep 匯出資料夾".to_string());
+    }
+    Ok(source)
+}
+
+fn simplenote_export(path: String) -> Result<PathBuf, String> {
+    let source = PathBuf::from(path);
+    if !source.exists() {
+        return Err("找不到 Simplenote 匯出檔".to_string());
+    }
+    Ok(source)
+}
+
+fn preview(app_handle: &AppHandle, prepared: PreparedImport) -> Result<ImportPreview, String> {
+    let store = memo_store(app_handle)?
+        .snapshot()
+        .map_err(map_memo_store_error)?;
+    Ok(preview_import(&store, &prepared))
+}
+
+fn commit_import(app_handle: &AppHandle, prepared: PreparedImport) -> Result<ImportReport, String> {
+    memo_store(app_handle)?
+        .transact(|store| Ok(apply_import(store, prepared)))
+        .map_err(map_memo_store_error)
+}
@@ -0,0 +1,325 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Deserialize;
+
+use crate::memo_import::{
+    append_tags, embed_image_file, end_block, failure, modified_secs, resolve_xml_reference,
+    start_line, tidy, ImportFailure, ImportFailureKind, ImportedMemo, PreparedImport, PINNED_LABEL,
+};
+use crate::memo_store::MemoCollection;
+use crate::models::MemoEntry;
+
+const MICROS_PER_SECOND: u64 = 1_000_000;
+const VOID_ELEMENTS: [&str; 6] = ["br", "img", "meta", "link", "hr", "input"];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct KeepJsonNote {
+    title: String,
+    text_content: String,
+    list_content: Vec<KeepListItem>,
+    labels: Vec<KeepLabel>,
+    attachments: Vec<KeepAttachment>,
+    is_trashed: bool,
+    is_archived: bool,
+    is_pinned: bool,
+    user_edited_timestamp_usec: Option<u64>,
+    created_timestamp_usec: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct KeepListItem {
+    text: String,
+    is_checked: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct KeepLabel {
+    name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct KeepAttachment {
+    file_path: String,
+}
+
+#[derive(Debug, Default)]
+struct KeepNote {
+    title: String,
+    body: String,
+    labels: Vec<String>,
+    trashed: bool,
+    archived: bool,
+    pinned: bool,
+    timestamp: Option<u64>,
+}
+
+pub fn prepare_keep_import(source: &Path) -> PreparedImport {
+    let mut prepared = PreparedImport::default();
+    let entries = match fs::read_dir(source) {
+        Ok(entries) => entries,
+        Err(error) => {
+            prepared
+                .failures
+                .push(failure(ImportFailureKind::Unreadable, source, error));
+            return prepared;
+        }
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+
+    let json_stems: HashSet<PathBuf> = paths
+        .iter()
+        .filter(|path| has_extension(path, "json"))
+        .map(|path| path.with_extension(""))
+        .collect();
+    for path in &paths {
+        let note = if has_extension(path, "json") {
+            read_json_note(path, &mut prepared.failures)
+        } else if has_extension(path, "html") && !json_stems.contains(&path.with_extension("")) {
+            read_html_note(path, &mut prepared.failures)
+        } else {
+            continue;
+        };
+        if let Some(note) = note {
+            push_note(path, note, &mut prepared);
+        }
+    }
+
+    prepared
+}
+
+fn read_json_note(path: &Path, failures: &mut Vec<ImportFailure>) -> Option<KeepNote> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            failures.push(failure(ImportFailureKind::Unreadable, path, error));
+            return None;
+        }
+    };
+    let note: KeepJsonNote = match serde_json::from_slice(&bytes) {
+        Ok(note) => note,
+        Err(error) => {
+            failures.push(failure(ImportFailureKind::InvalidJson, path, error));
+            return None;
+        }
+    };
+
+    let mut body = note.text_content.replace("\r\n", "\n");
+    for item in &note.list_content {
+        start_line(&mut body);
+        body.push_str(if item.is_checked { "- [x] " } else { "- [ ] " });
+        body.push_str(item.text.trim_end());
+    }
+    let base = path.parent().unwrap_or(Path::new(""));
+    for attachment in &note.attachments {
+        if let Some(markup) = embed_image_file(&base.join(&attachment.file_path), failures) {
+            end_block(&mut body);
+            body.push_str(&markup);
+        }
+    }
+
+    Some(KeepNote {
+        title: note.title,
+        body,
+        labels: note.labels.into_iter().map(|label| label.name).collect(),
+        trashed: note.is_trashed,
+        archived: note.is_archived,
+        pinned: note.is_pinned,
+        timestamp: note
+            .user_edited_timestamp_usec
+            .or(note.created_timestamp_usec)
+            .map(|micros| micros / MICROS_PER_SECOND),
+    })
+}
+
+fn read_html_note(path: &Path, failures: &mut Vec<ImportFailure>) -> Option<KeepNote> {
+    let html = match fs::read_to_string(path) {
+        Ok(html) => html,
+        Err(error) => {
+            failures.push(failure(ImportFailureKind::Unreadable, path, error));
+            return None;
+        }
+    };
+    let base = path.parent().unwrap_or(Path::new(""));
+    match parse_html_note(&html, base, failures) {
+        Ok(note) => Some(note),
+        Err(error) => {
+            failures.push(failure(ImportFailureKind::InvalidXml, path, error));
+            None
+        }
+    }
+}
+
+fn parse_html_note(
+    html: &str,
+    base: &Path,
+    failures: &mut Vec<ImportFailure>,
+) -> Result<KeepNote, quick_xml::Error> {
+    let mut note = KeepNote::default();
+    let mut reader = Reader::from_str(html);
+    reader.config_mut().check_end_names = false;
+    reader.config_mut().allow_unmatched_ends = true;
+    let mut classes: Vec<String> = Vec::new();
+    let mut label = String::new();
+
+    loop {
+        let event = reader.read_event()?;
+        let (element, is_empty) = match &event {
+            Event::Start(element) => (Some(element), false),
+            Event::Empty(element) => (Some(element), true),
+            _ => (None, false),
+        };
+        if let Some(element) = element {
+            let name = String::from_utf8_lossy(element.local_name().as_ref()).to_ascii_lowercase();
+            let class = attribute(element, "class").unwrap_or_default();
+            let in_content = classes.iter().any(|class| has_class(class, "content"));
+            let in_attachments = classes.iter().any(|class| has_class(class, "attachments"));
+            for flag in class.split_whitespace() {
+                match flag {
+                    "archived" => note.archived = true,
+                    "trashed" => note.trashed = true,
+                    "pinned" => note.pinned = true,
+                    _ => {}
+                }
+            }
+            match name.as_str() {
+                "br" if in_content => note.body.push('\n'),
+                "img" if in_content || in_attachments => {
+                    let src = attribute(element, "src").unwrap_or_default();
+                    let markup = if src.starts_with("data:image/") {
+                        Some(format!("<img src=\"{src}\">"))
+                    } else if !src.is_empty() && !src.contains("://") {
+                        embed_image_file(&base.join(&src), failures)
+                    } else {
+                        None
+                    };
+                    if let Some(markup) = markup {
+                        end_block(&mut note.body);
+                        note.body.push_str(&markup);
+                    }
+                }
+                _ if has_class(&class, "listitem") => {
+                    start_line(&mut note.body);
+                    note.body.push_str(if has_class(&class, "checked") {
+                        "- [x] "
+                    } else {
+                        "- [ ] "
+                    });
+                }
+                _ => {}
+            }
+            if !is_empty && !VOID_ELEMENTS.contains(&name.as_str()) {
+                classes.push(class);
+            }
+            continue;
+        }
+
+        let text = match event {
+            Event::End(_) => {
+                let closed = classes.pop().unwrap_or_default();
+                if has_class(&closed, "label-name") && !label.trim().is_empty() {
+                    note.labels.push(label.trim().to_string());
+                    label.clear();
+                }
+                continue;
+            }
+            Event::Text(text) => {
+                let text = text.xml_content()?;
+                if text.trim().is_empty() && text.contains('\n') {
+                    continue;
+                }
+                text.replace('\n', " ")
+            }
+            Event::GeneralRef(reference) => resolve_xml_reference(&reference)?,
+            Event::Eof => break,
+            _ => continue,
+        };
+        let Some(context) = classes
+            .iter()
+            .rev()
+            .find(|class| !class.is_empty())
+            .cloned()
+        else {
+            continue;
+        };
+        if has_class(&context, "bullet") {
+            continue;
+        }
+        if has_class(&context, "title") {
+            note.title.push_str(&text);
+        } else if has_class(&context, "label-name") {
+            label.push_str(&text);
+        } else if classes.iter().any(|class| has_class(class, "content")) {
+            note.body.push_str(&text);
+        }
+    }
+
+    Ok(note)
+}
+
+fn push_note(path: &Path, note: KeepNote, prepared: &mut PreparedImport) {
+    let mut content = String::new();
+    if !note.title.trim().is_empty() {
+        content.push_str(note.title.trim());
+        end_block(&mut content);
+    }
+    content.push_str(&note.body);
+    let mut labels = note.labels;
+    if note.pinned {
+        labels.insert(0, PINNED_LABEL.to_string());
+    }
+    append_tags(&mut content, &labels);
+    let content = tidy(&content);
+    if content.trim().is_empty() {
+        return;
+    }
+
+    let id = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .map_or_else(|| uuid::Uuid::new_v4().to_string(), str::to_string);
+    prepared.memos.push(ImportedMemo {
+        path: path.to_path_buf(),
+        entry: MemoEntry {
+            id,
+            content,
+            timestamp: note.timestamp.unwrap_or_else(|| modified_secs(path)),
+        },
+        collection: if note.trashed {
+            MemoCollection::Trash
+        } else if note.archived {
+            MemoCollection::Archive
+        } else {
+            MemoCollection::History
+        },
+    });
+}
+
+fn attribute(element: &BytesStart<'_>, name: &str) -> Option<String> {
+    element
+        .html_attributes()
+        .flatten()
+        .find(|attribute| attribute.key.local_name().as_ref() == name.as_bytes())
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
+
+fn has_class(classes: &str, class: &str) -> bool {
+    classes.split_whitespace().any(|known| known == class)
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .is_some_and(|known| known.eq_ignore_ascii_case(extension))
+}
@@ -1,4 +1,22 @@
+pub mod app_lock;
+pub mod bundle;
 pub mod clock;
+pub mod draft_journal;
 pub mod edit_session;
+pub mod enex_import;
+pub mod keep_import;
+pub mod markdown_export;
+pub mod markdown_import;
+pub mod memo_import;
 pub mod memo_store;
+pub mod memo_sync;
 pub mod models;
+pub mod plain_mirror;
+pub mod plum_import;
+pub mod private_memo;
+pub mod secret_scan;
+pub mod shred;
+pub mod shutdown_state;
+pub mod simplenote_import;
+pub mod tomboy_import;
+pub mod version_history;
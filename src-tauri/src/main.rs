@@ -1,19 +1,38 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod app_lock_commands;
+mod bundle_commands;
 mod clock;
 mod collection_commands;
+mod draft_commands;
 mod font_commands;
+mod import_commands;
+mod markdown_commands;
+mod memo_store_state;
+mod mirror_commands;
 mod note_commands;
 mod preview_commands;
+mod private_memo_commands;
 mod shortcut_commands;
+mod shred_commands;
+mod shutdown;
 mod storage;
+mod store_commands;
+mod store_encryption;
+mod store_events;
+mod store_flush;
+mod store_watcher;
+mod sync_commands;
 mod tray;
+mod version_history_commands;
 
+use memo_store_state::AppMemoStore;
 use std::sync::Mutex;
 use tauri::Manager;
-use upmemo::memo_store::MemoStoreRepository;
+use upmemo::memo_store::{is_sealed_file, SealedCommit};
 use upmemo::models::AppState;
+use upmemo::private_memo::PrivateMemoSessions;
 
 fn main() {
     let result = tauri::Builder::default()
@@ -22,13 +41,17 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .manage(AppState {
             current_shortcut: Mutex::new(None),
-            current_memo_id: Mutex::new(None),
         })
             .manage(preview_commands::PreviewImageState::default())
+            .manage(shutdown::ShutdownState::default())
+            .manage(PrivateMemoSessions::new(
+                private_memo_commands::PRIVATE_MEMO_IDLE_TIMEOUT,
+            ))
             .invoke_handler(tauri::generate_handler![
             note_commands::load_note,
             note_commands::save_note,
             note_commands::save_note_to_history,
+            note_commands::decline_secret_auto_lock,
             collection_commands::get_history,
             collection_commands::load_history_item,
             collection_commands::delete_history_item,
@@ -49,7 +72,59 @@ fn main() {
             note_commands::get_current_memo_id,
             note_commands::create_new_memo,
             preview_commands::set_preview_image_data,
-            preview_commands::take_preview_image_data
+            preview_commands::take_preview_image_data,
+            shutdown::acknowledge_autosave_flush,
+            draft_commands::record_draft,
+            draft_commands::get_recoverable_draft,
+            draft_commands::resolve_recovered_drafts,
+            store_commands::get_memo_store_encoding,
+            store_commands::set_memo_store_encoding,
+            store_commands::export_memo_store_json,
+            store_encryption::is_memo_store_locked,
+            store_encryption::is_memo_store_encrypted,
+            store_encryption::unlock_memo_store,
+            store_encryption::enable_memo_store_encryption,
+            store_encryption::change_memo_store_passphrase,
+            store_encryption::disable_memo_store_encryption,
+            private_memo_commands::lock_memo,
+            private_memo_commands::unlock_memo,
+            private_memo_commands::relock_memo,
+            private_memo_commands::keep_private_memos_unlocked,
+            private_memo_commands::remove_memo_lock,
+            app_lock_commands::get_app_lock_status,
+            app_lock_commands::report_app_activity,
+            app_lock_commands::lock_app,
+            app_lock_commands::unlock_app,
+            app_lock_commands::set_app_lock_pin,
+            app_lock_commands::disable_app_lock,
+            app_lock_commands::set_app_lock_idle_timeout,
+            shred_commands::shred_memo,
+            markdown_commands::export_markdown,
+            markdown_commands::import_markdown,
+            bundle_commands::export_bundle,
+            bundle_commands::import_bundle,
+            import_commands::import_tomboy,
+            import_commands::import_plum_database,
+            import_commands::preview_keep_import,
+            import_commands::import_keep,
+            import_commands::preview_simplenote_import,
+            import_commands::import_simplenote,
+            import_commands::import_enex,
+            mirror_commands::get_plain_mirror,
+            mirror_commands::enable_plain_mirror,
+            mirror_commands::disable_plain_mirror,
+            mirror_commands::sync_plain_mirror_now,
+            version_history_commands::get_version_history_status,
+            version_history_commands::enable_version_history,
+            version_history_commands::disable_version_history,
+            version_history_commands::list_version_history,
+            version_history_commands::get_memo_revisions,
+            version_history_commands::blame_memo,
+            version_history_commands::restore_version,
+            sync_commands::get_sync_status,
+            sync_commands::enable_folder_sync,
+            sync_commands::disable_sync,
+            sync_commands::sync_memos_now
             ])
                 .setup(|app| {
                     let app_handle = app.handle();
@@ -57,8 +132,16 @@ fn main() {
                     eprintln!("legacy store migration failed: {}", error);
                 }
 
-                let memo_store_path = app.path().app_data_dir()?.join("memo_store.json");
-                app.manage(MemoStoreRepository::open(memo_store_path)?);
+                app.manage(app_lock_commands::open_app_lock(app.handle())?);
+                app_lock_commands::spawn_idle_locker(app.handle());
+                app.manage(note_commands::open_secret_markers(app.handle())?);
+
+                let store_path = memo_store_state::memo_store_path(app.handle())?;
+                if !is_sealed_file(&store_path)? {
+                    let repository = AppMemoStore::open_with(store_path, SealedCommit::default())?;
+                    memo_store_state::install_memo_store(app.handle(), repository)?;
+                }
+                private_memo_commands::spawn_idle_relocker(app.handle());
 
             tray::setup_tray(app)?;
             tray::show_main_window(app.handle());
@@ -68,10 +151,22 @@ fn main() {
 
             Ok(())
         })
-        .run(tauri::generate_context!());
+        .build(tauri::generate_context!());
 
-    if let Err(error) = result {
-        eprintln!("Tauri 應用程式執行失敗: {}", error);
-        std::process::exit(1);
+    match result {
+        Ok(app) => app.run(|app_handle, event| match event {
+            tauri::RunEvent::ExitRequested { api, .. } => {
+                if !app_handle.state::<shutdown::ShutdownState>().is_complete() {
+                    api.prevent_exit();
+                    shutdown::request_shutdown(app_handle);
+                }
+            }
+            tauri::RunEvent::Exit => store_flush::flush_memo_store(app_handle),
+            _ => {}
+        }),
+        Err(error) => {
+            eprintln!("Tauri 應用程式執行失敗: {}", error);
+            std::process::exit(1);
+        }
     }
 }
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+use upmemo::markdown_export::{
+    export_markdown_folder, MarkdownExportError, MarkdownExportReport, MarkdownMemo,
+    ATTACHMENTS_DIR,
+};
+use upmemo::markdown_import::prepare_markdown_import;
+use upmemo::memo_import::{apply_import, ImportReport};
+use upmemo::memo_store::{AtomicFileCommit, MemoCollection};
+
+use crate::memo_store_state::memo_store;
+use crate::note_commands::map_memo_store_error;
+
+#[tauri::command]
+pub fn export_markdown(
+    app_handle: AppHandle,
+    path: String,
+    collections: Vec<MemoCollection>,
+) -> Result<MarkdownExportReport, String> {
+    if collections.contains(&MemoCollection::Trash) {
+        return Err("只能匯出歷史記錄或封存的記事".to_string());
+    }
+    let destination = PathBuf::from(path);
+    if destination.as_os_str().is_empty() {
+        return Err("請輸入匯出資料夾路徑".to_string());
+    }
+
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))?;
+    let store = memo_store(&app_handle)?
+        .snapshot()
+        .map_err(map_memo_store_error)?;
+
+    let memos: Vec<MarkdownMemo<'_>> = collections
+        .iter()
+        .flat_map(|collection| {
+            let entries = match collection {
+                MemoCollection::Archive => &store.archive,
+                _ => &store.history,
+            };
+            entries.iter().map(|entry| MarkdownMemo {
+                entry,
+                collection: *collection,
+            })
+        })
+        .collect();
+
+    export_markdown_folder(
+        &destination,
+        &memos,
+        &data_dir.join(ATTACHMENTS_DIR),
+        &AtomicFileCommit,
+    )
+    .map_err(map_markdown_export_error)
+}
+
+#[tauri::command]
+pub fn import_markdown(app_handle: AppHandle, path: String) -> Result<ImportReport, String> {
+    let source = PathBuf::from(path);
+    if !source.is_dir() {
+        return Err("找不到要匯入的資料夾".to_string());
+    }
+
+    let repository = memo_store(&app_handle)?;
+    let prepared = prepare_markdown_import(&source);
+    repository
+        .transact(|store| Ok(apply_import(store, prepared)))
+        .map_err(map_memo_store_error)
+}
+
+fn map_markdown_export_error(error: MarkdownExportError) -> String {
+    format!("匯出 Markdown 失敗: {error}")
+}
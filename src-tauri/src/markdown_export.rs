@@ -0,0 +1,544 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::markdown_import::split_front_matter;
+use crate::memo_store::{CommitAdapter, CommitError, MemoCollection};
+use crate::models::MemoEntry;
+use crate::private_memo;
+
+pub const ASSETS_DIR: &str = "assets";
+pub const ATTACHMENTS_DIR: &str = "attachments";
+const FRONT_MATTER_FENCE: &str = "---";
+const TITLE_MAX_CHARS: usize = 50;
+const UNTITLED: &str = "untitled";
+const FORBIDDEN_FILENAME_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const RESERVED_FILENAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+const MARKDOWN_ESCAPED: &[char] = &['\\', '`', '*', '_', '[', ']', '<', '>'];
+const IMAGE_EXTENSIONS: [(&str, &str); 6] = [
+    ("png", "png"),
+    ("jpeg", "jpg"),
+    ("jpg", "jpg"),
+    ("gif", "gif"),
+    ("webp", "webp"),
+    ("bmp", "bmp"),
+];
+
+#[derive(Clone, Copy, Debug)]
+pub struct MarkdownMemo<'a> {
+    pub entry: &'a MemoEntry,
+    pub collection: MemoCollection,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MemoImage {
+    Embedded { name: String, bytes: Vec<u8> },
+    Reference { name: String },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConvertedMemo {
+    pub markdown: String,
+    pub images: Vec<MemoImage>,
+}
+
+#[derive(Debug)]
+pub(crate) struct MarkdownDocument {
+    pub(crate) text: String,
+    pub(crate) images: Vec<(String, Vec<u8>)>,
+    pub(crate) missing_images: Vec<(String, io::Error)>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ExportedMemo {
+    pub id: String,
+    pub collection: MemoCollection,
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct MarkdownExportReport {
+    pub memos: Vec<ExportedMemo>,
+    pub images: Vec<PathBuf>,
+    pub skipped_private: Vec<String>,
+    pub missing_images: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum MarkdownExportError {
+    #[error("{operation} failed for {path}: {source}")]
+    Io {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+}
+
+pub fn export_markdown_folder(
+    destination: &Path,
+    memos: &[MarkdownMemo<'_>],
+    attachments: &Path,
+    committer: &impl CommitAdapter,
+) -> Result<MarkdownExportReport, MarkdownExportError> {
+    fs::create_dir_all(destination).map_err(|source| MarkdownExportError::Io {
+        operation: "create export directory",
+        path: destination.to_path_buf(),
+        source,
+    })?;
+
+    let mut report = MarkdownExportReport::default();
+    let mut taken = HashSet::new();
+    let mut written_images = HashSet::new();
+
+    for memo in memos {
+        let entry = memo.entry;
+        if private_memo::is_private(&entry.content) {
+            report.skipped_private.push(entry.id.clone());
+            continue;
+        }
+
+        let document = markdown_document(entry, memo.collection, attachments);
+        report
+            .missing_images
+            .extend(document.missing_images.into_iter().map(|(name, _)| name));
+        for (name, bytes) in document.images {
+            if !written_images.insert(name.clone()) {
+                continue;
+            }
+            let path = destination.join(ASSETS_DIR).join(name);
+            if !path.exists() {
+                committer.commit(&path, &bytes)?;
+            }
+            report.images.push(path);
+        }
+
+        let path = unique_path(
+            destination,
+            &derive_title(&entry.content),
+            &entry.id,
+            &mut taken,
+        );
+        committer.commit(&path, document.text.as_bytes())?;
+        report.memos.push(ExportedMemo {
+            id: entry.id.clone(),
+            collection: memo.collection,
+            path,
+        });
+    }
+
+    Ok(report)
+}
+
+pub(crate) fn markdown_document(
+    entry: &MemoEntry,
+    collection: MemoCollection,
+    attachments: &Path,
+) -> MarkdownDocument {
+    let converted = memo_to_markdown(&entry.content);
+    let mut images = Vec::new();
+    let mut missing_images = Vec::new();
+    for image in converted.images {
+        match image {
+            MemoImage::Embedded { name, bytes } => images.push((name, bytes)),
+            MemoImage::Reference { name } => match fs::read(attachments.join(&name)) {
+                Ok(bytes) => images.push((name, bytes)),
+                Err(error) => missing_images.push((name, error)),
+            },
+        }
+    }
+
+    MarkdownDocument {
+        text: format!(
+            "{FRONT_MATTER_FENCE}\nid: {}\ntimestamp: {}\ncollection: {}\n{FRONT_MATTER_FENCE}\n\n{}\n",
+            entry.id,
+            entry.timestamp,
+            collection.as_str(),
+            converted.markdown
+        ),
+        images,
+        missing_images,
+    }
+}
+
+pub fn memo_to_markdown(content: &str) -> ConvertedMemo {
+    let mut markdown = String::new();
+    let mut images = Vec::new();
+    let mut link: Option<(String, usize)> = None;
+    let mut divs = Vec::new();
+    let mut position = 0;
+
+    while position < content.len() {
+        let rest = &content[position..];
+        let Some(tag) = rest.starts_with('<').then(|| read_tag(rest)).flatten() else {
+            let end = text_run_len(rest);
+            if link.is_some() {
+                escape_into(&mut markdown, &rest[..end]);
+            } else {
+                push_text(&mut markdown, &rest[..end]);
+            }
+            position += end;
+            continue;
+        };
+        position += tag.len;
+
+        match (tag.name.as_str(), tag.closing) {
+            ("img", false) => {
+                if let Some(image) = image_from_tag(&tag) {
+                    let name = match &image {
+                        MemoImage::Embedded { name, .. } | MemoImage::Reference { name } => name,
+                    };
+                    markdown.push_str(&format!("![]({ASSETS_DIR}/{name})"));
+                    images.push(image);
+                }
+            }
+            ("br", _) => markdown.push('\n'),
+            ("div", false) => {
+                let class = tag.attribute("class").unwrap_or_default();
+                let decorative = class
+                    .split_whitespace()
+                    .any(|name| name == "image-container" || name == "resize-handle");
+                divs.push(decorative);
+            }
+            ("div" | "p", true) => {
+                let decorative = tag.name == "div" && divs.pop().unwrap_or(false);
+                if !decorative && !markdown.is_empty() && !markdown.ends_with('\n') {
+                    markdown.push('\n');
+                }
+            }
+            ("a", false) => {
+                link = tag
+                    .attribute("href")
+                    .filter(|href| is_http_url(href))
+                    .map(|href| (href.to_string(), markdown.len()));
+            }
+            ("a", true) => {
+                if let Some((href, start)) = link.take() {
+                    let text = markdown.split_off(start);
+                    if text.is_empty() {
+                        markdown.push_str(&format!("<{href}>"));
+                    } else {
+                        markdown.push_str(&format!("[{text}]({href})"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    ConvertedMemo {
+        markdown: hard_line_breaks(&markdown),
+        images,
+    }
+}
+
+pub fn derive_title(content: &str) -> String {
+    let mut plain = String::new();
+    let mut position = 0;
+    while position < content.len() {
+        let rest = &content[position..];
+        match rest.starts_with('<').then(|| read_tag(rest)).flatten() {
+            Some(tag) => {
+                if tag.name == "br" || (tag.closing && tag.name == "div") {
+                    plain.push('\n');
+                }
+                position += tag.len;
+            }
+            None => {
+                let end = text_run_len(rest);
+                plain.push_str(&rest[..end]);
+                position += end;
+            }
+        }
+    }
+
+    plain
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| line.chars().take(TITLE_MAX_CHARS).collect::<String>())
+        .unwrap_or_default()
+}
+
+fn unique_path(destination: &Path, title: &str, id: &str, taken: &mut HashSet<String>) -> PathBuf {
+    let stem = sanitize_filename(title);
+    let mut attempt = 1;
+    loop {
+        let name = if attempt == 1 {
+            format!("{stem}.md")
+        } else {
+            format!("{stem} ({attempt}).md")
+        };
+        let path = destination.join(&name);
+        let owned_by_other = path.exists() && !has_front_matter_id(&path, id);
+        if !owned_by_other && taken.insert(name.to_lowercase()) {
+            return path;
+        }
+        attempt += 1;
+    }
+}
+
+pub(crate) fn sanitize_filename(title: &str) -> String {
+    let replaced: String = title
+        .chars()
+        .map(|character| {
+            if character.is_control() || FORBIDDEN_FILENAME_CHARS.contains(&character) {
+                ' '
+            } else {
+                character
+            }
+        })
+        .collect();
+    let stem = replaced
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches('.')
+        .trim()
+        .to_string();
+
+    if stem.is_empty() {
+        UNTITLED.to_string()
+    } else if RESERVED_FILENAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(&stem))
+    {
+        format!("{stem}_")
+    } else {
+        stem
+    }
+}
+
+fn has_front_matter_id(path: &Path, id: &str) -> bool {
+    let Ok(text) = fs::read_to_string(path) else {
+        return false;
+    };
+    split_front_matter(&text).0.id.as_deref() == Some(id)
+}
+
+fn push_text(markdown: &mut String, text: &str) {
+    let mut rest = text;
+    while !rest.is_empty() {
+        let url_start = ["https://", "http://", "www."]
+            .iter()
+            .filter_map(|scheme| rest.find(scheme))
+            .filter(|start| {
+                rest[..*start]
+                    .chars()
+                    .next_back()
+                    .is_none_or(|previous| !previous.is_alphanumeric())
+            })
+            .min();
+        let Some(start) = url_start else {
+            escape_into(markdown, rest);
+            return;
+        };
+
+        escape_into(markdown, &rest[..start]);
+        let url_len = rest[start..]
+            .find(|character: char| character.is_whitespace() || character == '<')
+            .unwrap_or(rest.len() - start);
+        let url = rest[start..start + url_len].trim_end_matches(['.', ',', ')', '!', '?']);
+        if url.starts_with("www.") {
+            markdown.push_str(&format!("[{url}](https://{url})"));
+        } else {
+            markdown.push_str(&format!("<{url}>"));
+        }
+        rest = &rest[start + url.len()..];
+    }
+}
+
+fn escape_into(markdown: &mut String, text: &str) {
+    for character in text.chars() {
+        if MARKDOWN_ESCAPED.contains(&character) {
+            markdown.push('\\');
+        }
+        markdown.push(character);
+    }
+}
+
+fn hard_line_breaks(markdown: &str) -> String {
+    let lines: Vec<String> = markdown
+        .trim_matches('\n')
+        .split('\n')
+        .map(escape_block_marker)
+        .collect();
+    let mut output = String::new();
+    for (index, line) in lines.iter().enumerate() {
+        output.push_str(line);
+        let continues = lines
+            .get(index + 1)
+            .is_some_and(|next| !next.trim().is_empty());
+        if continues && !line.trim().is_empty() {
+            output.push('\\');
+        }
+        if index + 1 < lines.len() {
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn escape_block_marker(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    if trimmed.starts_with(['#', '-', '+', '=', '|']) {
+        return format!("{indent}\\{trimmed}");
+    }
+
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    let ordered = digits > 0
+        && trimmed[digits..].starts_with(['.', ')'])
+        && trimmed[digits + 1..].starts_with(' ');
+    if ordered {
+        return format!("{indent}{}\\{}", &trimmed[..digits], &trimmed[digits..]);
+    }
+    line.to_string()
+}
+
+pub(crate) fn text_run_len(rest: &str) -> usize {
+    let first = rest.chars().next().map_or(0, char::len_utf8);
+    rest[first..]
+        .find('<')
+        .map_or(rest.len(), |next| next + first)
+}
+
+fn is_http_url(href: &str) -> bool {
+    href.starts_with("https://") || href.starts_with("http://")
+}
+
+fn image_from_tag(tag: &HtmlTag) -> Option<MemoImage> {
+    if let Some(reference) = tag.attribute("data-upmemo-image") {
+        let safe = !reference.is_empty()
+            && reference
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || character == '.');
+        return safe.then(|| MemoImage::Reference {
+            name: reference.to_string(),
+        });
+    }
+
+    let source: String = tag.attribute("src")?.split_whitespace().collect();
+    let (header, payload) = source.strip_prefix("data:image/")?.split_once(',')?;
+    let format = header.strip_suffix(";base64")?.to_ascii_lowercase();
+    let extension = IMAGE_EXTENSIONS
+        .iter()
+        .find(|(mime, _)| *mime == format)
+        .map(|(_, extension)| *extension)?;
+    let bytes = STANDARD.decode(payload).ok()?;
+    let name = format!("{:x}.{extension}", Sha256::digest(&bytes));
+    Some(MemoImage::Embedded { name, bytes })
+}
+
+pub(crate) struct HtmlTag {
+    pub(crate) name: String,
+    pub(crate) closing: bool,
+    attributes: Vec<(String, String)>,
+    pub(crate) len: usize,
+}
+
+impl HtmlTag {
+    pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub(crate) fn read_tag(markup: &str) -> Option<HtmlTag> {
+    let bytes = markup.as_bytes();
+    let mut position = 1;
+    let closing = bytes.get(position) == Some(&b'/');
+    if closing {
+        position += 1;
+    }
+    let name_start = position;
+    while bytes
+        .get(position)
+        .is_some_and(|byte| byte.is_ascii_alphanumeric() || *byte == b'-')
+    {
+        position += 1;
+    }
+    if position == name_start || !bytes[name_start].is_ascii_alphabetic() {
+        return None;
+    }
+    let name = markup[name_start..position].to_ascii_lowercase();
+
+    let mut attributes = Vec::new();
+    loop {
+        while bytes
+            .get(position)
+            .is_some_and(|byte| byte.is_ascii_whitespace() || *byte == b'/')
+        {
+            position += 1;
+        }
+        match bytes.get(position)? {
+            b'>' => break,
+            _ => {
+                let attribute_start = position;
+                while bytes.get(position).is_some_and(|byte| {
+                    !byte.is_ascii_whitespace() && !matches!(byte, b'=' | b'>' | b'/')
+                }) {
+                    position += 1;
+                }
+                let attribute = markup[attribute_start..position].to_ascii_lowercase();
+                let mut value = String::new();
+                if bytes.get(position) == Some(&b'=') {
+                    position += 1;
+                    let value_end = match bytes.get(position)? {
+                        quote @ (b'"' | b'\'') => {
+                            position += 1;
+                            let end = position + markup[position..].find(*quote as char)?;
+                            value = decode_entities(&markup[position..end]);
+                            end + 1
+                        }
+                        _ => {
+                            let end = position
+                                + markup[position..]
+                                    .find(|c: char| c.is_ascii_whitespace() || c == '>')?;
+                            value = decode_entities(&markup[position..end]);
+                            end
+                        }
+                    };
+                    position = value_end;
+                }
+                attributes.push((attribute, value));
+            }
+        }
+    }
+
+    Some(HtmlTag {
+        name,
+        closing,
+        attributes,
+        len: position + 1,
+    })
+}
+
+pub(crate) fn decode_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#039;", "'")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
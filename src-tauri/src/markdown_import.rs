@@ -0,0 +1,258 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+
+use crate::memo_import::{
+    embed_image_file, end_block, failure, modified_secs, start_line, tidy, ImportFailure,
+    ImportFailureKind, ImportedMemo, PreparedImport,
+};
+use crate::memo_store::MemoCollection;
+use crate::models::MemoEntry;
+
+const FRONT_MATTER_FENCE: &str = "---";
+const IMPORTED_EXTENSIONS: [&str; 3] = ["md", "markdown", "txt"];
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FrontMatter {
+    pub id: Option<String>,
+    pub timestamp: Option<u64>,
+    pub collection: Option<MemoCollection>,
+}
+
+pub fn prepare_markdown_import(source: &Path) -> PreparedImport {
+    let mut prepared = PreparedImport::default();
+    let mut pending = vec![source.to_path_buf()];
+
+    while let Some(directory) = pending.pop() {
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(error) => {
+                prepared
+                    .failures
+                    .push(failure(ImportFailureKind::Unreadable, &directory, error));
+                continue;
+            }
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            if path.is_dir() {
+                pending.push(path);
+            } else if has_imported_extension(&path) {
+                import_file(&path, &mut prepared);
+            }
+        }
+    }
+
+    prepared
+}
+
+pub fn split_front_matter(text: &str) -> (FrontMatter, &str) {
+    let mut front_matter = FrontMatter::default();
+    let Some(rest) = text
+        .strip_prefix(FRONT_MATTER_FENCE)
+        .and_then(|rest| rest.strip_prefix('\n'))
+    else {
+        return (front_matter, text);
+    };
+    let Some(end) = rest
+        .match_indices(FRONT_MATTER_FENCE)
+        .map(|(index, _)| index)
+        .find(|index| *index == 0 || rest[..*index].ends_with('\n'))
+    else {
+        return (front_matter, text);
+    };
+
+    for line in rest[..end].lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"');
+        match key.trim() {
+            "id" if !value.is_empty() => front_matter.id = Some(value.to_string()),
+            "timestamp" => front_matter.timestamp = value.parse().ok(),
+            "collection" => front_matter.collection = MemoCollection::from_name(value),
+            _ => {}
+        }
+    }
+
+    let body = &rest[end + FRONT_MATTER_FENCE.len()..];
+    (front_matter, body.trim_start_matches('\n'))
+}
+
+pub fn markdown_to_memo(markdown: &str, base: &Path, failures: &mut Vec<ImportFailure>) -> String {
+    let mut content = String::new();
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut link: Option<(String, usize)> = None;
+    let mut in_image = false;
+
+    let options =
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS | Options::ENABLE_TABLES;
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                in_image = true;
+                if let Some(markup) = image_markup(&dest_url, base, failures) {
+                    start_line(&mut content);
+                    content.push_str(&markup);
+                    content.push('\n');
+                }
+            }
+            Event::End(TagEnd::Image) => in_image = false,
+            _ if in_image => {}
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                link = Some((dest_url.to_string(), content.len()));
+            }
+            Event::End(TagEnd::Link) => {
+                if let Some((url, start)) = link.take() {
+                    let text = content[start..].trim();
+                    let bare = url
+                        .trim_start_matches("https://")
+                        .trim_start_matches("http://");
+                    if !url.is_empty() && text != url && text != bare {
+                        content.push_str(&format!(" ({url})"));
+                    }
+                }
+            }
+            Event::Start(Tag::List(first)) => lists.push(first),
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                end_block(&mut content);
+            }
+            Event::Start(Tag::Item) => {
+                start_line(&mut content);
+                content.push_str(&"  ".repeat(lists.len().saturating_sub(1)));
+                match lists.last_mut() {
+                    Some(Some(number)) => {
+                        content.push_str(&format!("{number}. "));
+                        *number += 1;
+                    }
+                    _ => content.push_str("- "),
+                }
+            }
+            Event::TaskListMarker(done) => content.push_str(if done { "[x] " } else { "[ ] " }),
+            Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::CodeBlock)
+            | Event::End(TagEnd::BlockQuote(_) | TagEnd::Table)
+            | Event::Rule => {
+                if lists.is_empty() {
+                    end_block(&mut content);
+                } else {
+                    start_line(&mut content);
+                }
+            }
+            Event::End(TagEnd::TableRow | TagEnd::TableHead) => start_line(&mut content),
+            Event::End(TagEnd::TableCell) => content.push('\t'),
+            Event::Text(text) | Event::Code(text) => content.push_str(&text),
+            Event::InlineHtml(html) | Event::Html(html) if is_line_break(&html) => {
+                content.push('\n');
+            }
+            Event::SoftBreak | Event::HardBreak => content.push('\n'),
+            _ => {}
+        }
+    }
+
+    tidy(&content)
+}
+
+fn import_file(path: &Path, prepared: &mut PreparedImport) {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            prepared
+                .failures
+                .push(failure(ImportFailureKind::Unreadable, path, error));
+            return;
+        }
+    };
+    let text = match String::from_utf8(bytes) {
+        Ok(text) => text.replace("\r\n", "\n"),
+        Err(error) => {
+            prepared
+                .failures
+                .push(failure(ImportFailureKind::NotUtf8, path, error));
+            return;
+        }
+    };
+
+    let is_text = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("txt"));
+    let (front_matter, body) = split_front_matter(&text);
+    let content = if is_text {
+        tidy(body)
+    } else {
+        let base = path.parent().unwrap_or(Path::new(""));
+        markdown_to_memo(body, base, &mut prepared.failures)
+    };
+    if content.trim().is_empty() {
+        return;
+    }
+
+    let timestamp = front_matter
+        .timestamp
+        .unwrap_or_else(|| modified_secs(path));
+    prepared.memos.push(ImportedMemo {
+        path: path.to_path_buf(),
+        entry: MemoEntry {
+            id: front_matter
+                .id
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            content,
+            timestamp,
+        },
+        collection: front_matter.collection.unwrap_or(MemoCollection::History),
+    });
+}
+
+fn image_markup(url: &str, base: &Path, failures: &mut Vec<ImportFailure>) -> Option<String> {
+    if url.contains("://") || url.starts_with("data:") {
+        return None;
+    }
+
+    embed_image_file(&base.join(percent_decode(url)), failures)
+}
+
+fn percent_decode(url: &str) -> String {
+    let bytes = url.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| url.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn is_line_break(html: &str) -> bool {
+    let tag: String = html
+        .chars()
+        .filter(|character| !character.is_whitespace() && *character != '/')
+        .collect();
+    tag.eq_ignore_ascii_case("<br>")
+}
+
+fn has_imported_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            IMPORTED_EXTENSIONS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(extension))
+        })
+}
@@ -0,0 +1,371 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::BytesRef;
+use serde::Serialize;
+
+use crate::markdown_export::derive_title;
+use crate::memo_store::{MemoCollection, MemoStoreV2, HISTORY_LIMIT};
+use crate::models::MemoEntry;
+
+pub(crate) const PINNED_LABEL: &str = "置頂";
+const IMAGE_MIME_TYPES: [(&str, &str); 6] = [
+    ("png", "png"),
+    ("jpg", "jpeg"),
+    ("jpeg", "jpeg"),
+    ("gif", "gif"),
+    ("webp", "webp"),
+    ("bmp", "bmp"),
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFailureKind {
+    Unreadable,
+    NotUtf8,
+    MissingImage,
+    UnsupportedImage,
+    InvalidXml,
+    InvalidDatabase,
+    InvalidJson,
+    InvalidResource,
+    AttachmentNotWritten,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ImportFailure {
+    pub kind: ImportFailureKind,
+    pub path: PathBuf,
+    pub source: String,
+}
+
+impl std::fmt::Display for ImportFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} | {} | path={}",
+            self.kind,
+            self.source,
+            self.path.display()
+        )
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ImportedFile {
+    pub path: PathBuf,
+    pub id: String,
+    pub collection: MemoCollection,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ImportReport {
+    pub imported: Vec<ImportedFile>,
+    pub unchanged: Vec<ImportedFile>,
+    pub failures: Vec<ImportFailure>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportedMemo {
+    pub path: PathBuf,
+    pub entry: MemoEntry,
+    pub collection: MemoCollection,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PreparedImport {
+    pub memos: Vec<ImportedMemo>,
+    pub failures: Vec<ImportFailure>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ImportPreviewItem {
+    pub path: PathBuf,
+    pub id: String,
+    pub title: String,
+    pub timestamp: u64,
+    pub collection: MemoCollection,
+    pub replaces_existing: bool,
+    pub keeps_existing: bool,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ImportPreview {
+    pub memos: Vec<ImportPreviewItem>,
+    pub failures: Vec<ImportFailure>,
+}
+
+pub fn preview_import(store: &MemoStoreV2, prepared: &PreparedImport) -> ImportPreview {
+    let memos = prepared
+        .memos
+        .iter()
+        .map(|memo| {
+            let existing = existing_entry(store, &memo.entry.id).map(|(_, entry)| entry.timestamp);
+            ImportPreviewItem {
+                path: memo.path.clone(),
+                id: memo.entry.id.clone(),
+                title: derive_title(&memo.entry.content),
+                timestamp: memo.entry.timestamp,
+                collection: memo.collection,
+                replaces_existing: existing
+                    .is_some_and(|timestamp| timestamp < memo.entry.timestamp),
+                keeps_existing: existing.is_some_and(|timestamp| timestamp >= memo.entry.timestamp),
+            }
+        })
+        .collect();
+
+    ImportPreview {
+        memos,
+        failures: prepared.failures.clone(),
+    }
+}
+
+pub fn apply_import(store: &mut MemoStoreV2, prepared: PreparedImport) -> ImportReport {
+    let mut report = ImportReport {
+        failures: prepared.failures,
+        ..ImportReport::default()
+    };
+
+    let mut imported = Vec::new();
+    for memo in prepared.memos {
+        if let Some((collection, existing)) = existing_entry(store, &memo.entry.id) {
+            if existing.timestamp >= memo.entry.timestamp {
+                report.unchanged.push(ImportedFile {
+                    path: memo.path,
+                    id: memo.entry.id,
+                    collection,
+                });
+                continue;
+            }
+        }
+
+        for entries in [&mut store.history, &mut store.archive, &mut store.trash] {
+            entries.retain(|entry| entry.id != memo.entry.id);
+        }
+        let entries = match memo.collection {
+            MemoCollection::History => &mut store.history,
+            MemoCollection::Archive => &mut store.archive,
+            MemoCollection::Trash => &mut store.trash,
+        };
+        imported.push((memo.path, memo.entry.id.clone()));
+        insert_by_timestamp(entries, memo.entry);
+    }
+    spill_history_overflow(store);
+
+    for (path, id) in imported {
+        if let Some((collection, _)) = existing_entry(store, &id) {
+            report.imported.push(ImportedFile {
+                path,
+                id,
+                collection,
+            });
+        }
+    }
+    report
+}
+
+pub(crate) fn insert_by_timestamp(entries: &mut Vec<MemoEntry>, entry: MemoEntry) {
+    let position = entries
+        .iter()
+        .position(|existing| existing.timestamp < entry.timestamp)
+        .unwrap_or(entries.len());
+    entries.insert(position, entry);
+}
+
+pub(crate) fn spill_history_overflow(store: &mut MemoStoreV2) {
+    if store.history.len() > HISTORY_LIMIT {
+        for entry in store.history.split_off(HISTORY_LIMIT) {
+            insert_by_timestamp(&mut store.archive, entry);
+        }
+    }
+}
+
+fn existing_entry<'a>(store: &'a MemoStoreV2, id: &str) -> Option<(MemoCollection, &'a MemoEntry)> {
+    [
+        (MemoCollection::History, &store.history),
+        (MemoCollection::Archive, &store.archive),
+        (MemoCollection::Trash, &store.trash),
+    ]
+    .into_iter()
+    .find_map(|(collection, entries)| {
+        entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| (collection, entry))
+    })
+}
+
+pub(crate) fn start_line(content: &mut String) {
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+}
+
+pub(crate) fn end_block(content: &mut String) {
+    start_line(content);
+    if !content.is_empty() && !content.ends_with("\n\n") {
+        content.push('\n');
+    }
+}
+
+pub(crate) fn tidy(content: &str) -> String {
+    let mut tidied = String::new();
+    let mut blank_lines = 0;
+    for line in content.trim_matches('\n').split('\n') {
+        let line = line.trim_end_matches([' ', '\t']);
+        blank_lines = if line.is_empty() { blank_lines + 1 } else { 0 };
+        if blank_lines > 1 {
+            continue;
+        }
+        if !tidied.is_empty() {
+            tidied.push('\n');
+        }
+        tidied.push_str(line);
+    }
+    tidied
+}
+
+pub(crate) fn append_tags(content: &mut String, tags: &[String]) {
+    let hashtags: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join("_"))
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| format!("#{tag}"))
+        .collect();
+    if hashtags.is_empty() {
+        return;
+    }
+    end_block(content);
+    content.push_str(&hashtags.join(" "));
+}
+
+pub(crate) fn parse_iso8601(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (date, time) = value.split_once(['T', ' ']).unwrap_or((value, ""));
+    let date: String = date.chars().filter(|character| *character != '-').collect();
+    if date.len() != 8 || !date.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let year: i64 = date[..4].parse().ok()?;
+    let month: i64 = date[4..6].parse().ok()?;
+    let day: i64 = date[6..].parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let (clock, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(index) => (&time[..index], &time[index..]),
+        None => (time, ""),
+    };
+    let clock: String = clock
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|character| *character != ':')
+        .collect();
+    let mut seconds_of_day = 0;
+    for (index, unit) in [3600, 60, 1].into_iter().enumerate() {
+        if let Some(part) = clock.get(index * 2..index * 2 + 2) {
+            seconds_of_day += part.parse::<i64>().ok()? * unit;
+        }
+    }
+
+    let offset_seconds = match offset.chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            let digits: String = offset[1..]
+                .chars()
+                .filter(|character| *character != ':')
+                .collect();
+            let hours: i64 = digits.get(..2)?.parse().ok()?;
+            let minutes: i64 = digits
+                .get(2..4)
+                .map_or(Some(0), |minutes| minutes.parse().ok())?;
+            let seconds = hours * 3600 + minutes * 60;
+            if sign == '-' {
+                -seconds
+            } else {
+                seconds
+            }
+        }
+        _ => 0,
+    };
+
+    let shifted = if month <= 2 { year - 1 } else { year };
+    let era = shifted.div_euclid(400);
+    let year_of_era = shifted - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    u64::try_from(days * 86_400 + seconds_of_day - offset_seconds).ok()
+}
+
+pub(crate) fn embed_image_file(path: &Path, failures: &mut Vec<ImportFailure>) -> Option<String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let Some(mime) = IMAGE_MIME_TYPES
+        .iter()
+        .find(|(known, _)| Some(*known) == extension.as_deref())
+        .map(|(_, mime)| *mime)
+    else {
+        failures.push(ImportFailure {
+            kind: ImportFailureKind::UnsupportedImage,
+            path: path.to_path_buf(),
+            source: "unsupported image format".to_string(),
+        });
+        return None;
+    };
+
+    match fs::read(path) {
+        Ok(bytes) => Some(image_markup(mime, &bytes)),
+        Err(error) => {
+            failures.push(failure(ImportFailureKind::MissingImage, path, error));
+            None
+        }
+    }
+}
+
+pub(crate) fn image_markup(mime: &str, bytes: &[u8]) -> String {
+    format!(
+        "<img src=\"data:image/{mime};base64,{}\">",
+        STANDARD.encode(bytes)
+    )
+}
+
+pub(crate) fn resolve_xml_reference(reference: &BytesRef<'_>) -> Result<String, quick_xml::Error> {
+    if let Some(character) = reference.resolve_char_ref()? {
+        return Ok(character.to_string());
+    }
+    let name = reference.decode()?;
+    Ok(match name.as_ref() {
+        "nbsp" => " ".to_string(),
+        name => resolve_predefined_entity(name).map_or_else(|| format!("&{name};"), str::to_string),
+    })
+}
+
+pub(crate) fn modified_secs(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_secs())
+}
+
+pub(crate) fn failure(
+    kind: ImportFailureKind,
+    path: &Path,
+    error: impl std::fmt::Display,
+) -> ImportFailure {
+    ImportFailure {
+        kind,
+        path: path.to_path_buf(),
+        source: error.to_string(),
+    }
+}
@@ -1,7 +1,21 @@
 mod atomic_file;
+mod change;
+mod encoding;
+mod encryption;
+mod engine;
 mod model;
 mod repository;
+mod sqlite;
+mod store_lock;
 
 pub use atomic_file::{AtomicFileCommit, CommitAdapter, CommitError};
-pub use model::{MemoCollection, MemoStoreV2, MEMO_STORE_VERSION};
-pub use repository::{MemoStoreError, MemoStoreRepository};
+pub use change::StoreChange;
+pub use encoding::StoreEncoding;
+pub use encryption::{
+    is_sealed, is_sealed_file, reseal_files, stage_reseal, EncryptionError, KdfParams,
+    SealedCommit, StagedReseal, StoreKey, SwappedReseal,
+};
+pub use engine::MemoStoreEngine;
+pub use model::{MemoCollection, MemoStoreV2, HISTORY_LIMIT, MEMO_STORE_VERSION};
+pub use repository::{ExternalChangePolicy, MemoStoreError, MemoStoreRepository};
+pub use sqlite::SqliteMemoStore;
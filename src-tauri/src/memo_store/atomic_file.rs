@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
@@ -5,8 +6,18 @@ use std::path::{Path, PathBuf};
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
+use super::encryption::{is_sealed, EncryptionError};
+
 pub trait CommitAdapter: Send + Sync {
     fn commit(&self, target: &Path, bytes: &[u8]) -> Result<(), CommitError>;
+
+    fn unseal<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, [u8]>, EncryptionError> {
+        if is_sealed(bytes) {
+            Err(EncryptionError::Locked)
+        } else {
+            Ok(Cow::Borrowed(bytes))
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -32,6 +43,8 @@ pub enum CommitError {
     },
     #[error("injected commit failure: {0}")]
     Injected(String),
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
 }
 
 impl CommitAdapter for AtomicFileCommit {
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::models::MemoEntry;
+
+use super::{MemoCollection, MemoStoreV2};
+
+pub(super) type ChangeListener = Box<dyn Fn(&[StoreChange]) + Send + Sync>;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StoreChange {
+    Added {
+        collection: MemoCollection,
+        id: String,
+    },
+    Updated {
+        collection: MemoCollection,
+        id: String,
+    },
+    Moved {
+        from: MemoCollection,
+        to: MemoCollection,
+        id: String,
+    },
+    Deleted {
+        collection: MemoCollection,
+        id: String,
+    },
+}
+
+impl StoreChange {
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Added { id, .. }
+            | Self::Updated { id, .. }
+            | Self::Moved { id, .. }
+            | Self::Deleted { id, .. } => id,
+        }
+    }
+}
+
+pub(super) fn diff_stores(before: &MemoStoreV2, after: &MemoStoreV2) -> Vec<StoreChange> {
+    let previous = index_entries(before);
+    let mut changes = Vec::new();
+
+    for (collection, entry) in entries(after) {
+        let id = entry.id.clone();
+        match previous.get(entry.id.as_str()) {
+            None => changes.push(StoreChange::Added { collection, id }),
+            Some((from, _)) if *from != collection => changes.push(StoreChange::Moved {
+                from: *from,
+                to: collection,
+                id,
+            }),
+            Some((_, old)) if *old != entry => {
+                changes.push(StoreChange::Updated { collection, id })
+            }
+            Some(_) => {}
+        }
+    }
+
+    let current = index_entries(after);
+    for (collection, entry) in entries(before) {
+        if !current.contains_key(entry.id.as_str()) {
+            changes.push(StoreChange::Deleted {
+                collection,
+                id: entry.id.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+pub(super) fn rebase_changes(
+    base: &MemoStoreV2,
+    local: &MemoStoreV2,
+    mut external: MemoStoreV2,
+) -> MemoStoreV2 {
+    if local.current_memo_id != base.current_memo_id
+        || local.current_content != base.current_content
+    {
+        external.current_memo_id = local.current_memo_id.clone();
+        external.current_content = local.current_content.clone();
+    }
+    if local.encoding != base.encoding {
+        external.encoding = local.encoding;
+    }
+
+    for change in diff_stores(base, local) {
+        let id = change.id();
+        let located = MemoCollection::ALL.into_iter().find_map(|collection| {
+            let entries = local.collection(collection);
+            entries
+                .iter()
+                .position(|entry| entry.id == id)
+                .map(|index| (collection, index, entries[index].clone()))
+        });
+        let kept = located.as_ref().and_then(|(collection, _, _)| {
+            external
+                .collection(*collection)
+                .iter()
+                .position(|entry| entry.id == id)
+        });
+        for collection in MemoCollection::ALL {
+            while external.take(id, collection).is_some() {}
+        }
+        if let Some((collection, index, entry)) = located {
+            external.insert_at(entry, collection, kept.unwrap_or(index));
+        }
+    }
+    external
+}
+
+fn entries(store: &MemoStoreV2) -> impl Iterator<Item = (MemoCollection, &MemoEntry)> {
+    [
+        (MemoCollection::History, &store.history),
+        (MemoCollection::Archive, &store.archive),
+        (MemoCollection::Trash, &store.trash),
+    ]
+    .into_iter()
+    .flat_map(|(collection, entries)| entries.iter().map(move |entry| (collection, entry)))
+}
+
+fn index_entries(store: &MemoStoreV2) -> HashMap<&str, (MemoCollection, &MemoEntry)> {
+    entries(store)
+        .map(|(collection, entry)| (entry.id.as_str(), (collection, entry)))
+        .collect()
+}
@@ -0,0 +1,67 @@
+use std::borrow::Cow;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use super::MemoStoreV2;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreEncoding {
+    #[default]
+    Json,
+    Zstd,
+}
+
+impl StoreEncoding {
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&ZSTD_MAGIC) {
+            Self::Zstd
+        } else {
+            Self::Json
+        }
+    }
+
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    pub(super) fn from_name(name: &str) -> Option<Self> {
+        [Self::Json, Self::Zstd]
+            .into_iter()
+            .find(|encoding| encoding.as_str() == name)
+    }
+
+    pub(super) fn is_json(&self) -> bool {
+        *self == Self::Json
+    }
+}
+
+#[derive(Debug)]
+pub(super) enum EncodeError {
+    Serialize(serde_json::Error),
+    Compress(io::Error),
+}
+
+pub(super) fn encode(store: &MemoStoreV2) -> Result<Vec<u8>, EncodeError> {
+    match store.encoding {
+        StoreEncoding::Json => serde_json::to_vec_pretty(store).map_err(EncodeError::Serialize),
+        StoreEncoding::Zstd => {
+            let json = serde_json::to_vec(store).map_err(EncodeError::Serialize)?;
+            zstd::encode_all(json.as_slice(), ZSTD_LEVEL).map_err(EncodeError::Compress)
+        }
+    }
+}
+
+pub(super) fn decode(bytes: &[u8]) -> io::Result<Cow<'_, [u8]>> {
+    match StoreEncoding::detect(bytes) {
+        StoreEncoding::Json => Ok(Cow::Borrowed(bytes)),
+        StoreEncoding::Zstd => zstd::decode_all(bytes).map(Cow::Owned),
+    }
+}
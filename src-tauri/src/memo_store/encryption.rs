@@ -0,0 +1,468 @@
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use super::{AtomicFileCommit, CommitAdapter, CommitError};
+
+const MAGIC: &[u8; 8] = b"UPMEMOE1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + 12 + NONCE_LEN;
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 16;
+const MAX_PARALLELISM: u32 = 16;
+const STAGING_SUFFIX: &str = ".resealing";
+const PREVIOUS_SUFFIX: &str = ".previous";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KdfParams {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl KdfParams {
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> Self {
+        Self {
+            memory_kib,
+            iterations,
+            parallelism,
+        }
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self::new(19 * 1024, 2, 1)
+    }
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum EncryptionError {
+    #[error("memo store is encrypted and has not been unlocked")]
+    Locked,
+    #[error("memo store should be encrypted but is stored as plain text")]
+    NotSealed,
+    #[error("failed to derive key from passphrase: {0}")]
+    KeyDerivation(String),
+    #[error("failed to encrypt memo store")]
+    Encrypt,
+    #[error("wrong passphrase or tampered memo store")]
+    Decrypt,
+    #[error("encrypted memo store envelope is malformed")]
+    MalformedEnvelope,
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+pub struct StoreKey {
+    key: Zeroizing<[u8; KEY_LEN]>,
+    salt: [u8; SALT_LEN],
+    params: KdfParams,
+}
+
+impl StoreKey {
+    pub fn derive(passphrase: &str, params: KdfParams) -> Result<Self, EncryptionError> {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self::derive_with_salt(passphrase, salt, params)
+    }
+
+    pub fn derive_for(passphrase: &str, sealed: &[u8]) -> Result<Self, EncryptionError> {
+        let header = Header::parse(sealed)?;
+        let key = Self::derive_with_salt(passphrase, header.salt, header.params)?;
+        key.unseal(sealed)?;
+        Ok(key)
+    }
+
+    pub fn matches_passphrase(&self, passphrase: &str) -> Result<bool, EncryptionError> {
+        let candidate = Self::derive_with_salt(passphrase, self.salt, self.params)?;
+        Ok(bool::from(candidate.key.ct_eq(self.key.as_ref())))
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let mut sealed = Header {
+            salt: self.salt,
+            params: self.params,
+            nonce: nonce.into(),
+        }
+        .to_bytes();
+        let ciphertext = self
+            .cipher()
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: &sealed,
+                },
+            )
+            .map_err(|_| EncryptionError::Encrypt)?;
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    pub fn unseal(&self, sealed: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let header = Header::parse(sealed)?;
+        if header.salt != self.salt || header.params != self.params {
+            return Err(EncryptionError::Decrypt);
+        }
+        self.cipher()
+            .decrypt(
+                XNonce::from_slice(&header.nonce),
+                Payload {
+                    msg: &sealed[HEADER_LEN..],
+                    aad: &sealed[..HEADER_LEN],
+                },
+            )
+            .map_err(|_| EncryptionError::Decrypt)
+    }
+
+    fn derive_with_salt(
+        passphrase: &str,
+        salt: [u8; SALT_LEN],
+        params: KdfParams,
+    ) -> Result<Self, EncryptionError> {
+        let argon_params = Params::new(
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+            Some(KEY_LEN),
+        )
+        .map_err(|error| EncryptionError::KeyDerivation(error.to_string()))?;
+        let mut key = Zeroizing::new([0; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+            .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+            .map_err(|error| EncryptionError::KeyDerivation(error.to_string()))?;
+        Ok(Self { key, salt, params })
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(self.key.as_ref().into())
+    }
+}
+
+struct Header {
+    salt: [u8; SALT_LEN],
+    params: KdfParams,
+    nonce: [u8; NONCE_LEN],
+}
+
+impl Header {
+    fn parse(sealed: &[u8]) -> Result<Self, EncryptionError> {
+        if !is_sealed(sealed) || sealed.len() < HEADER_LEN {
+            return Err(EncryptionError::MalformedEnvelope);
+        }
+        let (salt, rest) = sealed[MAGIC.len()..].split_at(SALT_LEN);
+        let (params, rest) = rest.split_at(12);
+        let word = |index: usize| {
+            u32::from_le_bytes([
+                params[index * 4],
+                params[index * 4 + 1],
+                params[index * 4 + 2],
+                params[index * 4 + 3],
+            ])
+        };
+        let params = KdfParams::new(word(0), word(1), word(2));
+        if params.memory_kib > MAX_MEMORY_KIB
+            || params.iterations > MAX_ITERATIONS
+            || params.parallelism > MAX_PARALLELISM
+        {
+            return Err(EncryptionError::MalformedEnvelope);
+        }
+        let mut header = Self {
+            salt: [0; SALT_LEN],
+            params,
+            nonce: [0; NONCE_LEN],
+        };
+        header.salt.copy_from_slice(salt);
+        header.nonce.copy_from_slice(&rest[..NONCE_LEN]);
+        Ok(header)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.salt);
+        for word in [
+            self.params.memory_kib,
+            self.params.iterations,
+            self.params.parallelism,
+        ] {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.nonce);
+        bytes
+    }
+}
+
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub fn is_sealed_file(path: &Path) -> Result<bool, EncryptionError> {
+    let mut prefix = [0; MAGIC.len()];
+    let read = fs::File::open(path).and_then(|mut file| file.read_exact(&mut prefix));
+    match read {
+        Ok(()) => Ok(is_sealed(&prefix)),
+        Err(source)
+            if matches!(
+                source.kind(),
+                io::ErrorKind::NotFound | io::ErrorKind::UnexpectedEof
+            ) =>
+        {
+            Ok(false)
+        }
+        Err(source) => Err(EncryptionError::Io {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+pub struct StagedReseal {
+    directory: PathBuf,
+    staging: PathBuf,
+    touched: Vec<PathBuf>,
+}
+
+impl StagedReseal {
+    pub fn swap(self) -> Result<Vec<PathBuf>, CommitError> {
+        self.swap_reversibly()?.finish()
+    }
+
+    pub fn swap_reversibly(self) -> Result<SwappedReseal, CommitError> {
+        let previous = sibling(&self.directory, PREVIOUS_SUFFIX);
+        if self.touched.is_empty() {
+            remove_directory(&self.staging)?;
+            return Ok(SwappedReseal {
+                directory: self.directory,
+                previous: None,
+                touched: self.touched,
+            });
+        }
+        remove_directory(&previous)?;
+        rename(&self.directory, &previous)?;
+        if let Err(error) = rename(&self.staging, &self.directory) {
+            rename(&previous, &self.directory)?;
+            return Err(error);
+        }
+        Ok(SwappedReseal {
+            directory: self.directory,
+            previous: Some(previous),
+            touched: self.touched,
+        })
+    }
+
+    pub fn abandon(self) -> Result<(), CommitError> {
+        remove_directory(&self.staging)
+    }
+}
+
+pub struct SwappedReseal {
+    directory: PathBuf,
+    previous: Option<PathBuf>,
+    touched: Vec<PathBuf>,
+}
+
+impl SwappedReseal {
+    pub fn finish(self) -> Result<Vec<PathBuf>, CommitError> {
+        if let Some(previous) = &self.previous {
+            remove_directory(previous)?;
+        }
+        Ok(self.touched)
+    }
+
+    pub fn revert(self) -> Result<(), CommitError> {
+        let Some(previous) = &self.previous else {
+            return Ok(());
+        };
+        remove_directory(&self.directory)?;
+        rename(previous, &self.directory)
+    }
+}
+
+pub fn stage_reseal(
+    directory: &Path,
+    from: Option<&StoreKey>,
+    to: Option<&StoreKey>,
+) -> Result<StagedReseal, CommitError> {
+    let staging = sibling(directory, STAGING_SUFFIX);
+    if !directory.exists() && staging.exists() {
+        rename(&staging, directory)?;
+    }
+    remove_directory(&sibling(directory, PREVIOUS_SUFFIX))?;
+    remove_directory(&staging)?;
+
+    let mut touched = Vec::new();
+    let staged = stage_directory(directory, &staging, from, to, &mut touched);
+    if let Err(error) = staged {
+        remove_directory(&staging)?;
+        return Err(error);
+    }
+    Ok(StagedReseal {
+        directory: directory.to_path_buf(),
+        staging,
+        touched,
+    })
+}
+
+pub fn reseal_files(
+    directory: &Path,
+    from: Option<&StoreKey>,
+    to: Option<&StoreKey>,
+) -> Result<Vec<PathBuf>, CommitError> {
+    stage_reseal(directory, from, to)?.swap()
+}
+
+fn stage_directory(
+    directory: &Path,
+    staging: &Path,
+    from: Option<&StoreKey>,
+    to: Option<&StoreKey>,
+    touched: &mut Vec<PathBuf>,
+) -> Result<(), CommitError> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(source) if source.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(source) => {
+            return Err(CommitError::Io {
+                operation: "list directory",
+                path: directory.to_path_buf(),
+                source,
+            });
+        }
+    };
+    fs::create_dir_all(staging).map_err(|source| CommitError::Io {
+        operation: "create staging directory",
+        path: staging.to_path_buf(),
+        source,
+    })?;
+
+    for entry in entries {
+        let entry = entry.map_err(|source| CommitError::Io {
+            operation: "list directory",
+            path: directory.to_path_buf(),
+            source,
+        })?;
+        let path = entry.path();
+        let staged = staging.join(entry.file_name());
+        if path.is_dir() {
+            stage_directory(&path, &staged, from, to, touched)?;
+            continue;
+        }
+
+        let bytes = fs::read(&path).map_err(|source| CommitError::Io {
+            operation: "read file",
+            path: path.clone(),
+            source,
+        })?;
+        let plaintext = if is_sealed(&bytes) {
+            from.ok_or(EncryptionError::Locked)?.unseal(&bytes)?
+        } else {
+            bytes.clone()
+        };
+        let rewritten = match to {
+            Some(key) => key.seal(&plaintext)?,
+            None => plaintext,
+        };
+        if rewritten != bytes {
+            touched.push(path);
+        }
+        fs::write(&staged, &rewritten).map_err(|source| CommitError::Io {
+            operation: "write staged file",
+            path: staged.clone(),
+            source,
+        })?;
+    }
+    Ok(())
+}
+
+fn sibling(directory: &Path, suffix: &str) -> PathBuf {
+    let mut name = directory.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    directory.with_file_name(name)
+}
+
+fn rename(from: &Path, to: &Path) -> Result<(), CommitError> {
+    fs::rename(from, to).map_err(|source| CommitError::Io {
+        operation: "swap resealed directory",
+        path: from.to_path_buf(),
+        source,
+    })
+}
+
+fn remove_directory(directory: &Path) -> Result<(), CommitError> {
+    match fs::remove_dir_all(directory) {
+        Ok(()) => Ok(()),
+        Err(source) if source.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(source) => Err(CommitError::Io {
+            operation: "remove directory",
+            path: directory.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+#[derive(Default)]
+pub struct SealedCommit<C = AtomicFileCommit> {
+    inner: C,
+    key: RwLock<Option<Arc<StoreKey>>>,
+}
+
+impl<C> SealedCommit<C> {
+    pub fn new(inner: C, key: Option<StoreKey>) -> Self {
+        Self {
+            inner,
+            key: RwLock::new(key.map(Arc::new)),
+        }
+    }
+
+    pub fn key(&self) -> Option<Arc<StoreKey>> {
+        self.key.read().ok().and_then(|key| key.clone())
+    }
+
+    pub fn replace_key(&self, key: Option<Arc<StoreKey>>) -> Option<Arc<StoreKey>> {
+        match self.key.write() {
+            Ok(mut current) => std::mem::replace(&mut *current, key),
+            Err(poisoned) => std::mem::replace(&mut *poisoned.into_inner(), key),
+        }
+    }
+}
+
+impl<C> CommitAdapter for SealedCommit<C>
+where
+    C: CommitAdapter,
+{
+    fn commit(&self, target: &Path, bytes: &[u8]) -> Result<(), CommitError> {
+        match self.key() {
+            Some(key) => self.inner.commit(target, &key.seal(bytes)?),
+            None => self.inner.commit(target, bytes),
+        }
+    }
+
+    fn unseal<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, [u8]>, EncryptionError> {
+        match (self.key(), is_sealed(bytes)) {
+            (Some(key), true) => key.unseal(bytes).map(Cow::Owned),
+            (Some(_), false) => Err(EncryptionError::NotSealed),
+            (None, true) => Err(EncryptionError::Locked),
+            (None, false) => Ok(Cow::Borrowed(bytes)),
+        }
+    }
+}
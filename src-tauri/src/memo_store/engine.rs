@@ -0,0 +1,59 @@
+use super::{
+    CommitAdapter, MemoCollection, MemoStoreError, MemoStoreRepository, MemoStoreV2, StoreChange,
+};
+
+pub trait MemoStoreEngine: Send + Sync {
+    fn snapshot(&self) -> Result<MemoStoreV2, MemoStoreError>;
+
+    fn transact<R>(
+        &self,
+        mutation: impl FnOnce(&mut MemoStoreV2) -> Result<R, MemoStoreError>,
+    ) -> Result<R, MemoStoreError>;
+
+    fn subscribe(
+        &self,
+        listener: impl Fn(&[StoreChange]) + Send + Sync + 'static,
+    ) -> Result<(), MemoStoreError>;
+
+    fn move_entry(
+        &self,
+        id: &str,
+        source: MemoCollection,
+        destination: MemoCollection,
+    ) -> Result<bool, MemoStoreError> {
+        if source == destination {
+            return Err(MemoStoreError::SameCollection { collection: source });
+        }
+
+        self.transact(|store| {
+            let Some(entry) = store.take(id, source) else {
+                return Ok(false);
+            };
+            store.insert_front(entry, destination);
+            Ok(true)
+        })
+    }
+}
+
+impl<C> MemoStoreEngine for MemoStoreRepository<C>
+where
+    C: CommitAdapter,
+{
+    fn snapshot(&self) -> Result<MemoStoreV2, MemoStoreError> {
+        MemoStoreRepository::snapshot(self)
+    }
+
+    fn transact<R>(
+        &self,
+        mutation: impl FnOnce(&mut MemoStoreV2) -> Result<R, MemoStoreError>,
+    ) -> Result<R, MemoStoreError> {
+        MemoStoreRepository::transact(self, mutation)
+    }
+
+    fn subscribe(
+        &self,
+        listener: impl Fn(&[StoreChange]) + Send + Sync + 'static,
+    ) -> Result<(), MemoStoreError> {
+        MemoStoreRepository::subscribe(self, listener)
+    }
+}
@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+use super::StoreEncoding;
 use crate::models::MemoEntry;
 
 pub const MEMO_STORE_VERSION: u32 = 2;
-const HISTORY_LIMIT: usize = 100;
+pub const HISTORY_LIMIT: usize = 100;
 const TRASH_LIMIT: usize = 50;
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -15,6 +16,8 @@ pub struct MemoStoreV2 {
     pub history: Vec<MemoEntry>,
     pub archive: Vec<MemoEntry>,
     pub trash: Vec<MemoEntry>,
+    #[serde(default, skip_serializing_if = "StoreEncoding::is_json")]
+    pub encoding: StoreEncoding,
 }
 
 impl Default for MemoStoreV2 {
@@ -26,18 +29,69 @@ impl Default for MemoStoreV2 {
             history: Vec::new(),
             archive: Vec::new(),
             trash: Vec::new(),
+            encoding: StoreEncoding::Json,
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum MemoCollection {
     History,
     Archive,
     Trash,
 }
 
+impl MemoCollection {
+    pub(super) const ALL: [Self; 3] = [Self::History, Self::Archive, Self::Trash];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::History => "history",
+            Self::Archive => "archive",
+            Self::Trash => "trash",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|collection| collection.as_str() == name)
+    }
+}
+
 impl MemoStoreV2 {
+    pub fn save_current(&mut self, content: &str, timestamp: u64) -> String {
+        let memo_id = self
+            .current_memo_id
+            .clone()
+            .unwrap_or_else(|| timestamp.to_string());
+        self.current_memo_id = Some(memo_id.clone());
+        self.current_content = content.to_string();
+        if !content.trim().is_empty() {
+            self.upsert_history(memo_id.clone(), content, timestamp);
+        }
+        memo_id
+    }
+
+    pub fn upsert_history(&mut self, memo_id: String, content: &str, timestamp: u64) {
+        match self.history.iter_mut().find(|entry| entry.id == memo_id) {
+            Some(existing) if existing.content != content => {
+                existing.content = content.to_string();
+                existing.timestamp = timestamp;
+            }
+            Some(_) => {}
+            None => self.history.insert(
+                0,
+                MemoEntry {
+                    id: memo_id,
+                    content: content.to_string(),
+                    timestamp,
+                },
+            ),
+        }
+    }
+
     pub(super) fn enforce_limits(&mut self) {
         self.history.truncate(HISTORY_LIMIT);
         self.trash.truncate(TRASH_LIMIT);
@@ -53,6 +107,23 @@ impl MemoStoreV2 {
         self.collection_mut(collection).insert(0, entry);
     }
 
+    pub(super) fn insert_back(&mut self, entry: MemoEntry, collection: MemoCollection) {
+        self.collection_mut(collection).push(entry);
+    }
+
+    pub(super) fn insert_at(&mut self, entry: MemoEntry, collection: MemoCollection, index: usize) {
+        let entries = self.collection_mut(collection);
+        entries.insert(index.min(entries.len()), entry);
+    }
+
+    pub(super) fn collection(&self, collection: MemoCollection) -> &[MemoEntry] {
+        match collection {
+            MemoCollection::History => &self.history,
+            MemoCollection::Archive => &self.archive,
+            MemoCollection::Trash => &self.trash,
+        }
+    }
+
     fn collection_mut(&mut self, collection: MemoCollection) -> &mut Vec<MemoEntry> {
         match collection {
             MemoCollection::History => &mut self.history,
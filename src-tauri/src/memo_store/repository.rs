@@ -1,18 +1,61 @@
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
+use super::change::{diff_stores, rebase_changes, ChangeListener};
+use super::encoding::{self, EncodeError};
+use super::engine::MemoStoreEngine;
+use super::store_lock::{lock_path_for, StoreLock};
 use super::{
-    AtomicFileCommit, CommitAdapter, CommitError, MemoCollection, MemoStoreV2, MEMO_STORE_VERSION,
+    AtomicFileCommit, CommitAdapter, CommitError, EncryptionError, MemoCollection, MemoStoreV2,
+    StoreChange, StoreEncoding, MEMO_STORE_VERSION,
 };
 
 pub struct MemoStoreRepository<C = AtomicFileCommit> {
     path: PathBuf,
-    state: Mutex<MemoStoreV2>,
+    lock_path: PathBuf,
+    state: Mutex<CommittedState>,
     committer: C,
+    external_change_policy: ExternalChangePolicy,
+    write_behind: Option<Duration>,
+    listeners: RwLock<Vec<ChangeListener>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ExternalChangePolicy {
+    #[default]
+    Rebase,
+    Reject,
+}
+
+struct CommittedState {
+    store: MemoStoreV2,
+    base: MemoStoreV2,
+    fingerprint: Option<StoreFingerprint>,
+    pending_since: Option<Instant>,
+}
+
+impl CommittedState {
+    fn clean(store: MemoStoreV2, fingerprint: Option<StoreFingerprint>) -> Self {
+        Self {
+            base: store.clone(),
+            store,
+            fingerprint,
+            pending_since: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct StoreFingerprint {
+    len: u64,
+    modified: Option<SystemTime>,
+    sha256: [u8; 32],
 }
 
 #[derive(Debug, Error)]
@@ -24,6 +67,18 @@ pub enum MemoStoreError {
         #[source]
         source: io::Error,
     },
+    #[error("failed to decompress memo store {path}: {source}")]
+    Decompress {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("failed to unseal memo store {path}: {source}")]
+    Encryption {
+        path: PathBuf,
+        #[source]
+        source: EncryptionError,
+    },
     #[error("memo store {path} is malformed: {source}")]
     Malformed {
         path: PathBuf,
@@ -38,8 +93,41 @@ pub enum MemoStoreError {
     SameCollection { collection: MemoCollection },
     #[error("failed to serialize memo store: {0}")]
     Serialize(#[source] serde_json::Error),
+    #[error("failed to compress memo store: {0}")]
+    Compress(#[source] io::Error),
+    #[error("failed to lock memo store {path}: {source}")]
+    Lock {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("memo store {path} was modified by another process")]
+    ExternalChange { path: PathBuf },
     #[error(transparent)]
     Commit(#[from] CommitError),
+    #[error("memo store database {path} failed: {source}")]
+    Sqlite {
+        path: PathBuf,
+        #[source]
+        source: rusqlite::Error,
+    },
+    #[error("memo {id} is private: {source}")]
+    PrivateMemo {
+        id: String,
+        #[source]
+        source: EncryptionError,
+    },
+    #[error("memo store database {path} is corrupt: {reason}")]
+    CorruptDatabase { path: PathBuf, reason: String },
+}
+
+impl From<EncodeError> for MemoStoreError {
+    fn from(error: EncodeError) -> Self {
+        match error {
+            EncodeError::Serialize(source) => Self::Serialize(source),
+            EncodeError::Compress(source) => Self::Compress(source),
+        }
+    }
 }
 
 impl MemoStoreRepository<AtomicFileCommit> {
@@ -54,61 +142,344 @@ where
 {
     pub fn open_with(path: impl Into<PathBuf>, committer: C) -> Result<Self, MemoStoreError> {
         let path = path.into();
-        let store = read_strict(&path)?;
+        let (store, fingerprint) = read_strict(&path, &committer)?;
         Ok(Self {
+            lock_path: lock_path_for(&path),
             path,
-            state: Mutex::new(store),
+            state: Mutex::new(CommittedState::clean(store, fingerprint)),
             committer,
+            external_change_policy: ExternalChangePolicy::default(),
+            write_behind: None,
+            listeners: RwLock::new(Vec::new()),
         })
     }
 
+    pub fn with_external_change_policy(mut self, policy: ExternalChangePolicy) -> Self {
+        self.external_change_policy = policy;
+        self
+    }
+
+    pub fn with_write_behind(mut self, window: Duration) -> Self {
+        self.write_behind = Some(window);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn committer(&self) -> &C {
+        &self.committer
+    }
+
+    pub fn subscribe(
+        &self,
+        listener: impl Fn(&[StoreChange]) + Send + Sync + 'static,
+    ) -> Result<(), MemoStoreError> {
+        self.listeners
+            .write()
+            .map(|mut listeners| listeners.push(Box::new(listener)))
+            .map_err(|_| MemoStoreError::Poisoned)
+    }
+
     pub fn snapshot(&self) -> Result<MemoStoreV2, MemoStoreError> {
         self.state
             .lock()
-            .map(|store| store.clone())
+            .map(|state| state.store.clone())
             .map_err(|_| MemoStoreError::Poisoned)
     }
 
+    pub fn reload(&self) -> Result<MemoStoreV2, MemoStoreError> {
+        let mut current = self.state.lock().map_err(|_| MemoStoreError::Poisoned)?;
+        let _lock = self.acquire_lock()?;
+        if current.pending_since.is_some() {
+            return self.commit_pending(&mut current);
+        }
+        let (store, fingerprint) = read_strict(&self.path, &self.committer)?;
+        *current = CommittedState::clean(store.clone(), fingerprint);
+        Ok(store)
+    }
+
+    pub fn reload_if_changed(&self) -> Result<Option<MemoStoreV2>, MemoStoreError> {
+        let mut current = self.state.lock().map_err(|_| MemoStoreError::Poisoned)?;
+        let _lock = self.acquire_lock()?;
+        let Some(change) = self.detect_external_change(&mut current)? else {
+            return Ok(None);
+        };
+        if current.pending_since.is_none() {
+            let (store, fingerprint) = read_strict(&self.path, &self.committer)?;
+            *current = CommittedState::clean(store.clone(), fingerprint);
+            return Ok(Some(store));
+        }
+
+        self.adopt_external_change(&mut current, change)?;
+        let candidate = current.store.clone();
+        self.commit_candidate(&mut current, candidate.clone())?;
+        Ok(Some(candidate))
+    }
+
     pub fn transact<R>(
         &self,
         mutation: impl FnOnce(&mut MemoStoreV2) -> Result<R, MemoStoreError>,
     ) -> Result<R, MemoStoreError> {
-        let mut current = self.state.lock().map_err(|_| MemoStoreError::Poisoned)?;
-        let mut candidate = current.clone();
-        let result = mutation(&mut candidate)?;
-        candidate.enforce_limits();
-        validate_version(&candidate)?;
-        let bytes = serde_json::to_vec_pretty(&candidate).map_err(MemoStoreError::Serialize)?;
-        self.committer.commit(&self.path, &bytes)?;
-        *current = candidate;
+        let (result, changes) = self.commit_mutation(mutation)?;
+        if !changes.is_empty() {
+            self.notify(&changes);
+        }
         Ok(result)
     }
 
+    pub fn has_pending_writes(&self) -> Result<bool, MemoStoreError> {
+        self.state
+            .lock()
+            .map(|state| state.pending_since.is_some())
+            .map_err(|_| MemoStoreError::Poisoned)
+    }
+
+    pub fn flush(&self) -> Result<bool, MemoStoreError> {
+        let mut current = self.state.lock().map_err(|_| MemoStoreError::Poisoned)?;
+        if current.pending_since.is_none() {
+            return Ok(false);
+        }
+
+        let _lock = self.acquire_lock()?;
+        self.commit_pending(&mut current)?;
+        Ok(true)
+    }
+
+    pub fn flush_if_due(&self) -> Result<bool, MemoStoreError> {
+        let window = self.write_behind.unwrap_or_default();
+        let due = self
+            .state
+            .lock()
+            .map(|state| {
+                state
+                    .pending_since
+                    .is_some_and(|pending_since| pending_since.elapsed() >= window)
+            })
+            .map_err(|_| MemoStoreError::Poisoned)?;
+        if due {
+            self.flush()
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub fn recommit_with<T>(
+        &self,
+        reconfigure: impl FnOnce(&C) -> T,
+        restore: impl FnOnce(&C, T),
+    ) -> Result<(), MemoStoreError> {
+        let mut current = self.state.lock().map_err(|_| MemoStoreError::Poisoned)?;
+        let _lock = self.acquire_lock()?;
+        if let Some(change) = self.detect_external_change(&mut current)? {
+            self.adopt_external_change(&mut current, change)?;
+        }
+
+        let previous = reconfigure(&self.committer);
+        let candidate = current.store.clone();
+        if let Err(error) = self.commit_candidate(&mut current, candidate) {
+            restore(&self.committer, previous);
+            return Err(error);
+        }
+        Ok(())
+    }
+
     pub fn move_entry(
         &self,
         id: &str,
         source: MemoCollection,
         destination: MemoCollection,
     ) -> Result<bool, MemoStoreError> {
-        if source == destination {
-            return Err(MemoStoreError::SameCollection { collection: source });
-        }
+        MemoStoreEngine::move_entry(self, id, source, destination)
+    }
 
+    pub fn encoding(&self) -> Result<StoreEncoding, MemoStoreError> {
+        self.snapshot().map(|store| store.encoding)
+    }
+
+    pub fn set_encoding(&self, encoding: StoreEncoding) -> Result<bool, MemoStoreError> {
         self.transact(|store| {
-            let Some(entry) = store.take(id, source) else {
-                return Ok(false);
-            };
-            store.insert_front(entry, destination);
-            Ok(true)
+            let changed = store.encoding != encoding;
+            store.encoding = encoding;
+            Ok(changed)
         })
     }
+
+    pub fn export_plain_json(&self, destination: &Path) -> Result<(), MemoStoreError> {
+        let store = MemoStoreV2 {
+            encoding: StoreEncoding::Json,
+            ..self.snapshot()?
+        };
+        let bytes = encoding::encode(&store)?;
+        AtomicFileCommit.commit(destination, &bytes)?;
+        Ok(())
+    }
+
+    fn commit_mutation<R>(
+        &self,
+        mutation: impl FnOnce(&mut MemoStoreV2) -> Result<R, MemoStoreError>,
+    ) -> Result<(R, Vec<StoreChange>), MemoStoreError> {
+        let mut current = self.state.lock().map_err(|_| MemoStoreError::Poisoned)?;
+        let _lock = self.acquire_lock()?;
+        if let Some(change) = self.detect_external_change(&mut current)? {
+            self.adopt_external_change(&mut current, change)?;
+        }
+        let mut candidate = current.store.clone();
+        let result = mutation(&mut candidate)?;
+        candidate.enforce_limits();
+        validate_version(&candidate)?;
+        if candidate == current.store {
+            return Ok((result, Vec::new()));
+        }
+
+        let changes = diff_stores(&current.store, &candidate);
+        if self.write_behind.is_some() {
+            current.pending_since.get_or_insert_with(Instant::now);
+            current.store = candidate;
+        } else {
+            self.commit_candidate(&mut current, candidate)?;
+        }
+        Ok((result, changes))
+    }
+
+    fn adopt_external_change(
+        &self,
+        current: &mut CommittedState,
+        change: ExternalChange,
+    ) -> Result<(), MemoStoreError> {
+        if self.external_change_policy == ExternalChangePolicy::Reject {
+            return Err(MemoStoreError::ExternalChange {
+                path: self.path.clone(),
+            });
+        }
+
+        let external = parse_strict(&self.path, &change.bytes, &self.committer)?;
+        let pending_since = current.pending_since;
+        let store = match pending_since {
+            Some(_) => rebase_changes(&current.base, &current.store, external.clone()),
+            None => external.clone(),
+        };
+        *current = CommittedState {
+            store,
+            base: external,
+            fingerprint: Some(change.fingerprint),
+            pending_since,
+        };
+        Ok(())
+    }
+
+    fn commit_pending(&self, current: &mut CommittedState) -> Result<MemoStoreV2, MemoStoreError> {
+        if let Some(change) = self.detect_external_change(current)? {
+            self.adopt_external_change(current, change)?;
+        }
+        let candidate = current.store.clone();
+        self.commit_candidate(current, candidate.clone())?;
+        Ok(candidate)
+    }
+
+    fn commit_candidate(
+        &self,
+        current: &mut CommittedState,
+        candidate: MemoStoreV2,
+    ) -> Result<(), MemoStoreError> {
+        let bytes = encoding::encode(&candidate)?;
+        self.committer.commit(&self.path, &bytes)?;
+        *current = CommittedState::clean(candidate, StoreFingerprint::observe(&self.path, &bytes));
+        Ok(())
+    }
+
+    fn notify(&self, changes: &[StoreChange]) {
+        if let Ok(listeners) = self.listeners.read() {
+            for listener in listeners.iter() {
+                listener(changes);
+            }
+        }
+    }
+
+    fn acquire_lock(&self) -> Result<StoreLock, MemoStoreError> {
+        StoreLock::acquire(&self.lock_path).map_err(|error| MemoStoreError::Lock {
+            path: error.path,
+            source: error.source,
+        })
+    }
+
+    fn detect_external_change(
+        &self,
+        current: &mut CommittedState,
+    ) -> Result<Option<ExternalChange>, MemoStoreError> {
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(source) if source.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => {
+                return Err(MemoStoreError::Read {
+                    path: self.path.clone(),
+                    source,
+                });
+            }
+        };
+        if current
+            .fingerprint
+            .as_ref()
+            .is_some_and(|fingerprint| fingerprint.matches_metadata(&metadata))
+        {
+            return Ok(None);
+        }
+
+        let bytes = read_bytes(&self.path)?;
+        let fingerprint = StoreFingerprint::from_parts(&bytes, &metadata);
+        if current
+            .fingerprint
+            .as_ref()
+            .is_some_and(|committed| committed.sha256 == fingerprint.sha256)
+        {
+            current.fingerprint = Some(fingerprint);
+            return Ok(None);
+        }
+
+        Ok(Some(ExternalChange { bytes, fingerprint }))
+    }
+}
+
+struct ExternalChange {
+    bytes: Vec<u8>,
+    fingerprint: StoreFingerprint,
+}
+
+impl StoreFingerprint {
+    fn from_parts(bytes: &[u8], metadata: &fs::Metadata) -> Self {
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            sha256: Sha256::digest(bytes).into(),
+        }
+    }
+
+    fn observe(path: &Path, committed: &[u8]) -> Option<Self> {
+        fs::metadata(path)
+            .ok()
+            .map(|metadata| Self::from_parts(committed, &metadata))
+    }
+
+    fn matches_metadata(&self, metadata: &fs::Metadata) -> bool {
+        self.modified.is_some()
+            && self.len == metadata.len()
+            && self.modified == metadata.modified().ok()
+    }
+}
+
+pub(super) fn read_store(path: &Path) -> Result<MemoStoreV2, MemoStoreError> {
+    read_strict(path, &AtomicFileCommit).map(|(store, _)| store)
 }
 
-fn read_strict(path: &Path) -> Result<MemoStoreV2, MemoStoreError> {
-    let bytes = match fs::read(path) {
-        Ok(bytes) => bytes,
+fn read_strict(
+    path: &Path,
+    committer: &impl CommitAdapter,
+) -> Result<(MemoStoreV2, Option<StoreFingerprint>), MemoStoreError> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
         Err(source) if source.kind() == io::ErrorKind::NotFound => {
-            return Ok(MemoStoreV2::default())
+            return Ok((MemoStoreV2::default(), None))
         }
         Err(source) => {
             return Err(MemoStoreError::Read {
@@ -117,6 +488,33 @@ fn read_strict(path: &Path) -> Result<MemoStoreV2, MemoStoreError> {
             });
         }
     };
+    let bytes = read_bytes(path)?;
+    let store = parse_strict(path, &bytes, committer)?;
+    Ok((store, Some(StoreFingerprint::from_parts(&bytes, &metadata))))
+}
+
+fn read_bytes(path: &Path) -> Result<Vec<u8>, MemoStoreError> {
+    fs::read(path).map_err(|source| MemoStoreError::Read {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn parse_strict(
+    path: &Path,
+    bytes: &[u8],
+    committer: &impl CommitAdapter,
+) -> Result<MemoStoreV2, MemoStoreError> {
+    let bytes = committer
+        .unseal(bytes)
+        .map_err(|source| MemoStoreError::Encryption {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    let bytes = encoding::decode(&bytes).map_err(|source| MemoStoreError::Decompress {
+        path: path.to_path_buf(),
+        source,
+    })?;
     let store = serde_json::from_slice(&bytes).map_err(|source| MemoStoreError::Malformed {
         path: path.to_path_buf(),
         source,
@@ -125,7 +523,7 @@ fn read_strict(path: &Path) -> Result<MemoStoreV2, MemoStoreError> {
     Ok(store)
 }
 
-fn validate_version(store: &MemoStoreV2) -> Result<(), MemoStoreError> {
+pub(super) fn validate_version(store: &MemoStoreV2) -> Result<(), MemoStoreError> {
     if store.version == MEMO_STORE_VERSION {
         Ok(())
     } else {
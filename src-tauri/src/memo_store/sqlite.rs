@@ -0,0 +1,537 @@
+mod attachments;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+
+use self::attachments::{Attachment, StoredAttachment};
+use super::change::{diff_stores, ChangeListener};
+use super::engine::MemoStoreEngine;
+use super::repository::{read_store, validate_version};
+use super::{MemoCollection, MemoStoreError, MemoStoreV2, StoreChange, StoreEncoding};
+use crate::models::MemoEntry;
+
+const SCHEMA_VERSION: i64 = 1;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+const SCHEMA: &str = "
+    CREATE TABLE store_header (
+        singleton INTEGER PRIMARY KEY CHECK (singleton = 1),
+        version INTEGER NOT NULL,
+        current_memo_id TEXT,
+        current_content TEXT NOT NULL,
+        encoding TEXT NOT NULL
+    );
+    CREATE TABLE collections (
+        name TEXT PRIMARY KEY NOT NULL
+    );
+    INSERT INTO collections (name) VALUES ('history'), ('archive'), ('trash');
+    CREATE TABLE entries (
+        row_id INTEGER PRIMARY KEY,
+        collection TEXT NOT NULL REFERENCES collections (name),
+        position INTEGER NOT NULL,
+        id TEXT NOT NULL,
+        content TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        UNIQUE (collection, position)
+    );
+    CREATE INDEX entries_by_id ON entries (id);
+    CREATE TABLE attachments (
+        sha256 TEXT PRIMARY KEY NOT NULL,
+        mime_type TEXT NOT NULL,
+        data BLOB NOT NULL
+    );
+    CREATE TABLE entry_attachments (
+        entry_row_id INTEGER NOT NULL REFERENCES entries (row_id) ON DELETE CASCADE,
+        sha256 TEXT NOT NULL REFERENCES attachments (sha256),
+        PRIMARY KEY (entry_row_id, sha256)
+    );
+";
+
+pub struct SqliteMemoStore {
+    path: PathBuf,
+    database: Mutex<Database>,
+    listeners: RwLock<Vec<ChangeListener>>,
+}
+
+struct Database {
+    connection: Connection,
+    loaded: Option<LoadedStore>,
+}
+
+struct LoadedStore {
+    data_version: i64,
+    store: MemoStoreV2,
+    rows: HashMap<String, i64>,
+}
+
+impl SqliteMemoStore {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, MemoStoreError> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|source| MemoStoreError::Read {
+                path: path.clone(),
+                source,
+            })?;
+        }
+
+        let mut connection = Connection::open(&path).map_err(|source| MemoStoreError::Sqlite {
+            path: path.clone(),
+            source,
+        })?;
+        prepare_connection(&mut connection).map_err(|source| MemoStoreError::Sqlite {
+            path: path.clone(),
+            source,
+        })?;
+        let store = Self {
+            path,
+            database: Mutex::new(Database {
+                connection,
+                loaded: None,
+            }),
+            listeners: RwLock::new(Vec::new()),
+        };
+        store.snapshot()?;
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn subscribe(
+        &self,
+        listener: impl Fn(&[StoreChange]) + Send + Sync + 'static,
+    ) -> Result<(), MemoStoreError> {
+        self.listeners
+            .write()
+            .map(|mut listeners| listeners.push(Box::new(listener)))
+            .map_err(|_| MemoStoreError::Poisoned)
+    }
+
+    pub fn snapshot(&self) -> Result<MemoStoreV2, MemoStoreError> {
+        let mut database = self.database.lock().map_err(|_| MemoStoreError::Poisoned)?;
+        let Database { connection, loaded } = &mut *database;
+        self.cached(connection, loaded)
+            .map(|loaded| loaded.store.clone())
+    }
+
+    pub fn transact<R>(
+        &self,
+        mutation: impl FnOnce(&mut MemoStoreV2) -> Result<R, MemoStoreError>,
+    ) -> Result<R, MemoStoreError> {
+        let (result, changes) = self.commit_mutation(mutation)?;
+        if !changes.is_empty() {
+            self.notify(&changes);
+        }
+        Ok(result)
+    }
+
+    pub fn move_entry(
+        &self,
+        id: &str,
+        source: MemoCollection,
+        destination: MemoCollection,
+    ) -> Result<bool, MemoStoreError> {
+        MemoStoreEngine::move_entry(self, id, source, destination)
+    }
+
+    pub fn import_json(&self, json_path: &Path) -> Result<bool, MemoStoreError> {
+        let imported = read_store(json_path)?;
+        self.transact(|store| {
+            if *store != MemoStoreV2::default() {
+                return Ok(false);
+            }
+            *store = imported;
+            Ok(true)
+        })
+    }
+
+    fn commit_mutation<R>(
+        &self,
+        mutation: impl FnOnce(&mut MemoStoreV2) -> Result<R, MemoStoreError>,
+    ) -> Result<(R, Vec<StoreChange>), MemoStoreError> {
+        let mut database = self.database.lock().map_err(|_| MemoStoreError::Poisoned)?;
+        let Database { connection, loaded } = &mut *database;
+        let transaction = connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(|source| self.sqlite_error(source))?;
+        let current = self.cached(&transaction, loaded)?;
+        let mut candidate = current.store.clone();
+        let result = mutation(&mut candidate)?;
+        candidate.enforce_limits();
+        validate_version(&candidate)?;
+        if candidate == current.store {
+            return Ok((result, Vec::new()));
+        }
+
+        let changes = diff_stores(&current.store, &candidate);
+        let written = write_changes(&transaction, &current.store, &candidate, &mut current.rows)
+            .and_then(|rows_known| transaction.commit().map(|()| rows_known));
+        match written {
+            Ok(true) => current.store = candidate,
+            Ok(false) => *loaded = None,
+            Err(source) => {
+                *loaded = None;
+                return Err(self.sqlite_error(source));
+            }
+        }
+        Ok((result, changes))
+    }
+
+    fn cached<'a>(
+        &self,
+        connection: &Connection,
+        loaded: &'a mut Option<LoadedStore>,
+    ) -> Result<&'a mut LoadedStore, MemoStoreError> {
+        let data_version: i64 = connection
+            .pragma_query_value(None, "data_version", |row| row.get(0))
+            .map_err(|source| self.sqlite_error(source))?;
+        let fresh = match loaded.take() {
+            Some(cached) if cached.data_version == data_version => cached,
+            _ => {
+                let (store, rows) = self.load(connection)?;
+                LoadedStore {
+                    data_version,
+                    store,
+                    rows,
+                }
+            }
+        };
+        Ok(loaded.insert(fresh))
+    }
+
+    fn load(
+        &self,
+        connection: &Connection,
+    ) -> Result<(MemoStoreV2, HashMap<String, i64>), MemoStoreError> {
+        let header = read_header(connection).map_err(|source| self.sqlite_error(source))?;
+        let mut store = match header {
+            Some((version, current_memo_id, current_content, encoding)) => MemoStoreV2 {
+                version,
+                current_memo_id,
+                current_content,
+                encoding: StoreEncoding::from_name(&encoding)
+                    .ok_or_else(|| self.corrupt(format!("unknown encoding {encoding}")))?,
+                ..MemoStoreV2::default()
+            },
+            None => MemoStoreV2::default(),
+        };
+        validate_version(&store)?;
+
+        let attachments =
+            read_attachments(connection).map_err(|source| self.sqlite_error(source))?;
+        let mut rows = HashMap::new();
+        for (row_id, collection, entry) in
+            read_entries(connection).map_err(|source| self.sqlite_error(source))?
+        {
+            let collection = MemoCollection::from_name(&collection)
+                .ok_or_else(|| self.corrupt(format!("unknown collection {collection}")))?;
+            let entry = MemoEntry {
+                content: attachments::inline(&entry.content, &attachments),
+                ..entry
+            };
+            rows.insert(entry.id.clone(), row_id);
+            store.insert_back(entry, collection);
+        }
+        Ok((store, rows))
+    }
+
+    fn notify(&self, changes: &[StoreChange]) {
+        if let Ok(listeners) = self.listeners.read() {
+            for listener in listeners.iter() {
+                listener(changes);
+            }
+        }
+    }
+
+    fn sqlite_error(&self, source: rusqlite::Error) -> MemoStoreError {
+        MemoStoreError::Sqlite {
+            path: self.path.clone(),
+            source,
+        }
+    }
+
+    fn corrupt(&self, reason: String) -> MemoStoreError {
+        MemoStoreError::CorruptDatabase {
+            path: self.path.clone(),
+            reason,
+        }
+    }
+}
+
+impl MemoStoreEngine for SqliteMemoStore {
+    fn snapshot(&self) -> Result<MemoStoreV2, MemoStoreError> {
+        SqliteMemoStore::snapshot(self)
+    }
+
+    fn transact<R>(
+        &self,
+        mutation: impl FnOnce(&mut MemoStoreV2) -> Result<R, MemoStoreError>,
+    ) -> Result<R, MemoStoreError> {
+        SqliteMemoStore::transact(self, mutation)
+    }
+
+    fn subscribe(
+        &self,
+        listener: impl Fn(&[StoreChange]) + Send + Sync + 'static,
+    ) -> Result<(), MemoStoreError> {
+        SqliteMemoStore::subscribe(self, listener)
+    }
+}
+
+type HeaderRow = (u32, Option<String>, String, String);
+type EntryPlaces<'a> = HashMap<&'a str, (MemoCollection, usize, &'a MemoEntry)>;
+
+fn prepare_connection(connection: &mut Connection) -> rusqlite::Result<()> {
+    connection.busy_timeout(BUSY_TIMEOUT)?;
+    connection.pragma_update(None, "journal_mode", "WAL")?;
+    connection.pragma_update(None, "foreign_keys", true)?;
+    let schema_version: i64 =
+        connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if schema_version < SCHEMA_VERSION {
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        transaction.execute_batch(SCHEMA)?;
+        transaction.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+fn read_header(connection: &Connection) -> rusqlite::Result<Option<HeaderRow>> {
+    connection
+        .query_row(
+            "SELECT version, current_memo_id, current_content, encoding
+             FROM store_header WHERE singleton = 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+}
+
+fn read_entries(connection: &Connection) -> rusqlite::Result<Vec<(i64, String, MemoEntry)>> {
+    let mut statement = connection.prepare(
+        "SELECT row_id, collection, id, content, timestamp
+         FROM entries ORDER BY collection, position",
+    )?;
+    let rows = statement.query_map([], |row| {
+        let timestamp: i64 = row.get(4)?;
+        Ok((
+            row.get(0)?,
+            row.get(1)?,
+            MemoEntry {
+                id: row.get(2)?,
+                content: row.get(3)?,
+                timestamp: timestamp as u64,
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+fn read_attachments(
+    connection: &Connection,
+) -> rusqlite::Result<HashMap<String, StoredAttachment>> {
+    let mut statement = connection.prepare("SELECT sha256, mime_type, data FROM attachments")?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get(0)?,
+            StoredAttachment {
+                mime_type: row.get(1)?,
+                data: row.get(2)?,
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+fn write_changes(
+    connection: &Connection,
+    current: &MemoStoreV2,
+    candidate: &MemoStoreV2,
+    rows: &mut HashMap<String, i64>,
+) -> rusqlite::Result<bool> {
+    connection.execute(
+        "INSERT INTO store_header (singleton, version, current_memo_id, current_content, encoding)
+         VALUES (1, ?1, ?2, ?3, ?4)
+         ON CONFLICT (singleton) DO UPDATE SET
+             version = excluded.version,
+             current_memo_id = excluded.current_memo_id,
+             current_content = excluded.current_content,
+             encoding = excluded.encoding",
+        params![
+            candidate.version,
+            candidate.current_memo_id,
+            candidate.current_content,
+            candidate.encoding.as_str(),
+        ],
+    )?;
+
+    let places = entry_places(current)
+        .filter(|before| before.keys().all(|id| rows.contains_key(*id)))
+        .zip(entry_places(candidate));
+    let rows_known = places.is_some();
+    match places {
+        Some((before, after)) => write_entries(connection, &before, &after, rows)?,
+        None => {
+            for collection in MemoCollection::ALL {
+                let entries = candidate.collection(collection);
+                if current.collection(collection) != entries {
+                    replace_collection(connection, collection, entries)?;
+                }
+            }
+            rows.clear();
+        }
+    }
+    connection.execute(
+        "DELETE FROM attachments
+         WHERE sha256 NOT IN (SELECT sha256 FROM entry_attachments)",
+        [],
+    )?;
+    Ok(rows_known)
+}
+
+fn entry_places(store: &MemoStoreV2) -> Option<EntryPlaces<'_>> {
+    let mut places = HashMap::new();
+    for collection in MemoCollection::ALL {
+        for (position, entry) in store.collection(collection).iter().enumerate() {
+            if places
+                .insert(entry.id.as_str(), (collection, position, entry))
+                .is_some()
+            {
+                return None;
+            }
+        }
+    }
+    Some(places)
+}
+
+fn write_entries(
+    connection: &Connection,
+    before: &EntryPlaces<'_>,
+    after: &EntryPlaces<'_>,
+    rows: &mut HashMap<String, i64>,
+) -> rusqlite::Result<()> {
+    let mut delete_entry = connection.prepare("DELETE FROM entries WHERE row_id = ?1")?;
+    for id in before.keys().filter(|id| !after.contains_key(*id)) {
+        if let Some(row_id) = rows.remove(*id) {
+            delete_entry.execute([row_id])?;
+        }
+    }
+
+    let moved: Vec<(i64, MemoCollection, usize)> = after
+        .iter()
+        .filter_map(|(id, (collection, position, _))| {
+            let (old_collection, old_position, _) = before.get(id)?;
+            ((old_collection, old_position) != (collection, position))
+                .then(|| (rows[*id], *collection, *position))
+        })
+        .collect();
+    let mut park_entry =
+        connection.prepare("UPDATE entries SET position = -row_id WHERE row_id = ?1")?;
+    for (row_id, _, _) in &moved {
+        park_entry.execute([row_id])?;
+    }
+
+    let mut inserter = EntryInserter::new(connection)?;
+    let mut update_entry =
+        connection.prepare("UPDATE entries SET content = ?2, timestamp = ?3 WHERE row_id = ?1")?;
+    let mut unlink_attachments =
+        connection.prepare("DELETE FROM entry_attachments WHERE entry_row_id = ?1")?;
+    for (id, (collection, position, entry)) in after {
+        match before.get(id) {
+            None => {
+                let row_id = inserter.insert(*collection, *position, entry)?;
+                rows.insert(entry.id.clone(), row_id);
+            }
+            Some((_, _, old)) if old != entry => {
+                let row_id = rows[*id];
+                let (content, found) = attachments::externalize(&entry.content);
+                update_entry.execute(params![row_id, content, entry.timestamp as i64])?;
+                unlink_attachments.execute([row_id])?;
+                inserter.link(row_id, found)?;
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mut place_entry = connection
+        .prepare("UPDATE entries SET collection = ?2, position = ?3 WHERE row_id = ?1")?;
+    for (row_id, collection, position) in moved {
+        place_entry.execute(params![row_id, collection.as_str(), position as i64])?;
+    }
+    Ok(())
+}
+
+fn replace_collection(
+    connection: &Connection,
+    collection: MemoCollection,
+    entries: &[MemoEntry],
+) -> rusqlite::Result<()> {
+    connection.execute(
+        "DELETE FROM entries WHERE collection = ?1",
+        [collection.as_str()],
+    )?;
+    let mut inserter = EntryInserter::new(connection)?;
+    for (position, entry) in entries.iter().enumerate() {
+        inserter.insert(collection, position, entry)?;
+    }
+    Ok(())
+}
+
+struct EntryInserter<'a> {
+    insert_entry: rusqlite::Statement<'a>,
+    insert_attachment: rusqlite::Statement<'a>,
+    link_attachment: rusqlite::Statement<'a>,
+}
+
+impl<'a> EntryInserter<'a> {
+    fn new(connection: &'a Connection) -> rusqlite::Result<Self> {
+        Ok(Self {
+            insert_entry: connection.prepare(
+                "INSERT INTO entries (collection, position, id, content, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?,
+            insert_attachment: connection.prepare(
+                "INSERT OR IGNORE INTO attachments (sha256, mime_type, data) VALUES (?1, ?2, ?3)",
+            )?,
+            link_attachment: connection.prepare(
+                "INSERT OR IGNORE INTO entry_attachments (entry_row_id, sha256) VALUES (?1, ?2)",
+            )?,
+        })
+    }
+
+    fn insert(
+        &mut self,
+        collection: MemoCollection,
+        position: usize,
+        entry: &MemoEntry,
+    ) -> rusqlite::Result<i64> {
+        let (content, found) = attachments::externalize(&entry.content);
+        let row_id = self.insert_entry.insert(params![
+            collection.as_str(),
+            position as i64,
+            entry.id,
+            content,
+            entry.timestamp as i64,
+        ])?;
+        self.link(row_id, found)?;
+        Ok(row_id)
+    }
+
+    fn link(&mut self, row_id: i64, found: Vec<Attachment>) -> rusqlite::Result<()> {
+        for attachment in found {
+            self.insert_attachment.execute(params![
+                attachment.sha256,
+                attachment.mime_type,
+                attachment.data,
+            ])?;
+            self.link_attachment
+                .execute(params![row_id, attachment.sha256])?;
+        }
+        Ok(())
+    }
+}
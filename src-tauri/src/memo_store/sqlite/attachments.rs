@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+const ATTACHMENT_SCHEME: &str = "upmemo-attachment:";
+const ESCAPED_SCHEME: &str = "upmemo-attachment:!";
+const DATA_IMAGE_PREFIX: &str = "data:image/";
+const BASE64_MARKER: &str = ";base64,";
+const SHA256_HEX_LEN: usize = 64;
+
+pub(super) struct Attachment {
+    pub(super) sha256: String,
+    pub(super) mime_type: String,
+    pub(super) data: Vec<u8>,
+}
+
+pub(super) struct StoredAttachment {
+    pub(super) mime_type: String,
+    pub(super) data: Vec<u8>,
+}
+
+pub(super) fn externalize(content: &str) -> (String, Vec<Attachment>) {
+    let mut output = String::with_capacity(content.len());
+    let mut attachments = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find(DATA_IMAGE_PREFIX) {
+        output.push_str(&rest[..start].replace(ATTACHMENT_SCHEME, ESCAPED_SCHEME));
+        let candidate = &rest[start..];
+        match parse_data_image(candidate) {
+            Some((attachment, consumed)) => {
+                output.push_str(ATTACHMENT_SCHEME);
+                output.push_str(&attachment.sha256);
+                attachments.push(attachment);
+                rest = &candidate[consumed..];
+            }
+            None => {
+                output.push_str(DATA_IMAGE_PREFIX);
+                rest = &candidate[DATA_IMAGE_PREFIX.len()..];
+            }
+        }
+    }
+    output.push_str(&rest.replace(ATTACHMENT_SCHEME, ESCAPED_SCHEME));
+    (output, attachments)
+}
+
+pub(super) fn inline(content: &str, attachments: &HashMap<String, StoredAttachment>) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find(ATTACHMENT_SCHEME) {
+        output.push_str(&rest[..start]);
+        if rest[start..].starts_with(ESCAPED_SCHEME) {
+            output.push_str(ATTACHMENT_SCHEME);
+            rest = &rest[start + ESCAPED_SCHEME.len()..];
+            continue;
+        }
+        let candidate = &rest[start + ATTACHMENT_SCHEME.len()..];
+        let stored = candidate
+            .get(..SHA256_HEX_LEN)
+            .and_then(|sha256| attachments.get(sha256));
+        match stored {
+            Some(stored) => {
+                output.push_str("data:");
+                output.push_str(&stored.mime_type);
+                output.push_str(BASE64_MARKER);
+                output.push_str(&STANDARD.encode(&stored.data));
+                rest = &candidate[SHA256_HEX_LEN..];
+            }
+            None => {
+                output.push_str(ATTACHMENT_SCHEME);
+                rest = candidate;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+fn parse_data_image(candidate: &str) -> Option<(Attachment, usize)> {
+    let subtype_len = candidate[DATA_IMAGE_PREFIX.len()..].find(|character: char| {
+        !(character.is_ascii_alphanumeric() || matches!(character, '.' | '+' | '-'))
+    })?;
+    let mime_end = DATA_IMAGE_PREFIX.len() + subtype_len;
+    if subtype_len == 0 || !candidate[mime_end..].starts_with(BASE64_MARKER) {
+        return None;
+    }
+
+    let payload_start = mime_end + BASE64_MARKER.len();
+    let payload_len = candidate[payload_start..]
+        .find(|character: char| {
+            !(character.is_ascii_alphanumeric() || matches!(character, '+' | '/' | '='))
+        })
+        .unwrap_or(candidate.len() - payload_start);
+    let payload = &candidate[payload_start..payload_start + payload_len];
+    let data = STANDARD.decode(payload).ok()?;
+    if data.is_empty() || STANDARD.encode(&data) != payload {
+        return None;
+    }
+
+    let mime_type = &candidate["data:".len()..mime_end];
+    let digest = Sha256::new()
+        .chain_update(mime_type)
+        .chain_update([0])
+        .chain_update(&data)
+        .finalize();
+    let attachment = Attachment {
+        sha256: format!("{digest:x}"),
+        mime_type: mime_type.to_string(),
+        data,
+    };
+    Some((attachment, payload_start + payload_len))
+}
@@ -0,0 +1,57 @@
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub(super) struct StoreLock {
+    file: File,
+}
+
+impl StoreLock {
+    pub(super) fn acquire(path: &Path) -> Result<Self, StoreLockError> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent).map_err(|source| StoreLockError {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .map_err(|source| StoreLockError {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        file.lock().map_err(|source| StoreLockError {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Ok(Self { file })
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+pub(super) struct StoreLockError {
+    pub(super) path: PathBuf,
+    pub(super) source: io::Error,
+}
+
+pub(super) fn lock_path_for(store_path: &Path) -> PathBuf {
+    let mut file_name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    file_name.push(".lock");
+    store_path.with_file_name(file_name)
+}
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager, State};
+use upmemo::draft_journal::DraftJournal;
+use upmemo::memo_store::{MemoStoreRepository, SealedCommit};
+
+use crate::{
+    app_lock_commands, draft_commands, mirror_commands, store_events, store_flush, store_watcher,
+    sync_commands, version_history_commands,
+};
+
+pub type AppMemoStore = MemoStoreRepository<SealedCommit>;
+
+pub fn memo_store(app: &AppHandle) -> Result<State<'_, AppMemoStore>, String> {
+    app_lock_commands::ensure_app_unlocked(app)?;
+    app.try_state::<AppMemoStore>()
+        .ok_or_else(|| "記事已加密，請先輸入密碼解鎖".to_string())
+}
+
+pub fn autosave_memo_store(app: &AppHandle) -> Result<State<'_, AppMemoStore>, String> {
+    app_lock_commands::ensure_app_writable(app)?;
+    app.try_state::<AppMemoStore>()
+        .ok_or_else(|| "記事已加密，請先輸入密碼解鎖".to_string())
+}
+
+pub fn memo_store_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|directory| directory.join("memo_store.json"))
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))
+}
+
+pub fn install_memo_store(
+    app: &AppHandle,
+    repository: AppMemoStore,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let app_data_dir = app.path().app_data_dir()?;
+    let repository = repository.with_write_behind(store_flush::WRITE_BEHIND_WINDOW);
+    app.manage(draft_commands::DraftState::open(
+        DraftJournal::new(app_data_dir.join("drafts")),
+        &repository.snapshot()?,
+    )?);
+    app.manage(repository);
+    store_flush::spawn_write_behind_flusher(app);
+    store_events::forward_store_changes(app)?;
+    app.manage(store_watcher::watch_memo_store(app)?);
+    mirror_commands::install_plain_mirror(app)?;
+    version_history_commands::install_version_history(app)?;
+    sync_commands::install_memo_sync(app)?;
+    Ok(())
+}
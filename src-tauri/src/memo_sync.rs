@@ -0,0 +1,9 @@
+mod backend;
+mod engine;
+mod folder;
+mod state;
+
+pub use backend::{ChangeRecord, SyncBackend, SyncBackendConfig, SyncError};
+pub use engine::{publish_pending, sync_memos, SyncConflict, SyncReport, CONFLICT_COPY_TAG};
+pub use folder::{FolderBackend, SYNC_DIRECTORY};
+pub use state::SyncState;
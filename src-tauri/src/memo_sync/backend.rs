@@ -0,0 +1,77 @@
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::folder::FolderBackend;
+use crate::memo_store::{CommitError, MemoCollection};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ChangeRecord {
+    pub id: String,
+    pub collection: Option<MemoCollection>,
+    pub content: String,
+    pub timestamp: u64,
+    pub changed_at: u64,
+    pub device: String,
+}
+
+impl ChangeRecord {
+    pub(super) fn fingerprint(&self) -> Option<String> {
+        self.collection
+            .map(|collection| fingerprint(collection, &self.content, self.timestamp))
+    }
+}
+
+pub(super) fn fingerprint(collection: MemoCollection, content: &str, timestamp: u64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(collection.as_str().as_bytes());
+    hasher.update([0]);
+    hasher.update(timestamp.to_le_bytes());
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+pub trait SyncBackend: Send + Sync {
+    fn publish(&self, device: &str, records: &[ChangeRecord]) -> Result<(), SyncError>;
+    fn fetch(&self, device: &str) -> Result<Vec<ChangeRecord>, SyncError>;
+    fn withdraw(&self, device: &str) -> Result<(), SyncError>;
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncBackendConfig {
+    Folder { path: PathBuf },
+}
+
+impl SyncBackendConfig {
+    pub fn open(&self) -> Box<dyn SyncBackend> {
+        match self {
+            Self::Folder { path } => Box::new(FolderBackend::new(path)),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SyncError {
+    #[error("failed to {operation} {path}: {source}")]
+    Io {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("sync data {path} is malformed: {source}")]
+    Malformed {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("failed to serialize sync data: {0}")]
+    Serialize(#[source] serde_json::Error),
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+}
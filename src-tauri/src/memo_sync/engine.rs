@@ -0,0 +1,298 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use super::backend::{fingerprint, ChangeRecord, SyncBackend, SyncError};
+use super::state::SyncState;
+use crate::memo_import::append_tags;
+use crate::memo_store::{MemoCollection, MemoStoreV2, HISTORY_LIMIT};
+use crate::models::MemoEntry;
+use crate::private_memo;
+
+pub const CONFLICT_COPY_TAG: &str = "衝突副本";
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct SyncConflict {
+    pub id: String,
+    pub copy_id: String,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct SyncReport {
+    pub pushed: Vec<String>,
+    pub pulled: Vec<String>,
+    pub conflicts: Vec<SyncConflict>,
+    pub skipped_private: usize,
+    pub current_replaced: bool,
+}
+
+impl SyncReport {
+    pub fn is_empty(&self) -> bool {
+        self.pushed.is_empty() && self.pulled.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+enum Decision {
+    Push,
+    Pull,
+    Settle,
+    Conflict { remote_wins: bool },
+}
+
+pub fn sync_memos(
+    store: &mut MemoStoreV2,
+    state: &mut SyncState,
+    backend: &dyn SyncBackend,
+    now: u64,
+) -> Result<SyncReport, SyncError> {
+    let device = state.device_id.clone();
+    let remote = latest_records(backend.fetch(&device)?, &device);
+
+    let mut report = SyncReport::default();
+    adopt_open_edit(store, now);
+    let mut private = BTreeSet::new();
+    let mut local: HashMap<String, (MemoCollection, MemoEntry)> = HashMap::new();
+    for (collection, entries) in [
+        (MemoCollection::History, &store.history),
+        (MemoCollection::Archive, &store.archive),
+        (MemoCollection::Trash, &store.trash),
+    ] {
+        for entry in entries {
+            if private_memo::is_private(&entry.content) {
+                private.insert(entry.id.clone());
+            } else {
+                local
+                    .entry(entry.id.clone())
+                    .or_insert_with(|| (collection, entry.clone()));
+            }
+        }
+    }
+    report.skipped_private = private.len();
+
+    let mut outgoing = Vec::new();
+    for id in &private {
+        if state.synced.remove(id).is_some() {
+            outgoing.push(local_record(id, None, &device, now));
+            report.pushed.push(id.clone());
+        }
+    }
+
+    let ids: BTreeSet<String> = local
+        .keys()
+        .chain(remote.keys())
+        .chain(state.synced.keys())
+        .filter(|id| !private.contains(*id))
+        .cloned()
+        .collect();
+    for id in ids {
+        let base = state.synced.get(&id).cloned();
+        let local_entry = local.get(&id);
+        let local_fingerprint = local_entry
+            .map(|(collection, entry)| fingerprint(*collection, &entry.content, entry.timestamp));
+        let record = remote.get(&id);
+        let remote_fingerprint = record.and_then(ChangeRecord::fingerprint);
+        let local_changed = local_fingerprint != base;
+        let remote_changed = record.is_some() && remote_fingerprint != base;
+
+        let decision = match (local_changed, remote_changed) {
+            (false, false) => continue,
+            (true, false) => Decision::Push,
+            (false, true) => Decision::Pull,
+            (true, true) if local_fingerprint == remote_fingerprint => Decision::Settle,
+            (true, true) => match (
+                local_entry,
+                record.filter(|record| record.collection.is_some()),
+            ) {
+                (None, _) => Decision::Pull,
+                (Some(_), None) => Decision::Push,
+                (Some((_, entry)), Some(record)) if entry.content == record.content => {
+                    if record.timestamp > entry.timestamp {
+                        Decision::Pull
+                    } else {
+                        Decision::Push
+                    }
+                }
+                (Some((_, entry)), Some(record)) => Decision::Conflict {
+                    remote_wins: record.timestamp > entry.timestamp,
+                },
+            },
+        };
+
+        let settled = match decision {
+            Decision::Push => {
+                outgoing.push(local_record(&id, local_entry, &device, now));
+                report.pushed.push(id.clone());
+                local_fingerprint
+            }
+            Decision::Pull => {
+                if let Some(record) = record {
+                    report.current_replaced |= apply_record(store, record);
+                }
+                report.pulled.push(id.clone());
+                remote_fingerprint
+            }
+            Decision::Settle => local_fingerprint,
+            Decision::Conflict { remote_wins } => {
+                let (Some((_, entry)), Some(record)) = (local_entry, record) else {
+                    continue;
+                };
+                let (loser_content, loser_timestamp) = if remote_wins {
+                    (&entry.content, entry.timestamp)
+                } else {
+                    (&record.content, record.timestamp)
+                };
+                let mut content = loser_content.clone();
+                append_tags(&mut content, &[CONFLICT_COPY_TAG.to_string()]);
+                let copy = MemoEntry {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    content,
+                    timestamp: loser_timestamp,
+                };
+                let copy_collection = place(store, MemoCollection::History, copy.clone());
+                state.synced.insert(
+                    copy.id.clone(),
+                    fingerprint(copy_collection, &copy.content, copy.timestamp),
+                );
+                outgoing.push(ChangeRecord {
+                    id: copy.id.clone(),
+                    collection: Some(copy_collection),
+                    content: copy.content,
+                    timestamp: copy.timestamp,
+                    changed_at: now,
+                    device: device.clone(),
+                });
+                report.conflicts.push(SyncConflict {
+                    id: id.clone(),
+                    copy_id: copy.id,
+                });
+
+                if remote_wins {
+                    report.current_replaced |= apply_record(store, record);
+                    remote_fingerprint
+                } else {
+                    outgoing.push(local_record(&id, local_entry, &device, now));
+                    local_fingerprint
+                }
+            }
+        };
+        match settled {
+            Some(fingerprint) => state.synced.insert(id, fingerprint),
+            None => state.synced.remove(&id),
+        };
+    }
+
+    for record in outgoing {
+        state.pending.retain(|pending| pending.id != record.id);
+        state.pending.push(record);
+    }
+    state.last_sync = Some(now);
+    Ok(report)
+}
+
+pub fn publish_pending(state: &mut SyncState, backend: &dyn SyncBackend) -> Result<(), SyncError> {
+    if !state.pending.is_empty() {
+        backend.publish(&state.device_id, &state.pending)?;
+        state.pending.clear();
+    }
+    Ok(())
+}
+
+fn latest_records(records: Vec<ChangeRecord>, device: &str) -> HashMap<String, ChangeRecord> {
+    let mut latest: HashMap<String, ChangeRecord> = HashMap::new();
+    for record in records {
+        if record.device == device {
+            continue;
+        }
+        let newer = latest.get(&record.id).is_none_or(|existing| {
+            (record.changed_at, &record.device) > (existing.changed_at, &existing.device)
+        });
+        if newer {
+            latest.insert(record.id.clone(), record);
+        }
+    }
+    latest
+}
+
+fn local_record(
+    id: &str,
+    local: Option<&(MemoCollection, MemoEntry)>,
+    device: &str,
+    now: u64,
+) -> ChangeRecord {
+    let (collection, content, timestamp) = match local {
+        Some((collection, entry)) => (Some(*collection), entry.content.clone(), entry.timestamp),
+        None => (None, String::new(), 0),
+    };
+    ChangeRecord {
+        id: id.to_string(),
+        collection,
+        content,
+        timestamp,
+        changed_at: now,
+        device: device.to_string(),
+    }
+}
+
+fn adopt_open_edit(store: &mut MemoStoreV2, now: u64) {
+    let Some(id) = store.current_memo_id.as_deref() else {
+        return;
+    };
+    let content = &store.current_content;
+    if content.trim().is_empty() || private_memo::is_private(content) {
+        return;
+    }
+    let open = store
+        .history
+        .iter_mut()
+        .chain(store.archive.iter_mut())
+        .chain(store.trash.iter_mut())
+        .find(|entry| entry.id == id);
+    if let Some(entry) = open {
+        if entry.content != *content && !private_memo::is_private(&entry.content) {
+            entry.content = content.clone();
+            entry.timestamp = now;
+        }
+    }
+}
+
+fn apply_record(store: &mut MemoStoreV2, record: &ChangeRecord) -> bool {
+    for entries in [&mut store.history, &mut store.archive, &mut store.trash] {
+        entries.retain(|entry| entry.id != record.id);
+    }
+    let open = store.current_memo_id.as_deref() == Some(record.id.as_str());
+    if open {
+        match record.collection {
+            Some(_) => store.current_content = record.content.clone(),
+            None => {
+                store.current_memo_id = None;
+                store.current_content.clear();
+            }
+        }
+    }
+    if let Some(collection) = record.collection {
+        place(
+            store,
+            collection,
+            MemoEntry {
+                id: record.id.clone(),
+                content: record.content.clone(),
+                timestamp: record.timestamp,
+            },
+        );
+    }
+    open
+}
+
+fn place(store: &mut MemoStoreV2, collection: MemoCollection, entry: MemoEntry) -> MemoCollection {
+    let collection = match collection {
+        MemoCollection::History if store.history.len() >= HISTORY_LIMIT => MemoCollection::Archive,
+        collection => collection,
+    };
+    let entries = match collection {
+        MemoCollection::History => &mut store.history,
+        MemoCollection::Archive => &mut store.archive,
+        MemoCollection::Trash => &mut store.trash,
+    };
+    entries.insert(0, entry);
+    collection
+}
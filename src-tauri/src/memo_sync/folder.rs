@@ -0,0 +1,97 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::backend::{ChangeRecord, SyncBackend, SyncError};
+use crate::memo_store::{AtomicFileCommit, CommitAdapter};
+use crate::models::memo_file_name;
+
+pub const SYNC_DIRECTORY: &str = "upmemo-sync";
+const RECORD_EXTENSION: &str = "json";
+
+pub struct FolderBackend {
+    root: PathBuf,
+}
+
+impl FolderBackend {
+    pub fn new(folder: impl AsRef<Path>) -> Self {
+        Self {
+            root: folder.as_ref().join(SYNC_DIRECTORY),
+        }
+    }
+}
+
+impl SyncBackend for FolderBackend {
+    fn publish(&self, device: &str, records: &[ChangeRecord]) -> Result<(), SyncError> {
+        let directory = self.root.join(device);
+        for record in records {
+            let bytes = serde_json::to_vec_pretty(record).map_err(SyncError::Serialize)?;
+            AtomicFileCommit.commit(
+                &directory.join(memo_file_name(&record.id, RECORD_EXTENSION)),
+                &bytes,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn fetch(&self, device: &str) -> Result<Vec<ChangeRecord>, SyncError> {
+        let mut records = Vec::new();
+        for peer in read_directory(&self.root)? {
+            if !peer.is_dir() || peer.file_name().is_some_and(|name| name == device) {
+                continue;
+            }
+            for path in read_directory(&peer)? {
+                if path
+                    .extension()
+                    .is_none_or(|extension| extension != RECORD_EXTENSION)
+                {
+                    continue;
+                }
+                let bytes = fs::read(&path).map_err(|source| SyncError::Io {
+                    operation: "read",
+                    path: path.clone(),
+                    source,
+                })?;
+                let record = serde_json::from_slice(&bytes)
+                    .map_err(|source| SyncError::Malformed { path, source })?;
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    fn withdraw(&self, device: &str) -> Result<(), SyncError> {
+        let directory = self.root.join(device);
+        match fs::remove_dir_all(&directory) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(SyncError::Io {
+                operation: "remove",
+                path: directory,
+                source,
+            }),
+        }
+    }
+}
+
+fn read_directory(directory: &Path) -> Result<Vec<PathBuf>, SyncError> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => {
+            return Err(SyncError::Io {
+                operation: "list",
+                path: directory.to_path_buf(),
+                source,
+            })
+        }
+    };
+    entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()
+        .map_err(|source| SyncError::Io {
+            operation: "list",
+            path: directory.to_path_buf(),
+            source,
+        })
+}
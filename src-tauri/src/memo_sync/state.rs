@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::backend::{ChangeRecord, SyncBackendConfig, SyncError};
+use crate::memo_store::CommitAdapter;
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SyncState {
+    pub device_id: String,
+    #[serde(default)]
+    pub backend: Option<SyncBackendConfig>,
+    #[serde(default)]
+    pub last_sync: Option<u64>,
+    #[serde(default)]
+    pub(super) synced: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(super) pending: Vec<ChangeRecord>,
+}
+
+impl SyncState {
+    pub fn new() -> Self {
+        Self {
+            device_id: uuid::Uuid::new_v4().to_string(),
+            backend: None,
+            last_sync: None,
+            synced: BTreeMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, SyncError> {
+        match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|source| SyncError::Malformed {
+                path: path.to_path_buf(),
+                source,
+            }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(source) => Err(SyncError::Io {
+                operation: "read",
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    pub fn save(&self, path: &Path, committer: &impl CommitAdapter) -> Result<(), SyncError> {
+        let bytes = serde_json::to_vec_pretty(self).map_err(SyncError::Serialize)?;
+        committer.commit(path, &bytes)?;
+        Ok(())
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn set_backend(&mut self, backend: Option<SyncBackendConfig>) {
+        if self.backend != backend {
+            self.synced.clear();
+            self.pending.clear();
+            self.last_sync = None;
+            self.backend = backend;
+        }
+    }
+}
+
+impl Default for SyncState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,268 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager};
+use upmemo::markdown_export::ATTACHMENTS_DIR;
+use upmemo::memo_store::AtomicFileCommit;
+use upmemo::plain_mirror::{
+    plan_plain_mirror, read_mirror_config, remove_plain_mirror, write_mirror_config, MirrorConfig,
+    MirrorFormat, MirrorOptions, MirrorReport, PlainMirrorError, MIRROR_STATE_FILE,
+};
+
+use crate::clock::get_timestamp;
+use crate::memo_store_state::{memo_store, AppMemoStore};
+use crate::note_commands::map_memo_store_error;
+
+pub const PLAIN_MIRROR_SYNCED_EVENT: &str = "plain-mirror-synced";
+const MIRROR_CONFIG_FILE: &str = "plain_mirror.json";
+const SYNC_DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub struct PlainMirror {
+    config: Mutex<Option<MirrorConfig>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    trigger: Mutex<Sender<()>>,
+    syncing: Mutex<()>,
+}
+
+impl PlainMirror {
+    fn config(&self) -> MutexGuard<'_, Option<MirrorConfig>> {
+        self.config
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn watcher(&self) -> MutexGuard<'_, Option<RecommendedWatcher>> {
+        self.watcher
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn syncing(&self) -> MutexGuard<'_, ()> {
+        self.syncing
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn trigger(&self) -> Sender<()> {
+        self.trigger
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+pub fn install_plain_mirror(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = read_mirror_config(&config_path(app)?)?;
+    let encrypted = app.state::<AppMemoStore>().committer().key().is_some();
+    if let Some(folder) = config
+        .as_ref()
+        .filter(|_| encrypted)
+        .map(|config| config.folder.clone())
+    {
+        match remove_plain_mirror(&folder) {
+            Ok(_) => {
+                write_mirror_config(&config_path(app)?, None, &AtomicFileCommit)?;
+                config = None;
+            }
+            Err(error) => eprintln!("無法移除鏡像資料夾中未加密的記事: {}", error),
+        }
+    }
+    let (trigger, requests) = mpsc::channel();
+
+    let listener = trigger.clone();
+    app.state::<AppMemoStore>().subscribe(move |_| {
+        let _ = listener.send(());
+    })?;
+
+    let watcher =
+        config.as_ref().and_then(
+            |config| match watch_folder(&config.folder, trigger.clone()) {
+                Ok(watcher) => Some(watcher),
+                Err(error) => {
+                    eprintln!("無法監看鏡像資料夾 {}: {}", config.folder.display(), error);
+                    None
+                }
+            },
+        );
+    if config.is_some() {
+        let _ = trigger.send(());
+    }
+
+    app.manage(PlainMirror {
+        config: Mutex::new(config),
+        watcher: Mutex::new(watcher),
+        trigger: Mutex::new(trigger),
+        syncing: Mutex::new(()),
+    });
+    spawn_mirror_worker(app, requests);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_plain_mirror(app_handle: AppHandle) -> Option<MirrorConfig> {
+    app_handle
+        .try_state::<PlainMirror>()
+        .and_then(|mirror| mirror.config().clone())
+}
+
+#[tauri::command]
+pub fn enable_plain_mirror(
+    app_handle: AppHandle,
+    folder: String,
+    format: MirrorFormat,
+) -> Result<MirrorReport, String> {
+    let folder = folder.trim();
+    if folder.is_empty() {
+        return Err("請輸入鏡像資料夾路徑".to_string());
+    }
+    if memo_store(&app_handle)?.committer().key().is_some() {
+        return Err("記事已加密，無法啟用鏡像資料夾".to_string());
+    }
+
+    let config = MirrorConfig {
+        folder: PathBuf::from(folder),
+        format,
+    };
+    write_mirror_config(&config_path(&app_handle)?, Some(&config), &AtomicFileCommit)
+        .map_err(map_plain_mirror_error)?;
+    let mirror = app_handle.state::<PlainMirror>();
+    *mirror.config() = Some(config.clone());
+
+    let report = match sync_now(&app_handle) {
+        Ok(report) => report.unwrap_or_default(),
+        Err(error) => {
+            disable_plain_mirror(app_handle)?;
+            return Err(error);
+        }
+    };
+    let watcher = watch_folder(&config.folder, mirror.trigger())
+        .map_err(|error| format!("無法監看鏡像資料夾: {error}"))?;
+    *mirror.watcher() = Some(watcher);
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn disable_plain_mirror(app_handle: AppHandle) -> Result<(), String> {
+    write_mirror_config(&config_path(&app_handle)?, None, &AtomicFileCommit)
+        .map_err(map_plain_mirror_error)?;
+    if let Some(mirror) = app_handle.try_state::<PlainMirror>() {
+        *mirror.watcher() = None;
+        *mirror.config() = None;
+    }
+    Ok(())
+}
+
+pub fn withdraw_plain_mirror(app: &AppHandle) -> Result<(), String> {
+    let Some(mirror) = app.try_state::<PlainMirror>() else {
+        return Ok(());
+    };
+    let _syncing = mirror.syncing();
+    let Some(config) = mirror.config().clone() else {
+        return Ok(());
+    };
+    *mirror.watcher() = None;
+    remove_plain_mirror(&config.folder)
+        .map_err(|error| format!("無法移除鏡像資料夾中未加密的記事: {error}"))?;
+    disable_plain_mirror(app.clone())
+}
+
+#[tauri::command]
+pub fn sync_plain_mirror_now(app_handle: AppHandle) -> Result<MirrorReport, String> {
+    sync_now(&app_handle)?.ok_or_else(|| "尚未設定鏡像資料夾".to_string())
+}
+
+fn sync_now(app: &AppHandle) -> Result<Option<MirrorReport>, String> {
+    let repository = memo_store(app)?;
+    let mirror = app.state::<PlainMirror>();
+    let _syncing = mirror.syncing();
+    let Some(config) = mirror.config().clone() else {
+        return Ok(None);
+    };
+    if repository.committer().key().is_some() {
+        return Err("記事已加密，無法同步鏡像資料夾".to_string());
+    }
+    let attachments = app
+        .path()
+        .app_data_dir()
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))?
+        .join(ATTACHMENTS_DIR);
+    let options = MirrorOptions {
+        folder: &config.folder,
+        format: config.format,
+        attachments: &attachments,
+        now: get_timestamp(),
+    };
+
+    let plan = repository
+        .transact(|store| Ok(plan_plain_mirror(store, &options)))
+        .map_err(map_memo_store_error)?
+        .map_err(map_plain_mirror_error)?;
+    plan.apply(&AtomicFileCommit)
+        .map(Some)
+        .map_err(map_plain_mirror_error)
+}
+
+fn spawn_mirror_worker(app: &AppHandle, requests: Receiver<()>) {
+    let app_handle = app.clone();
+    thread::spawn(move || {
+        while requests.recv().is_ok() {
+            while requests.recv_timeout(SYNC_DEBOUNCE).is_ok() {}
+            match sync_now(&app_handle) {
+                Ok(Some(report)) if !report.is_empty() => {
+                    if let Err(error) = app_handle.emit(PLAIN_MIRROR_SYNCED_EVENT, report) {
+                        eprintln!("無法通知鏡像資料夾同步結果: {}", error);
+                    }
+                }
+                Ok(_) => {}
+                Err(error) => eprintln!("鏡像資料夾同步失敗: {}", error),
+            }
+        }
+    });
+}
+
+fn watch_folder(
+    folder: &Path,
+    trigger: Sender<()>,
+) -> Result<RecommendedWatcher, Box<dyn std::error::Error>> {
+    fs::create_dir_all(folder)?;
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+            Ok(event) if touches_mirror(&event) => {
+                let _ = trigger.send(());
+            }
+            Ok(_) => {}
+            Err(error) => eprintln!("鏡像資料夾監看失敗: {}", error),
+        })?;
+    watcher.watch(folder, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+fn touches_mirror(event: &Event) -> bool {
+    !event.kind.is_access()
+        && event.paths.iter().any(|path| {
+            path.file_name()
+                .is_some_and(|name| name != MIRROR_STATE_FILE)
+        })
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|directory| directory.join(MIRROR_CONFIG_FILE))
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))
+}
+
+fn map_plain_mirror_error(error: PlainMirrorError) -> String {
+    match error {
+        PlainMirrorError::FolderInUse { path } => format!(
+            "鏡像資料夾 {} 已有其他 Markdown 或 HTML 檔案，請選擇空白或專用的資料夾",
+            path.display()
+        ),
+        error => format!("鏡像資料夾同步失敗: {error}"),
+    }
+}
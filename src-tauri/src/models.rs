@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::Mutex;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
@@ -8,6 +9,20 @@ pub struct MemoEntry {
     pub timestamp: u64,
 }
 
+pub fn memo_file_name(id: &str, extension: &str) -> String {
+    let safe = !id.is_empty()
+        && !id.starts_with('.')
+        && id.chars().all(|character| {
+            character.is_ascii_alphanumeric() || matches!(character, '-' | '_' | '.')
+        });
+    if safe {
+        format!("{id}.{extension}")
+    } else {
+        let digest = format!("{:x}", Sha256::digest(id.as_bytes()));
+        format!("{}.{extension}", &digest[..32])
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct FontConfig {
     pub chinese_font: String,
@@ -16,5 +31,4 @@ pub struct FontConfig {
 
 pub struct AppState {
     pub current_shortcut: Mutex<Option<String>>,
-    pub current_memo_id: Mutex<Option<String>>,
 }
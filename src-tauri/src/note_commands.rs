@@ -1,89 +1,161 @@
 use crate::clock::get_timestamp;
-use upmemo::models::AppState;
+use crate::draft_commands;
+use crate::memo_store_state::{autosave_memo_store, memo_store};
+use crate::private_memo_commands::{self, map_private_memo_error};
+
+use serde::Serialize;
 use tauri::Manager;
+use upmemo::memo_store::{MemoStoreError, MemoStoreV2, StoreKey};
+use upmemo::private_memo::{self, PrivateMemoSessions};
+use upmemo::secret_scan::{self, SecretFinding, SecretMarkers};
+
+#[derive(Serialize)]
+pub struct SavedNote {
+    memo_id: Option<String>,
+    secret_warnings: Vec<SecretFinding>,
+    lock_pending: bool,
+}
 
-use upmemo::memo_store::{MemoStoreError, MemoStoreRepository};
-use upmemo::models::MemoEntry;
+pub fn open_secret_markers(
+    app: &tauri::AppHandle,
+) -> Result<SecretMarkers, Box<dyn std::error::Error>> {
+    let path = app.path().app_data_dir()?.join("secret_markers.json");
+    Ok(SecretMarkers::open(path)?)
+}
 
 #[tauri::command]
 pub fn load_note(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
-
-    repository
-        .snapshot()
-        .map(|store| store.current_content)
-        .map_err(map_memo_store_error)
+    let repository = memo_store(&app_handle)?;
+    let sessions = app_handle.state::<PrivateMemoSessions>();
+
+    let store = repository.snapshot().map_err(map_memo_store_error)?;
+    let id = store.current_memo_id.clone().unwrap_or_default();
+    match private_memo_commands::readable_content(&sessions, &id, &store.current_content) {
+        Err(MemoStoreError::PrivateMemo { .. }) => Ok(String::new()),
+        readable => readable.map_err(map_memo_store_error),
+    }
 }
 
 #[tauri::command]
 pub fn save_note(app_handle: tauri::AppHandle, content: String) -> Result<(), String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
+    let repository = autosave_memo_store(&app_handle)?;
+    let sessions = app_handle.state::<PrivateMemoSessions>();
 
     repository
         .transact(|store| {
-            store.current_content = content.clone();
+            match private_memo_commands::current_memo_key(store, &sessions)? {
+                Some(key) => reseal_current(store, &key, &content)?,
+                None => store.current_content = content.clone(),
+            }
             Ok(())
         })
         .map_err(map_memo_store_error)
 }
 
 #[tauri::command]
-pub fn save_note_to_history(app_handle: tauri::AppHandle, content: String) -> Result<(), String> {
-    let state = app_handle.state::<AppState>();
-    let memo_id = match state.current_memo_id.lock() {
-        Ok(mut current_id) => {
-            if let Some(current_id) = current_id.as_ref() {
-                current_id.clone()
-            } else {
-                let new_id = get_timestamp().to_string();
-                *current_id = Some(new_id.clone());
-                new_id
+pub fn save_note_to_history(
+    app_handle: tauri::AppHandle,
+    content: String,
+    auto_lock: bool,
+) -> Result<SavedNote, String> {
+    let repository = autosave_memo_store(&app_handle)?;
+    let sessions = app_handle.state::<PrivateMemoSessions>();
+    let markers = app_handle.state::<SecretMarkers>();
+    let secret_warnings = secret_scan::scan(&content);
+
+    let saved = repository
+        .transact(|store| {
+            if let Some(key) = private_memo_commands::current_memo_key(store, &sessions)? {
+                let memo_id = store.current_memo_id.clone().unwrap_or_default();
+                reseal_current(store, &key, &content)?;
+                let unchanged = store
+                    .history
+                    .iter()
+                    .find(|entry| entry.id == memo_id)
+                    .and_then(|entry| private_memo::open_content(&key, &entry.content).ok())
+                    .is_some_and(|existing| existing == content);
+                if !unchanged && !content.trim().is_empty() {
+                    let sealed = private_memo_commands::seal(&memo_id, &key, &content)?;
+                    update_or_save_to_history_with_id(store, memo_id, &sealed)?;
+                }
+                return Ok(None);
             }
-        }
-        Err(_) => return Err("無法鎖定當前便條狀態".to_string()),
-    };
-
-    let repository = app_handle.state::<MemoStoreRepository>();
 
-    repository
-        .transact(|store| {
-            store.current_content = content.clone();
+            let memo_id = store
+                .current_memo_id
+                .clone()
+                .unwrap_or_else(|| get_timestamp().to_string());
             store.current_memo_id = Some(memo_id.clone());
 
-            if !content.trim().is_empty() {
-                update_or_save_to_history_with_id(store, memo_id, &content)?;
+            // 自動上鎖時先保留明文，待設定密碼後直接以密文寫入
+            let declined = markers
+                .get(&memo_id)
+                .is_some_and(|marker| marker.auto_lock_declined);
+            let lock_pending = auto_lock && !secret_warnings.is_empty() && !declined;
+            if lock_pending {
+                return Ok(Some((memo_id, true)));
             }
 
-            Ok(())
+            store.save_current(&content, get_timestamp());
+            Ok(Some((memo_id, false)))
         })
         .map_err(map_memo_store_error)?;
 
+    let Some((memo_id, lock_pending)) = saved else {
+        return Ok(SavedNote {
+            memo_id: None,
+            secret_warnings: Vec::new(),
+            lock_pending: false,
+        });
+    };
+    markers
+        .record(&memo_id, &secret_warnings)
+        .map_err(|error| format!("無法記錄機密警告: {error}"))?;
+    Ok(SavedNote {
+        memo_id: Some(memo_id),
+        secret_warnings,
+        lock_pending,
+    })
+}
+
+#[tauri::command]
+pub fn decline_secret_auto_lock(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
+    memo_store(&app_handle)?;
+    app_handle
+        .state::<SecretMarkers>()
+        .decline_auto_lock(&id)
+        .map_err(|error| format!("無法記錄機密警告: {error}"))
+}
+
+fn reseal_current(
+    store: &mut MemoStoreV2,
+    key: &StoreKey,
+    content: &str,
+) -> Result<(), MemoStoreError> {
+    let unchanged = private_memo::open_content(key, &store.current_content)
+        .is_ok_and(|existing| existing == content);
+    if !unchanged {
+        store.current_content = private_memo_commands::seal(
+            store.current_memo_id.as_deref().unwrap_or_default(),
+            key,
+            content,
+        )?;
+    }
     Ok(())
 }
 
 #[tauri::command]
 pub fn get_current_memo_id(app: tauri::AppHandle) -> Result<Option<String>, String> {
-    let state = app.state::<AppState>();
-    let result = match state.current_memo_id.lock() {
-        Ok(current_id) => Ok(current_id.clone()),
-        Err(_) => Err("無法鎖定當前便條狀態".to_string()),
-    };
-
-    result
+    memo_store(&app)?
+        .snapshot()
+        .map(|store| store.current_memo_id)
+        .map_err(map_memo_store_error)
 }
 
 #[tauri::command]
 pub fn create_new_memo(app: tauri::AppHandle) -> Result<String, String> {
     let new_id = get_timestamp().to_string();
-    let repository = app.state::<MemoStoreRepository>();
-
-    let state = app.state::<AppState>();
-    match state.current_memo_id.lock() {
-        Ok(mut current_id) => {
-            *current_id = Some(new_id.clone());
-        }
-        Err(_) => return Err("無法鎖定當前便條狀態".to_string()),
-    }
+    let repository = memo_store(&app)?;
 
     repository
         .transact(|store| {
@@ -92,13 +164,14 @@ pub fn create_new_memo(app: tauri::AppHandle) -> Result<String, String> {
             Ok(())
         })
         .map_err(map_memo_store_error)?;
+    draft_commands::switch_draft_session(&app)?;
 
     Ok(new_id)
 }
 
 #[tauri::command]
 pub fn clear_note(app_handle: tauri::AppHandle) -> Result<(), String> {
-    let repository = app_handle.state::<MemoStoreRepository>();
+    let repository = memo_store(&app_handle)?;
 
     repository
         .transact(|store| {
@@ -108,31 +181,18 @@ pub fn clear_note(app_handle: tauri::AppHandle) -> Result<(), String> {
         .map_err(map_memo_store_error)
 }
 
-fn update_or_save_to_history_with_id(
+pub(crate) fn update_or_save_to_history_with_id(
     store: &mut upmemo::memo_store::MemoStoreV2,
     memo_id: String,
     content: &str,
 ) -> Result<(), MemoStoreError> {
-    let current_time = get_timestamp();
-    let content = content.to_string();
-
-    if let Some(existing_entry) = store.history.iter_mut().find(|entry| entry.id == memo_id) {
-        existing_entry.content = content.clone();
-        existing_entry.timestamp = current_time;
-    } else {
-        store.history.insert(
-            0,
-            MemoEntry {
-                id: memo_id,
-                content,
-                timestamp: current_time,
-            },
-        );
-    }
-
+    store.upsert_history(memo_id, content, get_timestamp());
     Ok(())
 }
 
-fn map_memo_store_error(error: MemoStoreError) -> String {
-    format!("memo_store 操作失敗: {error}")
+pub(crate) fn map_memo_store_error(error: MemoStoreError) -> String {
+    match error {
+        MemoStoreError::PrivateMemo { source, .. } => map_private_memo_error(source),
+        error => format!("memo_store 操作失敗: {error}"),
+    }
 }
@@ -0,0 +1,843 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::markdown_export::{
+    decode_entities, derive_title, markdown_document, read_tag, sanitize_filename, text_run_len,
+    ASSETS_DIR,
+};
+use crate::markdown_import::{markdown_to_memo, split_front_matter};
+use crate::memo_import::{failure, ImportFailure, ImportFailureKind};
+use crate::memo_store::{CommitAdapter, CommitError, MemoCollection, MemoStoreV2, HISTORY_LIMIT};
+use crate::models::MemoEntry;
+use crate::private_memo;
+
+pub const MIRROR_STATE_FILE: &str = ".upmemo-mirror.json";
+const ID_META: &str = "upmemo-id";
+const MARKDOWN_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+const HTML_EXTENSIONS: [&str; 2] = ["html", "htm"];
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl MirrorFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => MARKDOWN_EXTENSIONS[0],
+            Self::Html => HTML_EXTENSIONS[0],
+        }
+    }
+
+    fn of_name(name: &str) -> Option<Self> {
+        let extension = Path::new(name)
+            .extension()?
+            .to_string_lossy()
+            .to_ascii_lowercase();
+        if MARKDOWN_EXTENSIONS.contains(&extension.as_str()) {
+            Some(Self::Markdown)
+        } else if HTML_EXTENSIONS.contains(&extension.as_str()) {
+            Some(Self::Html)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MirrorConfig {
+    pub folder: PathBuf,
+    pub format: MirrorFormat,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MirrorOptions<'a> {
+    pub folder: &'a Path,
+    pub format: MirrorFormat,
+    pub attachments: &'a Path,
+    pub now: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct MirrorConflict {
+    pub id: String,
+    pub copy_id: String,
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct MirrorReport {
+    pub written: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    pub updated: Vec<String>,
+    pub added: Vec<String>,
+    pub trashed: Vec<String>,
+    pub conflicts: Vec<MirrorConflict>,
+    pub failures: Vec<ImportFailure>,
+}
+
+impl MirrorReport {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PlainMirrorError {
+    #[error("{operation} failed for {path}: {source}")]
+    Io {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("mirror file {path} is malformed: {source}")]
+    Malformed {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("mirror folder {path} already holds notes that upmemo did not write")]
+    FolderInUse { path: PathBuf },
+    #[error("failed to serialize mirror state: {0}")]
+    Serialize(#[source] serde_json::Error),
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+struct MirrorState {
+    files: BTreeMap<String, MirroredFile>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct MirroredFile {
+    name: String,
+    memo_sha256: String,
+    file_sha256: String,
+}
+
+#[derive(Debug)]
+pub struct MirrorPlan {
+    folder: PathBuf,
+    saved: Option<MirrorState>,
+    state: MirrorState,
+    operations: Vec<MirrorOperation>,
+    report: MirrorReport,
+}
+
+#[derive(Debug)]
+enum MirrorOperation {
+    Write {
+        path: PathBuf,
+        bytes: Vec<u8>,
+        owner: String,
+    },
+    Asset {
+        path: PathBuf,
+        bytes: Vec<u8>,
+    },
+    Remove {
+        path: PathBuf,
+    },
+}
+
+struct FileText {
+    sha256: String,
+    text: String,
+}
+
+enum MirroredSide {
+    Missing,
+    Unreadable,
+    Present(FileText),
+}
+
+struct ParsedFile {
+    id: Option<String>,
+    content: String,
+}
+
+pub fn read_mirror_config(path: &Path) -> Result<Option<MirrorConfig>, PlainMirrorError> {
+    read_json(path)
+}
+
+pub fn write_mirror_config(
+    path: &Path,
+    config: Option<&MirrorConfig>,
+    committer: &impl CommitAdapter,
+) -> Result<(), PlainMirrorError> {
+    match config {
+        Some(config) => {
+            let bytes = serde_json::to_vec_pretty(config).map_err(PlainMirrorError::Serialize)?;
+            committer.commit(path, &bytes)?;
+        }
+        None => match fs::remove_file(path) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(source) => {
+                return Err(PlainMirrorError::Io {
+                    operation: "remove mirror config",
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        },
+    }
+    Ok(())
+}
+
+pub fn sync_plain_mirror(
+    store: &mut MemoStoreV2,
+    options: &MirrorOptions<'_>,
+    committer: &impl CommitAdapter,
+) -> Result<MirrorReport, PlainMirrorError> {
+    plan_plain_mirror(store, options)?.apply(committer)
+}
+
+pub fn plan_plain_mirror(
+    store: &mut MemoStoreV2,
+    options: &MirrorOptions<'_>,
+) -> Result<MirrorPlan, PlainMirrorError> {
+    let saved: Option<MirrorState> = read_json(&options.folder.join(MIRROR_STATE_FILE))?;
+    let mut mirror = Mirror {
+        store,
+        options,
+        dedicated: saved.is_some(),
+        state: saved.clone().unwrap_or_default(),
+        operations: Vec::new(),
+        report: MirrorReport::default(),
+    };
+    mirror.run()?;
+
+    Ok(MirrorPlan {
+        folder: options.folder.to_path_buf(),
+        saved,
+        state: mirror.state,
+        operations: mirror.operations,
+        report: mirror.report,
+    })
+}
+
+pub fn remove_plain_mirror(folder: &Path) -> Result<Vec<PathBuf>, PlainMirrorError> {
+    let state_path = folder.join(MIRROR_STATE_FILE);
+    let Some(state) = read_json::<MirrorState>(&state_path)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut removed = Vec::new();
+    for file in state.files.values() {
+        let path = folder.join(&file.name);
+        if remove_if_present(&path, "remove mirrored file")? {
+            removed.push(path);
+        }
+    }
+    let assets = folder.join(ASSETS_DIR);
+    match fs::remove_dir_all(&assets) {
+        Ok(()) => removed.push(assets),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(source) => {
+            return Err(PlainMirrorError::Io {
+                operation: "remove mirrored assets",
+                path: assets,
+                source,
+            })
+        }
+    }
+    remove_if_present(&state_path, "remove mirror state")?;
+    Ok(removed)
+}
+
+impl MirrorPlan {
+    pub fn apply(self, committer: &impl CommitAdapter) -> Result<MirrorReport, PlainMirrorError> {
+        let MirrorPlan {
+            folder,
+            saved,
+            mut state,
+            operations,
+            mut report,
+        } = self;
+        fs::create_dir_all(&folder).map_err(|source| PlainMirrorError::Io {
+            operation: "create mirror directory",
+            path: folder.clone(),
+            source,
+        })?;
+
+        let mut result = Ok(());
+        let mut operations = operations.into_iter();
+        while let Some(operation) = operations.next() {
+            if let Err(error) = operation.perform(committer, &mut report) {
+                for skipped in std::iter::once(&operation).chain(operations.as_slice()) {
+                    if let MirrorOperation::Write { owner, .. } = skipped {
+                        state.files.remove(owner);
+                    }
+                }
+                result = Err(error);
+                break;
+            }
+        }
+
+        if saved.as_ref() != Some(&state) {
+            let bytes = serde_json::to_vec_pretty(&state).map_err(PlainMirrorError::Serialize)?;
+            committer.commit(&folder.join(MIRROR_STATE_FILE), &bytes)?;
+        }
+        result.map(|()| report)
+    }
+}
+
+impl MirrorOperation {
+    fn perform(
+        &self,
+        committer: &impl CommitAdapter,
+        report: &mut MirrorReport,
+    ) -> Result<(), PlainMirrorError> {
+        match self {
+            Self::Write { path, bytes, .. } => {
+                committer.commit(path, bytes)?;
+                report.written.push(path.clone());
+            }
+            Self::Asset { path, bytes } => {
+                if !path.exists() {
+                    committer.commit(path, bytes)?;
+                }
+            }
+            Self::Remove { path } => match fs::remove_file(path) {
+                Ok(()) => report.removed.push(path.clone()),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(source) => {
+                    return Err(PlainMirrorError::Io {
+                        operation: "remove mirrored file",
+                        path: path.clone(),
+                        source,
+                    })
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+struct Mirror<'a> {
+    store: &'a mut MemoStoreV2,
+    options: &'a MirrorOptions<'a>,
+    dedicated: bool,
+    state: MirrorState,
+    operations: Vec<MirrorOperation>,
+    report: MirrorReport,
+}
+
+impl Mirror<'_> {
+    fn run(&mut self) -> Result<(), PlainMirrorError> {
+        let mut untracked = self.untracked_files()?;
+        if !self.dedicated && !untracked.is_empty() {
+            return Err(PlainMirrorError::FolderInUse {
+                path: self.options.folder.to_path_buf(),
+            });
+        }
+        let tracked: Vec<String> = self.state.files.keys().cloned().collect();
+        for id in tracked {
+            self.sync_tracked(&id, &mut untracked);
+        }
+        for (name, file) in untracked {
+            self.adopt(&name, file);
+        }
+
+        let pending: Vec<MemoEntry> = self
+            .store
+            .history
+            .iter()
+            .filter(|entry| {
+                !self.state.files.contains_key(&entry.id)
+                    && !private_memo::is_private(&entry.content)
+            })
+            .cloned()
+            .collect();
+        for entry in pending {
+            self.write(&entry, None);
+        }
+        Ok(())
+    }
+
+    fn sync_tracked(&mut self, id: &str, untracked: &mut BTreeMap<String, FileText>) {
+        let Some(mut record) = self.state.files.get(id).cloned() else {
+            return;
+        };
+        let mut side = self.read_file(&record.name);
+        if matches!(side, MirroredSide::Missing) {
+            let renamed = untracked
+                .iter()
+                .find(|(name, file)| peek_id(name, &file.text).as_deref() == Some(id))
+                .map(|(name, _)| name.clone());
+            if let Some(file) = renamed.and_then(|name| {
+                let file = untracked.remove(&name)?;
+                record.name = name;
+                Some(file)
+            }) {
+                side = MirroredSide::Present(file);
+            }
+        }
+
+        let memo = self
+            .store
+            .history
+            .iter()
+            .find(|entry| entry.id == id && !private_memo::is_private(&entry.content))
+            .cloned();
+        let memo_changed = memo
+            .as_ref()
+            .is_some_and(|memo| sha256_hex(memo.content.as_bytes()) != record.memo_sha256);
+
+        match (memo, side) {
+            (_, MirroredSide::Unreadable) => {}
+            (Some(memo), MirroredSide::Present(file)) => {
+                let file_changed = file.sha256 != record.file_sha256;
+                let stale_format = MirrorFormat::of_name(&record.name) != Some(self.options.format);
+                if file_changed {
+                    let parsed = self.parse(&record.name, &file.text);
+                    if parsed.content == memo.content {
+                        record.memo_sha256 = sha256_hex(memo.content.as_bytes());
+                        record.file_sha256 = file.sha256;
+                        self.state.files.insert(id.to_string(), record);
+                    } else if memo_changed {
+                        let copy_id = self.insert_memo(None, parsed.content);
+                        self.report.conflicts.push(MirrorConflict {
+                            id: id.to_string(),
+                            copy_id,
+                            path: self.options.folder.join(&record.name),
+                        });
+                        self.write(&memo, Some(&record.name));
+                    } else {
+                        self.update_memo(id, &parsed.content);
+                        record.memo_sha256 = sha256_hex(parsed.content.as_bytes());
+                        record.file_sha256 = file.sha256;
+                        self.state.files.insert(id.to_string(), record);
+                    }
+                } else if memo_changed || stale_format {
+                    self.write(&memo, Some(&record.name));
+                } else {
+                    self.state.files.insert(id.to_string(), record);
+                }
+            }
+            (Some(memo), MirroredSide::Missing) => {
+                if memo_changed {
+                    self.write(&memo, Some(&record.name));
+                } else {
+                    self.state.files.remove(id);
+                    self.trash_memo(id);
+                }
+            }
+            (None, MirroredSide::Present(file)) => {
+                self.state.files.remove(id);
+                if file.sha256 == record.file_sha256 {
+                    self.remove_file(&record.name);
+                } else {
+                    let parsed = self.parse(&record.name, &file.text);
+                    let copy_id = self.insert_memo(None, parsed.content);
+                    self.report.conflicts.push(MirrorConflict {
+                        id: id.to_string(),
+                        copy_id: copy_id.clone(),
+                        path: self.options.folder.join(&record.name),
+                    });
+                    if let Some(copy) = self.find_memo(&copy_id) {
+                        self.write(&copy, Some(&record.name));
+                    }
+                }
+            }
+            (None, MirroredSide::Missing) => {
+                self.state.files.remove(id);
+            }
+        }
+    }
+
+    fn adopt(&mut self, name: &str, file: FileText) {
+        let parsed = self.parse(name, &file.text);
+        let restored = parsed
+            .id
+            .as_deref()
+            .filter(|id| !self.state.files.contains_key(*id))
+            .and_then(|id| self.store.history.iter().find(|entry| entry.id == id))
+            .filter(|memo| !private_memo::is_private(&memo.content))
+            .cloned();
+
+        match restored {
+            Some(memo) if memo.content == parsed.content => {
+                self.state.files.insert(
+                    memo.id,
+                    MirroredFile {
+                        name: name.to_string(),
+                        memo_sha256: sha256_hex(parsed.content.as_bytes()),
+                        file_sha256: file.sha256,
+                    },
+                );
+            }
+            Some(memo) => {
+                let copy_id = self.insert_memo(None, parsed.content);
+                self.report.conflicts.push(MirrorConflict {
+                    id: memo.id.clone(),
+                    copy_id,
+                    path: self.options.folder.join(name),
+                });
+                self.write(&memo, Some(name));
+            }
+            None => {
+                let id = self.insert_memo(parsed.id, parsed.content);
+                self.report.added.push(id.clone());
+                if let Some(memo) = self.find_memo(&id) {
+                    self.write(&memo, Some(name));
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, entry: &MemoEntry, previous: Option<&str>) {
+        let name = match previous {
+            Some(name) if MirrorFormat::of_name(name) == Some(self.options.format) => {
+                name.to_string()
+            }
+            previous => {
+                if let Some(name) = previous {
+                    self.remove_file(name);
+                }
+                self.unique_name(&entry.id, &entry.content)
+            }
+        };
+        let document = match self.options.format {
+            MirrorFormat::Markdown => self.markdown_document(entry),
+            MirrorFormat::Html => html_document(entry),
+        };
+
+        self.state.files.insert(
+            entry.id.clone(),
+            MirroredFile {
+                name: name.clone(),
+                memo_sha256: sha256_hex(entry.content.as_bytes()),
+                file_sha256: sha256_hex(document.as_bytes()),
+            },
+        );
+        self.operations.push(MirrorOperation::Write {
+            path: self.options.folder.join(name),
+            bytes: document.into_bytes(),
+            owner: entry.id.clone(),
+        });
+    }
+
+    fn markdown_document(&mut self, entry: &MemoEntry) -> String {
+        let document = markdown_document(entry, MemoCollection::History, self.options.attachments);
+        for (name, error) in document.missing_images {
+            self.report.failures.push(failure(
+                ImportFailureKind::MissingImage,
+                &self.options.attachments.join(name),
+                error,
+            ));
+        }
+        for (name, bytes) in document.images {
+            self.operations.push(MirrorOperation::Asset {
+                path: self.options.folder.join(ASSETS_DIR).join(name),
+                bytes,
+            });
+        }
+        document.text
+    }
+
+    fn unique_name(&self, id: &str, content: &str) -> String {
+        let stem = sanitize_filename(&derive_title(content));
+        let extension = self.options.format.extension();
+        let mut attempt = 1;
+        loop {
+            let name = if attempt == 1 {
+                format!("{stem}.{extension}")
+            } else {
+                format!("{stem} ({attempt}).{extension}")
+            };
+            let claimed = self
+                .state
+                .files
+                .iter()
+                .any(|(owner, file)| owner != id && file.name.eq_ignore_ascii_case(&name));
+            let owned = self
+                .state
+                .files
+                .get(id)
+                .is_some_and(|file| file.name == name);
+            if owned || (!claimed && !self.options.folder.join(&name).exists()) {
+                return name;
+            }
+            attempt += 1;
+        }
+    }
+
+    fn untracked_files(&mut self) -> Result<BTreeMap<String, FileText>, PlainMirrorError> {
+        let entries = match fs::read_dir(self.options.folder) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(source) => {
+                return Err(PlainMirrorError::Io {
+                    operation: "read mirror directory",
+                    path: self.options.folder.to_path_buf(),
+                    source,
+                })
+            }
+        };
+        let names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| MirrorFormat::of_name(name).is_some())
+            .filter(|name| !self.state.files.values().any(|file| &file.name == name))
+            .collect();
+
+        let mut untracked = BTreeMap::new();
+        for name in names {
+            if let MirroredSide::Present(file) = self.read_file(&name) {
+                untracked.insert(name, file);
+            }
+        }
+        Ok(untracked)
+    }
+
+    fn read_file(&mut self, name: &str) -> MirroredSide {
+        let path = self.options.folder.join(name);
+        match fs::read(&path) {
+            Ok(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => MirroredSide::Present(FileText {
+                    sha256: sha256_hex(text.as_bytes()),
+                    text,
+                }),
+                Err(error) => {
+                    self.report
+                        .failures
+                        .push(failure(ImportFailureKind::NotUtf8, &path, error));
+                    MirroredSide::Unreadable
+                }
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => MirroredSide::Missing,
+            Err(error) => {
+                self.report
+                    .failures
+                    .push(failure(ImportFailureKind::Unreadable, &path, error));
+                MirroredSide::Unreadable
+            }
+        }
+    }
+
+    fn parse(&mut self, name: &str, text: &str) -> ParsedFile {
+        match MirrorFormat::of_name(name) {
+            Some(MirrorFormat::Html) => ParsedFile {
+                id: meta_content(text, ID_META),
+                content: html_to_memo(text),
+            },
+            _ => {
+                let (front_matter, body) = split_front_matter(text);
+                ParsedFile {
+                    id: front_matter.id,
+                    content: markdown_to_memo(body, self.options.folder, &mut self.report.failures),
+                }
+            }
+        }
+    }
+
+    fn remove_file(&mut self, name: &str) {
+        self.operations.push(MirrorOperation::Remove {
+            path: self.options.folder.join(name),
+        });
+    }
+
+    fn find_memo(&self, id: &str) -> Option<MemoEntry> {
+        self.store
+            .history
+            .iter()
+            .chain(&self.store.archive)
+            .find(|entry| entry.id == id)
+            .cloned()
+    }
+
+    fn insert_memo(&mut self, id: Option<String>, content: String) -> String {
+        let taken = |id: &String| {
+            [&self.store.history, &self.store.archive, &self.store.trash]
+                .into_iter()
+                .flatten()
+                .any(|entry| &entry.id == id)
+        };
+        let id = id
+            .filter(|id| !taken(id) && !self.state.files.contains_key(id))
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let entry = MemoEntry {
+            id: id.clone(),
+            content,
+            timestamp: self.options.now,
+        };
+        if self.store.history.len() >= HISTORY_LIMIT {
+            self.store.archive.insert(0, entry);
+        } else {
+            self.store.history.insert(0, entry);
+        }
+        id
+    }
+
+    fn update_memo(&mut self, id: &str, content: &str) {
+        if let Some(entry) = self.store.history.iter_mut().find(|entry| entry.id == id) {
+            entry.content = content.to_string();
+            entry.timestamp = self.options.now;
+            self.report.updated.push(id.to_string());
+        }
+    }
+
+    fn trash_memo(&mut self, id: &str) {
+        if let Some(index) = self.store.history.iter().position(|entry| entry.id == id) {
+            let entry = self.store.history.remove(index);
+            self.store.trash.insert(0, entry);
+            self.report.trashed.push(id.to_string());
+        }
+    }
+}
+
+fn html_document(entry: &MemoEntry) -> String {
+    let mut body = String::new();
+    let content = &entry.content;
+    let mut position = 0;
+    while position < content.len() {
+        let rest = &content[position..];
+        match rest.starts_with('<').then(|| read_tag(rest)).flatten() {
+            Some(tag) => {
+                body.push_str(&rest[..tag.len]);
+                position += tag.len;
+            }
+            None => {
+                let end = text_run_len(rest);
+                body.push_str(&escape_html(&rest[..end]).replace('\n', "<br>\n"));
+                position += end;
+            }
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"{ID_META}\" content=\"{}\">\n<title>{}</title>\n</head>\n<body>\n{body}\n</body>\n</html>\n",
+        escape_html(&entry.id),
+        escape_html(&derive_title(content))
+    )
+}
+
+fn html_to_memo(document: &str) -> String {
+    let lowercase = document.to_ascii_lowercase();
+    let start = lowercase
+        .find("<body")
+        .and_then(|body| lowercase[body..].find('>').map(|end| body + end + 1))
+        .unwrap_or(0);
+    let end = lowercase
+        .rfind("</body")
+        .filter(|end| *end >= start)
+        .unwrap_or(document.len());
+    let body = &document[start..end];
+
+    let mut content = String::new();
+    let mut divs = Vec::new();
+    let mut position = 0;
+    while position < body.len() {
+        let rest = &body[position..];
+        match rest.starts_with('<').then(|| read_tag(rest)).flatten() {
+            Some(tag) => {
+                match (tag.name.as_str(), tag.closing) {
+                    ("img", false) => content.push_str(&rest[..tag.len]),
+                    ("br", _) => content.push('\n'),
+                    ("div", false) => {
+                        let class = tag.attribute("class").unwrap_or_default();
+                        divs.push(
+                            class
+                                .split_whitespace()
+                                .any(|name| name == "image-container" || name == "resize-handle"),
+                        );
+                    }
+                    ("div" | "p", true) => {
+                        let decorative = tag.name == "div" && divs.pop().unwrap_or(false);
+                        if !decorative && !content.is_empty() && !content.ends_with('\n') {
+                            content.push('\n');
+                        }
+                    }
+                    _ => {}
+                }
+                position += tag.len;
+            }
+            None => {
+                let end = text_run_len(rest);
+                let text: String = rest[..end]
+                    .chars()
+                    .filter(|character| !matches!(character, '\n' | '\r'))
+                    .collect();
+                content.push_str(&decode_entities(&text.replace("&nbsp;", " ")));
+                position += end;
+            }
+        }
+    }
+
+    content
+}
+
+fn meta_content(document: &str, name: &str) -> Option<String> {
+    document.match_indices('<').find_map(|(index, _)| {
+        let tag = read_tag(&document[index..])?;
+        (tag.name == "meta" && !tag.closing && tag.attribute("name") == Some(name))
+            .then(|| tag.attribute("content").map(str::to_string))
+            .flatten()
+            .filter(|content| !content.is_empty())
+    })
+}
+
+fn peek_id(name: &str, text: &str) -> Option<String> {
+    match MirrorFormat::of_name(name)? {
+        MirrorFormat::Markdown => split_front_matter(text).0.id,
+        MirrorFormat::Html => meta_content(text, ID_META),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn remove_if_present(path: &Path, operation: &'static str) -> Result<bool, PlainMirrorError> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(source) => Err(PlainMirrorError::Io {
+            operation,
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, PlainMirrorError> {
+    match fs::read(path) {
+        Ok(bytes) => {
+            serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|source| PlainMirrorError::Malformed {
+                    path: path.to_path_buf(),
+                    source,
+                })
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(PlainMirrorError::Io {
+            operation: "read mirror file",
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
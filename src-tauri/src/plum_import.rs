@@ -0,0 +1,215 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+
+use crate::memo_import::{
+    append_tags, failure, tidy, ImportFailure, ImportFailureKind, ImportedMemo, PreparedImport,
+};
+use crate::memo_store::MemoCollection;
+use crate::models::MemoEntry;
+
+const NOTE_TABLE: &str = "Note";
+const REQUIRED_COLUMNS: [&str; 2] = ["Id", "Text"];
+const OPTIONAL_COLUMNS: [&str; 4] = ["Theme", "CreatedAt", "UpdatedAt", "DeletedAt"];
+const UNIX_EPOCH_TICKS: i64 = 621_355_968_000_000_000;
+const TICKS_PER_SECOND: i64 = 10_000_000;
+const PARAGRAPH_ID_PREFIX: &str = "\\id=";
+const THEME_LABELS: [(&str, &str); 7] = [
+    ("Yellow", "黃色"),
+    ("Green", "綠色"),
+    ("Pink", "粉紅色"),
+    ("Purple", "紫色"),
+    ("Blue", "藍色"),
+    ("Gray", "灰色"),
+    ("Charcoal", "炭黑色"),
+];
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct PlumNote {
+    id: String,
+    text: String,
+    theme: Option<String>,
+    created_at: Option<i64>,
+    updated_at: Option<i64>,
+    deleted_at: Option<i64>,
+}
+
+pub fn prepare_plum_import(database: &Path) -> PreparedImport {
+    let mut prepared = PreparedImport::default();
+    match read_plum_notes(database) {
+        Ok(notes) => {
+            for note in notes {
+                if let Some(memo) = plum_note_to_memo(database, note) {
+                    prepared.memos.push(memo);
+                }
+            }
+        }
+        Err(error) => prepared.failures.push(error),
+    }
+    prepared
+}
+
+fn read_plum_notes(database: &Path) -> Result<Vec<PlumNote>, ImportFailure> {
+    let copy = tempfile::tempdir()
+        .map_err(|error| failure(ImportFailureKind::Unreadable, database, error))?;
+    let local = copy.path().join("plum.sqlite");
+    fs::copy(database, &local)
+        .map_err(|error| failure(ImportFailureKind::Unreadable, database, error))?;
+    for suffix in ["-wal", "-shm"] {
+        let sidecar = sidecar_path(database, suffix);
+        if sidecar.is_file() {
+            fs::copy(&sidecar, sidecar_path(&local, suffix))
+                .map_err(|error| failure(ImportFailureKind::Unreadable, &sidecar, error))?;
+        }
+    }
+
+    let invalid =
+        |error: rusqlite::Error| failure(ImportFailureKind::InvalidDatabase, database, error);
+    let connection = Connection::open(&local).map_err(invalid)?;
+    let columns = table_columns(&connection).map_err(invalid)?;
+    if let Some(missing) = REQUIRED_COLUMNS
+        .iter()
+        .find(|column| !columns.iter().any(|known| known == *column))
+    {
+        return Err(failure(
+            ImportFailureKind::InvalidDatabase,
+            database,
+            format!("{NOTE_TABLE} table has no {missing} column"),
+        ));
+    }
+
+    let selected: Vec<&str> = OPTIONAL_COLUMNS
+        .iter()
+        .map(|column| {
+            if columns.iter().any(|known| known == column) {
+                *column
+            } else {
+                "NULL"
+            }
+        })
+        .collect();
+    let query = format!(
+        "SELECT Id, Text, {} FROM {NOTE_TABLE} ORDER BY rowid",
+        selected.join(", ")
+    );
+    let mut statement = connection.prepare(&query).map_err(invalid)?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok(PlumNote {
+                id: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                text: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                theme: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+                deleted_at: row.get(5)?,
+            })
+        })
+        .map_err(invalid)?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map_err(invalid)
+}
+
+pub fn plum_text_to_memo(text: &str) -> String {
+    let mut content = String::new();
+    for line in text.replace("\r\n", "\n").replace('\r', "\n").split('\n') {
+        let line = match line.strip_prefix(PARAGRAPH_ID_PREFIX) {
+            Some(rest) => rest.split_once(' ').map_or("", |(_, text)| text),
+            None => line,
+        };
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        push_formatted(&mut content, line);
+    }
+    tidy(&content)
+}
+
+fn ticks_to_unix_secs(ticks: i64) -> Option<u64> {
+    u64::try_from((ticks - UNIX_EPOCH_TICKS) / TICKS_PER_SECOND).ok()
+}
+
+fn plum_note_to_memo(database: &Path, note: PlumNote) -> Option<ImportedMemo> {
+    let mut content = plum_text_to_memo(&note.text);
+    if content.trim().is_empty() {
+        return None;
+    }
+    if let Some(label) = note.theme.as_deref().and_then(theme_label) {
+        append_tags(&mut content, &[label.to_string()]);
+    }
+
+    let timestamp = note
+        .updated_at
+        .or(note.created_at)
+        .and_then(ticks_to_unix_secs)
+        .unwrap_or_default();
+    let id = if note.id.trim().is_empty() {
+        uuid::Uuid::new_v4().to_string()
+    } else {
+        note.id
+    };
+    Some(ImportedMemo {
+        path: database.to_path_buf(),
+        entry: MemoEntry {
+            id,
+            content,
+            timestamp,
+        },
+        collection: if note.deleted_at.is_some() {
+            MemoCollection::Trash
+        } else {
+            MemoCollection::History
+        },
+    })
+}
+
+fn push_formatted(content: &mut String, line: &str) {
+    let mut rest = line;
+    while let Some(index) = rest.find('\\') {
+        content.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+        if let Some(escaped) = rest.strip_prefix(['\\', '{', '}']) {
+            content.push_str(&rest[..1]);
+            rest = escaped;
+            continue;
+        }
+
+        let word_len = rest
+            .find(|character: char| !character.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+        let marker = match &rest[..word_len] {
+            "b" | "b0" => Some("**"),
+            "i" | "i0" => Some("*"),
+            "strike" | "strike0" => Some("~~"),
+            "ul" | "ul0" | "ulnone" => Some(""),
+            _ => None,
+        };
+        match marker {
+            Some(marker) => {
+                content.push_str(marker);
+                rest = &rest[word_len..];
+                rest = rest.strip_prefix(' ').unwrap_or(rest);
+            }
+            None => content.push('\\'),
+        }
+    }
+    content.push_str(rest);
+}
+
+fn theme_label(theme: &str) -> Option<&'static str> {
+    THEME_LABELS
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(theme.trim()))
+        .map(|(_, label)| *label)
+}
+
+fn table_columns(connection: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut statement = connection.prepare(&format!("PRAGMA table_info({NOTE_TABLE})"))?;
+    let columns = statement.query_map([], |row| row.get::<_, String>(1))?;
+    columns.collect()
+}
+
+fn sidecar_path(database: &Path, suffix: &str) -> PathBuf {
+    let mut path = database.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::memo_store::{EncryptionError, StoreKey};
+use crate::models::MemoEntry;
+
+pub const PRIVATE_MEMO_PREFIX: &str = "upmemo-private:v1:";
+
+pub fn is_private(content: &str) -> bool {
+    content.starts_with(PRIVATE_MEMO_PREFIX)
+}
+
+pub fn seal_content(key: &StoreKey, plaintext: &str) -> Result<String, EncryptionError> {
+    let sealed = key.seal(plaintext.as_bytes())?;
+    Ok(format!("{PRIVATE_MEMO_PREFIX}{}", STANDARD.encode(sealed)))
+}
+
+pub fn open_content(key: &StoreKey, content: &str) -> Result<String, EncryptionError> {
+    let plaintext = key.unseal(&envelope(content)?)?;
+    String::from_utf8(plaintext).map_err(|_| EncryptionError::MalformedEnvelope)
+}
+
+pub fn key_for(password: &str, content: &str) -> Result<StoreKey, EncryptionError> {
+    StoreKey::derive_for(password, &envelope(content)?)
+}
+
+fn envelope(content: &str) -> Result<Vec<u8>, EncryptionError> {
+    let encoded = content
+        .strip_prefix(PRIVATE_MEMO_PREFIX)
+        .ok_or(EncryptionError::MalformedEnvelope)?;
+    STANDARD
+        .decode(encoded)
+        .map_err(|_| EncryptionError::MalformedEnvelope)
+}
+
+struct UnlockedMemo {
+    key: Arc<StoreKey>,
+    last_used: Instant,
+    warned: bool,
+}
+
+pub struct PrivateMemoSessions {
+    idle_timeout: Duration,
+    unlocked: Mutex<HashMap<String, UnlockedMemo>>,
+}
+
+impl PrivateMemoSessions {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            idle_timeout,
+            unlocked: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn unlock(&self, id: &str, key: StoreKey, now: Instant) {
+        self.sessions().insert(
+            id.to_string(),
+            UnlockedMemo {
+                key: Arc::new(key),
+                last_used: now,
+                warned: false,
+            },
+        );
+    }
+
+    pub fn key(&self, id: &str, now: Instant) -> Option<Arc<StoreKey>> {
+        let mut sessions = self.sessions();
+        let session = sessions.get_mut(id)?;
+        if now.saturating_duration_since(session.last_used) >= self.idle_timeout {
+            sessions.remove(id);
+            return None;
+        }
+        session.last_used = now;
+        session.warned = false;
+        Some(Arc::clone(&session.key))
+    }
+
+    pub fn relock(&self, id: &str) -> bool {
+        self.sessions().remove(id).is_some()
+    }
+
+    pub fn expire(&self, now: Instant) -> Vec<String> {
+        let mut expired = Vec::new();
+        self.sessions().retain(|id, session| {
+            let idle = now.saturating_duration_since(session.last_used) >= self.idle_timeout;
+            if idle {
+                expired.push(id.clone());
+            }
+            !idle
+        });
+        expired
+    }
+
+    pub fn expiring(&self, now: Instant, warning: Duration) -> Vec<String> {
+        let mut expiring = Vec::new();
+        for (id, session) in self.sessions().iter_mut() {
+            let idle = now.saturating_duration_since(session.last_used);
+            if !session.warned && idle < self.idle_timeout && idle + warning >= self.idle_timeout {
+                session.warned = true;
+                expiring.push(id.clone());
+            }
+        }
+        expiring
+    }
+
+    pub fn reveal(&self, entry: MemoEntry, now: Instant) -> MemoEntry {
+        if !is_private(&entry.content) {
+            return entry;
+        }
+
+        let content = self
+            .peek(&entry.id, now)
+            .and_then(|key| open_content(&key, &entry.content).ok())
+            .unwrap_or_else(|| PRIVATE_MEMO_PREFIX.to_string());
+        MemoEntry { content, ..entry }
+    }
+
+    fn peek(&self, id: &str, now: Instant) -> Option<Arc<StoreKey>> {
+        let sessions = self.sessions();
+        let session = sessions.get(id)?;
+        (now.saturating_duration_since(session.last_used) < self.idle_timeout)
+            .then(|| Arc::clone(&session.key))
+    }
+
+    fn sessions(&self) -> MutexGuard<'_, HashMap<String, UnlockedMemo>> {
+        self.unlocked
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
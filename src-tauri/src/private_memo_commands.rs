@@ -0,0 +1,274 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use upmemo::memo_store::{EncryptionError, KdfParams, MemoStoreError, MemoStoreV2, StoreKey};
+use upmemo::models::MemoEntry;
+use upmemo::private_memo::{self, PrivateMemoSessions};
+
+use crate::memo_store_state::{memo_store, AppMemoStore};
+use crate::note_commands::{self, map_memo_store_error};
+use crate::shred_commands;
+use crate::version_history_commands;
+
+pub const PRIVATE_MEMO_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+pub const PRIVATE_MEMO_RELOCKED_EVENT: &str = "private-memo-relocked";
+pub const PRIVATE_MEMO_EXPIRING_EVENT: &str = "private-memo-expiring";
+const RELOCK_POLL_INTERVAL: Duration = Duration::from_secs(15);
+const EXPIRY_WARNING: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Serialize)]
+struct RelockedMemos {
+    ids: Vec<String>,
+    detached: bool,
+}
+
+pub fn spawn_idle_relocker(app: &AppHandle) {
+    let app_handle = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(RELOCK_POLL_INTERVAL);
+        let sessions = app_handle.state::<PrivateMemoSessions>();
+        let now = Instant::now();
+        let expired = sessions.expire(now);
+        if !expired.is_empty() {
+            relock(&app_handle, expired);
+        }
+        let expiring = sessions.expiring(now, EXPIRY_WARNING);
+        if !expiring.is_empty() {
+            if let Err(error) = app_handle.emit(PRIVATE_MEMO_EXPIRING_EVENT, expiring) {
+                eprintln!("無法通知私人記事即將重新上鎖: {}", error);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn lock_memo(
+    app_handle: AppHandle,
+    id: String,
+    password: String,
+    content: Option<String>,
+) -> Result<(), String> {
+    if password.is_empty() {
+        return Err("密碼不可為空白".to_string());
+    }
+    let repository = memo_store(&app_handle)?;
+    match find_entry(&repository, &id) {
+        Ok(entry) if private_memo::is_private(&entry.content) => {
+            return Err("此記事已經是私人記事".to_string());
+        }
+        Err(error) if content.is_none() => return Err(error),
+        _ => {}
+    }
+
+    let key = StoreKey::derive(&password, KdfParams::default()).map_err(map_private_memo_error)?;
+    let (detached, plaintexts) = repository
+        .transact(|store| {
+            let mut plaintexts = Vec::new();
+            if let Some(entry) = entry_mut(store, &id) {
+                if !private_memo::is_private(&entry.content) {
+                    plaintexts.push(entry.content.clone());
+                    entry.content = seal(&id, &key, content.as_deref().unwrap_or(&entry.content))?;
+                }
+            } else if let Some(content) = &content {
+                let sealed = seal(&id, &key, content)?;
+                note_commands::update_or_save_to_history_with_id(store, id.clone(), &sealed)?;
+            }
+            plaintexts.extend(content.clone());
+            if store.current_memo_id.as_deref() != Some(id.as_str()) {
+                return Ok((false, plaintexts));
+            }
+            plaintexts.push(std::mem::take(&mut store.current_content));
+            store.current_memo_id = None;
+            Ok((true, plaintexts))
+        })
+        .map_err(map_memo_store_error)?;
+    repository.flush().map_err(map_memo_store_error)?;
+    app_handle.state::<PrivateMemoSessions>().relock(&id);
+    notify_relocked(&app_handle, vec![id.clone()], detached);
+    shred_commands::purge_memo_copies(&app_handle, &id, &plaintexts)?;
+    version_history_commands::discard_version_history(
+        &app_handle,
+        "記事已設為私人，含有明文的版本歷史已刪除並停用",
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unlock_memo(
+    app_handle: AppHandle,
+    id: String,
+    password: String,
+) -> Result<(), String> {
+    let repository = memo_store(&app_handle)?;
+    let entry = find_entry(&repository, &id)?;
+    if !private_memo::is_private(&entry.content) {
+        return Err("此記事未上鎖".to_string());
+    }
+
+    let key = private_memo::key_for(&password, &entry.content).map_err(map_private_memo_error)?;
+    app_handle
+        .state::<PrivateMemoSessions>()
+        .unlock(&id, key, Instant::now());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn relock_memo(app_handle: AppHandle, id: String) {
+    app_handle.state::<PrivateMemoSessions>().relock(&id);
+    relock(&app_handle, vec![id]);
+}
+
+#[tauri::command]
+pub fn keep_private_memos_unlocked(app_handle: AppHandle, ids: Vec<String>) -> Vec<String> {
+    let sessions = app_handle.state::<PrivateMemoSessions>();
+    let now = Instant::now();
+    ids.into_iter()
+        .filter(|id| sessions.key(id, now).is_some())
+        .collect()
+}
+
+#[tauri::command]
+pub async fn remove_memo_lock(
+    app_handle: AppHandle,
+    id: String,
+    password: String,
+) -> Result<(), String> {
+    let repository = memo_store(&app_handle)?;
+    let entry = find_entry(&repository, &id)?;
+    if !private_memo::is_private(&entry.content) {
+        return Err("此記事未上鎖".to_string());
+    }
+
+    let key = private_memo::key_for(&password, &entry.content).map_err(map_private_memo_error)?;
+    repository
+        .transact(|store| {
+            if let Some(entry) = entry_mut(store, &id) {
+                entry.content = open(&id, &key, &entry.content)?;
+            }
+            if store.current_memo_id.as_deref() == Some(id.as_str())
+                && private_memo::is_private(&store.current_content)
+            {
+                store.current_content = open(&id, &key, &store.current_content)?;
+            }
+            Ok(())
+        })
+        .map_err(map_memo_store_error)?;
+    app_handle.state::<PrivateMemoSessions>().relock(&id);
+    Ok(())
+}
+
+pub fn reveal_entries(app: &AppHandle, entries: Vec<MemoEntry>) -> Vec<MemoEntry> {
+    let sessions = app.state::<PrivateMemoSessions>();
+    let now = Instant::now();
+    entries
+        .into_iter()
+        .map(|entry| sessions.reveal(entry, now))
+        .collect()
+}
+
+pub fn readable_content(
+    sessions: &PrivateMemoSessions,
+    id: &str,
+    content: &str,
+) -> Result<String, MemoStoreError> {
+    if !private_memo::is_private(content) {
+        return Ok(content.to_string());
+    }
+
+    let key = unlocked_key(sessions, id)?;
+    open(id, &key, content)
+}
+
+pub fn current_memo_key(
+    store: &MemoStoreV2,
+    sessions: &PrivateMemoSessions,
+) -> Result<Option<Arc<StoreKey>>, MemoStoreError> {
+    if !private_memo::is_private(&store.current_content) {
+        return Ok(None);
+    }
+
+    let id = store.current_memo_id.clone().unwrap_or_default();
+    unlocked_key(sessions, &id).map(Some)
+}
+
+pub fn seal(id: &str, key: &StoreKey, plaintext: &str) -> Result<String, MemoStoreError> {
+    private_memo::seal_content(key, plaintext).map_err(|source| MemoStoreError::PrivateMemo {
+        id: id.to_string(),
+        source,
+    })
+}
+
+pub fn open(id: &str, key: &StoreKey, content: &str) -> Result<String, MemoStoreError> {
+    private_memo::open_content(key, content).map_err(|source| MemoStoreError::PrivateMemo {
+        id: id.to_string(),
+        source,
+    })
+}
+
+pub fn detach_current(store: &mut MemoStoreV2) -> bool {
+    if !private_memo::is_private(&store.current_content) {
+        return false;
+    }
+
+    store.current_memo_id = None;
+    store.current_content = String::new();
+    true
+}
+
+pub fn map_private_memo_error(error: EncryptionError) -> String {
+    match error {
+        EncryptionError::Locked => "此記事已上鎖，請先解鎖".to_string(),
+        EncryptionError::Decrypt => "密碼錯誤".to_string(),
+        error => format!("私人記事操作失敗: {error}"),
+    }
+}
+
+fn unlocked_key(sessions: &PrivateMemoSessions, id: &str) -> Result<Arc<StoreKey>, MemoStoreError> {
+    sessions
+        .key(id, Instant::now())
+        .ok_or_else(|| MemoStoreError::PrivateMemo {
+            id: id.to_string(),
+            source: EncryptionError::Locked,
+        })
+}
+
+fn relock(app: &AppHandle, ids: Vec<String>) {
+    let detached = match app.try_state::<AppMemoStore>() {
+        Some(repository) => repository
+            .transact(|store| {
+                let current = store.current_memo_id.clone().unwrap_or_default();
+                Ok(ids.contains(&current) && detach_current(store))
+            })
+            .unwrap_or_else(|error| {
+                eprintln!("私人記事重新上鎖失敗: {}", error);
+                false
+            }),
+        None => false,
+    };
+    notify_relocked(app, ids, detached);
+}
+
+fn notify_relocked(app: &AppHandle, ids: Vec<String>, detached: bool) {
+    if let Err(error) = app.emit(PRIVATE_MEMO_RELOCKED_EVENT, RelockedMemos { ids, detached }) {
+        eprintln!("無法通知私人記事重新上鎖: {}", error);
+    }
+}
+
+fn find_entry(repository: &AppMemoStore, id: &str) -> Result<MemoEntry, String> {
+    let mut store = repository.snapshot().map_err(map_memo_store_error)?;
+    entry_mut(&mut store, id)
+        .map(|entry| entry.clone())
+        .ok_or_else(|| "找不到該記事".to_string())
+}
+
+fn entry_mut<'a>(store: &'a mut MemoStoreV2, id: &str) -> Option<&'a mut MemoEntry> {
+    store
+        .history
+        .iter_mut()
+        .chain(store.archive.iter_mut())
+        .chain(store.trash.iter_mut())
+        .find(|entry| entry.id == id)
+}
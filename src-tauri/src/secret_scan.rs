@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::memo_store::{AtomicFileCommit, CommitAdapter, CommitError};
+
+const PRIVATE_KEY_BEGIN: &str = "-----BEGIN ";
+const PRIVATE_KEY_SUFFIX: &str = "PRIVATE KEY-----";
+const PRIVATE_KEY_END: &str = "-----END ";
+const AWS_KEY_PREFIXES: [&str; 2] = ["AKIA", "ASIA"];
+const AWS_KEY_LEN: usize = 20;
+const HIGH_ENTROPY_MIN_LEN: usize = 24;
+const HIGH_ENTROPY_BITS_PER_CHAR: f64 = 4.0;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretKind {
+    AwsAccessKey,
+    PrivateKeyBlock,
+    Jwt,
+    HighEntropyToken,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct SecretFinding {
+    pub kind: SecretKind,
+    pub excerpt: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SecretMarker {
+    pub kinds: Vec<SecretKind>,
+    #[serde(default)]
+    pub auto_lock_declined: bool,
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SecretMarkerError {
+    #[error("failed to read secret markers {path}: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("secret markers {path} are malformed: {source}")]
+    Malformed {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("failed to serialize secret markers: {0}")]
+    Serialize(#[source] serde_json::Error),
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+}
+
+pub struct SecretMarkers {
+    path: PathBuf,
+    markers: Mutex<BTreeMap<String, SecretMarker>>,
+}
+
+impl SecretMarkers {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, SecretMarkerError> {
+        let path = path.into();
+        let markers = read_markers(&path)?;
+        Ok(Self {
+            path,
+            markers: Mutex::new(markers),
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Option<SecretMarker> {
+        self.markers().get(id).cloned()
+    }
+
+    pub fn record(
+        &self,
+        id: &str,
+        findings: &[SecretFinding],
+    ) -> Result<Option<SecretMarker>, SecretMarkerError> {
+        let mut kinds: Vec<SecretKind> = findings.iter().map(|finding| finding.kind).collect();
+        kinds.sort();
+        kinds.dedup();
+
+        self.update(|markers| {
+            if kinds.is_empty() {
+                markers.remove(id);
+                return None;
+            }
+            let marker = markers.entry(id.to_string()).or_default();
+            marker.kinds = kinds;
+            Some(marker.clone())
+        })
+    }
+
+    pub fn decline_auto_lock(&self, id: &str) -> Result<(), SecretMarkerError> {
+        self.update(|markers| {
+            markers
+                .entry(id.to_string())
+                .or_default()
+                .auto_lock_declined = true;
+        })
+    }
+
+    pub fn forget(&self, id: &str) -> Result<(), SecretMarkerError> {
+        self.update(|markers| {
+            markers.remove(id);
+        })
+    }
+
+    fn update<T>(
+        &self,
+        change: impl FnOnce(&mut BTreeMap<String, SecretMarker>) -> T,
+    ) -> Result<T, SecretMarkerError> {
+        let mut markers = self.markers();
+        let mut updated = markers.clone();
+        let result = change(&mut updated);
+        if updated != *markers {
+            let bytes =
+                serde_json::to_vec_pretty(&updated).map_err(SecretMarkerError::Serialize)?;
+            AtomicFileCommit.commit(&self.path, &bytes)?;
+            *markers = updated;
+        }
+        Ok(result)
+    }
+
+    fn markers(&self) -> MutexGuard<'_, BTreeMap<String, SecretMarker>> {
+        self.markers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub fn scan(content: &str) -> Vec<SecretFinding> {
+    let blocks = private_key_blocks(content);
+    let mut findings: Vec<SecretFinding> = blocks
+        .iter()
+        .map(|block| SecretFinding {
+            kind: SecretKind::PrivateKeyBlock,
+            excerpt: content[block.clone()]
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        })
+        .collect();
+
+    for (start, token) in tokens(content) {
+        let inside_block = blocks.iter().any(|block| block.contains(&start));
+        let embedded = content[..start].ends_with("base64,") || token.starts_with("//");
+        if inside_block || embedded {
+            continue;
+        }
+        if let Some(kind) = classify(token) {
+            findings.push(finding(kind, token));
+        }
+    }
+
+    findings
+}
+
+fn private_key_blocks(content: &str) -> Vec<Range<usize>> {
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    while let Some(found) = content[offset..].find(PRIVATE_KEY_BEGIN) {
+        let start = offset + found;
+        let header_end = content[start..]
+            .find('\n')
+            .map_or(content.len(), |end| start + end);
+        if !content[start..header_end].contains(PRIVATE_KEY_SUFFIX) {
+            offset = start + PRIVATE_KEY_BEGIN.len();
+            continue;
+        }
+        let end = content[header_end..]
+            .find(PRIVATE_KEY_END)
+            .and_then(|end| {
+                let footer = header_end + end + PRIVATE_KEY_END.len();
+                content[footer..]
+                    .find("-----")
+                    .map(|dashes| footer + dashes + 5)
+            })
+            .unwrap_or(content.len());
+        blocks.push(start..end);
+        offset = end;
+    }
+    blocks
+}
+
+fn classify(token: &str) -> Option<SecretKind> {
+    if is_aws_access_key(token) {
+        return Some(SecretKind::AwsAccessKey);
+    }
+    if is_jwt(token) {
+        return Some(SecretKind::Jwt);
+    }
+    token
+        .split('.')
+        .any(is_high_entropy)
+        .then_some(SecretKind::HighEntropyToken)
+}
+
+fn is_aws_access_key(token: &str) -> bool {
+    token.len() == AWS_KEY_LEN
+        && AWS_KEY_PREFIXES
+            .iter()
+            .any(|prefix| token.starts_with(prefix))
+        && token
+            .bytes()
+            .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit())
+}
+
+fn is_jwt(token: &str) -> bool {
+    let segments: Vec<&str> = token.split('.').collect();
+    segments.len() == 3
+        && segments[0].starts_with("eyJ")
+        && segments[1].starts_with("eyJ")
+        && segments.iter().all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+        })
+}
+
+fn is_high_entropy(candidate: &str) -> bool {
+    let has_letter = candidate.bytes().any(|byte| byte.is_ascii_alphabetic());
+    let has_digit = candidate.bytes().any(|byte| byte.is_ascii_digit());
+    candidate.len() >= HIGH_ENTROPY_MIN_LEN
+        && has_letter
+        && has_digit
+        && shannon_entropy(candidate) >= HIGH_ENTROPY_BITS_PER_CHAR
+}
+
+fn shannon_entropy(candidate: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for byte in candidate.bytes() {
+        counts[usize::from(byte)] += 1;
+    }
+
+    let length = candidate.len() as f64;
+    counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let probability = *count as f64 / length;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+fn tokens(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let is_token_byte = |byte: u8| byte.is_ascii_alphanumeric() || b"+/=_-.".contains(&byte);
+    let bytes = content.as_bytes();
+    let mut position = 0;
+
+    std::iter::from_fn(move || {
+        while position < bytes.len() && !is_token_byte(bytes[position]) {
+            position += 1;
+        }
+        let start = position;
+        while position < bytes.len() && is_token_byte(bytes[position]) {
+            position += 1;
+        }
+        (start < position).then(|| (start, content[start..position].trim_matches('.')))
+    })
+}
+
+fn finding(kind: SecretKind, secret: &str) -> SecretFinding {
+    let visible: String = secret.chars().take(4).collect();
+    SecretFinding {
+        kind,
+        excerpt: format!("{visible}…"),
+    }
+}
+
+fn read_markers(path: &Path) -> Result<BTreeMap<String, SecretMarker>, SecretMarkerError> {
+    match fs::read(path) {
+        Ok(bytes) => {
+            serde_json::from_slice(&bytes).map_err(|source| SecretMarkerError::Malformed {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(source) => Err(SecretMarkerError::Read {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
@@ -0,0 +1,314 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::memo_store::{CommitAdapter, CommitError, EncryptionError};
+use crate::models::MemoEntry;
+
+const SNAPSHOT_COLLECTIONS: [&str; 3] = ["history.json", "archive.json", "trash.json"];
+const SNAPSHOT_NOTE: &str = "note.txt";
+const SNAPSHOT_MANIFEST: &str = "manifest.json";
+const TEMPORARY_PREFIX: &str = ".tmp";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShredAction {
+    Rewritten,
+    Deleted,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ShreddedFile {
+    pub path: PathBuf,
+    pub action: ShredAction,
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ShredError {
+    #[error("{operation} failed for {path}: {source}")]
+    Io {
+        operation: &'static str,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("failed to unseal {path}: {source}")]
+    Encryption {
+        path: PathBuf,
+        #[source]
+        source: EncryptionError,
+    },
+    #[error("failed to serialize {path}: {source}")]
+    Serialize {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+}
+
+pub struct MemoRemnant<'a> {
+    pub id: &'a str,
+    pub contents: &'a [String],
+}
+
+impl MemoRemnant<'_> {
+    fn matches(&self, text: &str) -> bool {
+        !text.trim().is_empty()
+            && self
+                .contents
+                .iter()
+                .any(|content| content.trim() == text.trim())
+    }
+
+    fn appears_in(&self, text: &str) -> bool {
+        self.contents
+            .iter()
+            .any(|content| !content.trim().is_empty() && text.contains(content.as_str()))
+    }
+}
+
+pub fn shred_backups(
+    backups: &Path,
+    remnant: &MemoRemnant<'_>,
+    committer: &impl CommitAdapter,
+) -> Result<Vec<ShreddedFile>, ShredError> {
+    let mut touched = Vec::new();
+    for directory in directories(backups)? {
+        shred_snapshot(&directory, remnant, committer, &mut touched)?;
+    }
+    Ok(touched)
+}
+
+pub fn shred_temporary_files(
+    directory: &Path,
+    remnant: &MemoRemnant<'_>,
+    committer: &impl CommitAdapter,
+) -> Result<Vec<ShreddedFile>, ShredError> {
+    let mut touched = Vec::new();
+    for path in files(directory)? {
+        let is_temporary = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(TEMPORARY_PREFIX));
+        if !is_temporary {
+            continue;
+        }
+        let Ok(text) = read_plain(&path, committer) else {
+            continue;
+        };
+        if remnant.appears_in(&text) {
+            remove_file(&path)?;
+            touched.push(deleted(path));
+        }
+    }
+    Ok(touched)
+}
+
+fn shred_snapshot(
+    directory: &Path,
+    remnant: &MemoRemnant<'_>,
+    committer: &impl CommitAdapter,
+    touched: &mut Vec<ShreddedFile>,
+) -> Result<(), ShredError> {
+    let mut rewritten = Vec::new();
+    let mut remaining_entries = 0;
+
+    for name in SNAPSHOT_COLLECTIONS {
+        let path = directory.join(name);
+        if !path.is_file() {
+            continue;
+        }
+        let text = read_plain(&path, committer)?;
+        match serde_json::from_str::<Vec<MemoEntry>>(&text) {
+            Ok(mut entries) => {
+                let before = entries.len();
+                entries.retain(|entry| entry.id != remnant.id);
+                remaining_entries += entries.len();
+                if entries.len() != before {
+                    let bytes = serde_json::to_vec_pretty(&entries).map_err(|source| {
+                        ShredError::Serialize {
+                            path: path.clone(),
+                            source,
+                        }
+                    })?;
+                    committer.commit(&path, &bytes)?;
+                    rewritten.push((name, bytes));
+                    touched.push(rewritten_file(path));
+                }
+            }
+            Err(_) if remnant.appears_in(&text) => {
+                remove_file(&path)?;
+                touched.push(deleted(path));
+            }
+            Err(_) => remaining_entries += 1,
+        }
+    }
+
+    let note = directory.join(SNAPSHOT_NOTE);
+    let mut note_remains = false;
+    if note.is_file() {
+        let text = read_plain(&note, committer)?;
+        if remnant.matches(&text) {
+            committer.commit(&note, b"")?;
+            rewritten.push((SNAPSHOT_NOTE, Vec::new()));
+            touched.push(rewritten_file(note));
+        } else {
+            note_remains = !text.trim().is_empty();
+        }
+    }
+
+    if rewritten.is_empty() {
+        return Ok(());
+    }
+
+    if remaining_entries == 0 && !note_remains {
+        prune_snapshot(directory, touched)?;
+        return Ok(());
+    }
+
+    let manifest = directory.join(SNAPSHOT_MANIFEST);
+    if manifest.is_file() {
+        update_manifest(&manifest, &rewritten, committer)?;
+        touched.push(rewritten_file(manifest));
+    }
+    Ok(())
+}
+
+fn prune_snapshot(directory: &Path, touched: &mut Vec<ShreddedFile>) -> Result<(), ShredError> {
+    for path in files(directory)? {
+        touched.retain(|file| file.path != path);
+        touched.push(deleted(path));
+    }
+    fs::remove_dir_all(directory).map_err(|source| ShredError::Io {
+        operation: "remove backup snapshot",
+        path: directory.to_path_buf(),
+        source,
+    })
+}
+
+fn update_manifest(
+    path: &Path,
+    rewritten: &[(&str, Vec<u8>)],
+    committer: &impl CommitAdapter,
+) -> Result<(), ShredError> {
+    let text = read_plain(path, committer)?;
+    let Ok(mut manifest) = serde_json::from_str::<Value>(&text) else {
+        return Ok(());
+    };
+
+    if let Some(files) = manifest.get_mut("files").and_then(Value::as_array_mut) {
+        for file in files {
+            let filename = file.get("filename").and_then(Value::as_str);
+            let Some((_, bytes)) = rewritten.iter().find(|(name, _)| Some(*name) == filename)
+            else {
+                continue;
+            };
+            file["bytes"] = Value::from(bytes.len());
+            file["sha256"] = Value::from(format!("{:x}", Sha256::digest(bytes)));
+        }
+    }
+
+    let bytes = serde_json::to_vec_pretty(&manifest).map_err(|source| ShredError::Serialize {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    committer.commit(path, &bytes)?;
+    Ok(())
+}
+
+fn read_plain(path: &Path, committer: &impl CommitAdapter) -> Result<String, ShredError> {
+    let bytes = fs::read(path).map_err(|source| ShredError::Io {
+        operation: "read backup file",
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let plain = committer
+        .unseal(&bytes)
+        .map_err(|source| ShredError::Encryption {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    Ok(String::from_utf8_lossy(&plain).into_owned())
+}
+
+fn directories(root: &Path) -> Result<Vec<PathBuf>, ShredError> {
+    let mut found = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(directory) = pending.pop() {
+        for path in read_dir(&directory)? {
+            if path.is_dir() {
+                pending.push(path);
+            }
+        }
+        found.push(directory);
+    }
+    Ok(found)
+}
+
+fn files(root: &Path) -> Result<Vec<PathBuf>, ShredError> {
+    let mut found = Vec::new();
+    for directory in directories(root)? {
+        found.extend(
+            read_dir(&directory)?
+                .into_iter()
+                .filter(|path| path.is_file()),
+        );
+    }
+    Ok(found)
+}
+
+fn read_dir(directory: &Path) -> Result<Vec<PathBuf>, ShredError> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(source) if source.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => {
+            return Err(ShredError::Io {
+                operation: "read directory",
+                path: directory.to_path_buf(),
+                source,
+            })
+        }
+    };
+
+    entries
+        .map(|entry| {
+            entry
+                .map(|entry| entry.path())
+                .map_err(|source| ShredError::Io {
+                    operation: "read directory entry",
+                    path: directory.to_path_buf(),
+                    source,
+                })
+        })
+        .collect()
+}
+
+fn remove_file(path: &Path) -> Result<(), ShredError> {
+    fs::remove_file(path).map_err(|source| ShredError::Io {
+        operation: "remove file",
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn rewritten_file(path: PathBuf) -> ShreddedFile {
+    ShreddedFile {
+        path,
+        action: ShredAction::Rewritten,
+    }
+}
+
+fn deleted(path: PathBuf) -> ShreddedFile {
+    ShreddedFile {
+        path,
+        action: ShredAction::Deleted,
+    }
+}
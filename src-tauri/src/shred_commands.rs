@@ -0,0 +1,108 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use upmemo::private_memo::PrivateMemoSessions;
+use upmemo::secret_scan::SecretMarkers;
+use upmemo::shred::{
+    shred_backups, shred_temporary_files, MemoRemnant, ShredAction, ShredError, ShreddedFile,
+};
+
+use crate::draft_commands;
+use crate::memo_store_state::memo_store;
+use crate::note_commands::map_memo_store_error;
+use crate::version_history_commands;
+
+#[derive(Serialize)]
+pub struct ShredReport {
+    removed_from_store: bool,
+    files: Vec<ShreddedFile>,
+}
+
+#[tauri::command]
+pub fn shred_memo(app_handle: AppHandle, id: String) -> Result<ShredReport, String> {
+    let repository = memo_store(&app_handle)?;
+    let contents = repository
+        .transact(|store| {
+            let mut contents = Vec::new();
+            for entries in [&mut store.history, &mut store.archive, &mut store.trash] {
+                entries.retain(|entry| {
+                    let matched = entry.id == id;
+                    if matched {
+                        contents.push(entry.content.clone());
+                    }
+                    !matched
+                });
+            }
+            if store.current_memo_id.as_deref() == Some(id.as_str()) {
+                contents.push(std::mem::take(&mut store.current_content));
+                store.current_memo_id = None;
+            }
+            Ok(contents)
+        })
+        .map_err(map_memo_store_error)?;
+    repository.flush().map_err(map_memo_store_error)?;
+    app_handle.state::<PrivateMemoSessions>().relock(&id);
+
+    let removed_from_store = !contents.is_empty();
+    let mut files = Vec::new();
+    if removed_from_store {
+        files.push(ShreddedFile {
+            path: repository.path().to_path_buf(),
+            action: ShredAction::Rewritten,
+        });
+    }
+
+    files.extend(purge_memo_copies(&app_handle, &id, &contents)?);
+    files.extend(
+        version_history_commands::discard_version_history(
+            &app_handle,
+            "記事已銷毀，含有這則記事的版本歷史已刪除並停用",
+        )?
+        .map(|path| ShreddedFile {
+            path,
+            action: ShredAction::Deleted,
+        }),
+    );
+
+    Ok(ShredReport {
+        removed_from_store,
+        files,
+    })
+}
+
+pub fn purge_memo_copies(
+    app: &AppHandle,
+    id: &str,
+    contents: &[String],
+) -> Result<Vec<ShreddedFile>, String> {
+    let repository = memo_store(app)?;
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))?;
+    let remnant = MemoRemnant { id, contents };
+
+    let mut files = shred_backups(&data_dir.join("backups"), &remnant, repository.committer())
+        .map_err(map_shred_error)?;
+    files.extend(
+        shred_temporary_files(&data_dir, &remnant, repository.committer())
+            .map_err(map_shred_error)?,
+    );
+    files.extend(
+        draft_commands::purge_memo_drafts(app, id, contents)?
+            .into_iter()
+            .map(|path| ShreddedFile {
+                path,
+                action: ShredAction::Deleted,
+            }),
+    );
+    if let Some(markers) = app.try_state::<SecretMarkers>() {
+        markers
+            .forget(id)
+            .map_err(|error| format!("無法清除機密警告: {error}"))?;
+    }
+    Ok(files)
+}
+
+fn map_shred_error(error: ShredError) -> String {
+    format!("徹底刪除失敗: {error}")
+}
@@ -0,0 +1,45 @@
+use std::thread;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+pub use upmemo::shutdown_state::ShutdownState;
+use upmemo::shutdown_state::SHUTDOWN_FLUSH_TIMEOUT;
+
+use crate::{draft_commands, store_flush};
+
+pub const FLUSH_PENDING_AUTOSAVE_EVENT: &str = "flush-pending-autosave";
+
+pub fn request_shutdown(app: &AppHandle) {
+    if !app.state::<ShutdownState>().begin() {
+        return;
+    }
+
+    let frontend_notified = match app.emit_to("main", FLUSH_PENDING_AUTOSAVE_EVENT, ()) {
+        Ok(()) => true,
+        Err(error) => {
+            eprintln!("無法通知主視窗儲存: {}", error);
+            false
+        }
+    };
+
+    let app_handle = app.clone();
+    thread::spawn(move || {
+        let state = app_handle.state::<ShutdownState>();
+        let acknowledged =
+            frontend_notified && state.wait_for_acknowledgement(SHUTDOWN_FLUSH_TIMEOUT);
+        if !acknowledged {
+            eprintln!("等待主視窗儲存逾時，直接寫入目前內容");
+        }
+
+        store_flush::flush_memo_store(&app_handle);
+        if acknowledged {
+            draft_commands::discard_active_draft(&app_handle);
+        }
+        state.complete();
+        app_handle.exit(0);
+    });
+}
+
+#[tauri::command]
+pub fn acknowledge_autosave_flush(state: State<'_, ShutdownState>) {
+    state.acknowledge();
+}
@@ -0,0 +1,73 @@
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+pub const SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_millis(1500);
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum ShutdownPhase {
+    #[default]
+    Running,
+    WaitingForFrontend,
+    Acknowledged,
+    Complete,
+}
+
+#[derive(Default)]
+pub struct ShutdownState {
+    phase: Mutex<ShutdownPhase>,
+    acknowledged: Condvar,
+}
+
+impl ShutdownState {
+    pub fn begin(&self) -> bool {
+        match self.phase.lock() {
+            Ok(mut phase) if *phase == ShutdownPhase::Running => {
+                *phase = ShutdownPhase::WaitingForFrontend;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn acknowledge(&self) {
+        if let Ok(mut phase) = self.phase.lock() {
+            if *phase == ShutdownPhase::WaitingForFrontend {
+                *phase = ShutdownPhase::Acknowledged;
+                self.acknowledged.notify_all();
+            }
+        }
+    }
+
+    pub fn wait_for_acknowledgement(&self, timeout: Duration) -> bool {
+        let Ok(phase) = self.phase.lock() else {
+            return false;
+        };
+
+        self.acknowledged
+            .wait_timeout_while(phase, timeout, |phase| {
+                *phase == ShutdownPhase::WaitingForFrontend
+            })
+            .map(|(phase, _)| *phase == ShutdownPhase::Acknowledged)
+            .unwrap_or(false)
+    }
+
+    pub fn complete(&self) {
+        if let Ok(mut phase) = self.phase.lock() {
+            *phase = ShutdownPhase::Complete;
+        }
+    }
+
+    pub fn is_flushing(&self) -> bool {
+        self.phase
+            .lock()
+            .map(|phase| *phase == ShutdownPhase::WaitingForFrontend)
+            .unwrap_or(false)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.phase
+            .lock()
+            .map(|phase| *phase == ShutdownPhase::Complete)
+            .unwrap_or(true)
+    }
+}
@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::markdown_import::markdown_to_memo;
+use crate::memo_import::{
+    append_tags, failure, modified_secs, parse_iso8601, tidy, ImportFailureKind, ImportedMemo,
+    PreparedImport, PINNED_LABEL,
+};
+use crate::memo_store::MemoCollection;
+use crate::models::MemoEntry;
+
+const NOTES_FILE: &str = "notes.json";
+const EXPORT_SUBDIRECTORY: &str = "source";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SimplenoteExport {
+    active_notes: Vec<SimplenoteNote>,
+    trashed_notes: Vec<SimplenoteNote>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SimplenoteNote {
+    id: String,
+    content: String,
+    creation_date: String,
+    last_modified: String,
+    tags: Vec<String>,
+    pinned: bool,
+    markdown: bool,
+    deleted: bool,
+}
+
+pub fn prepare_simplenote_import(source: &Path) -> PreparedImport {
+    let mut prepared = PreparedImport::default();
+    let path = locate_notes_file(source);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            prepared
+                .failures
+                .push(failure(ImportFailureKind::Unreadable, &path, error));
+            return prepared;
+        }
+    };
+    let export: SimplenoteExport = match serde_json::from_slice(&bytes) {
+        Ok(export) => export,
+        Err(error) => {
+            prepared
+                .failures
+                .push(failure(ImportFailureKind::InvalidJson, &path, error));
+            return prepared;
+        }
+    };
+
+    let notes = export
+        .active_notes
+        .into_iter()
+        .map(|note| (note, false))
+        .chain(export.trashed_notes.into_iter().map(|note| (note, true)));
+    let base = path.parent().unwrap_or(Path::new(""));
+    for (note, trashed) in notes {
+        let deleted = trashed || note.deleted;
+        let text = note.content.replace("\r\n", "\n");
+        let mut content = if note.markdown {
+            markdown_to_memo(&text, base, &mut prepared.failures)
+        } else {
+            text
+        };
+        let mut tags = note.tags;
+        if note.pinned {
+            tags.insert(0, PINNED_LABEL.to_string());
+        }
+        append_tags(&mut content, &tags);
+        let content = tidy(&content);
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let timestamp = parse_iso8601(&note.last_modified)
+            .or_else(|| parse_iso8601(&note.creation_date))
+            .unwrap_or_else(|| modified_secs(&path));
+        prepared.memos.push(ImportedMemo {
+            path: path.clone(),
+            entry: MemoEntry {
+                id: if note.id.is_empty() {
+                    uuid::Uuid::new_v4().to_string()
+                } else {
+                    note.id
+                },
+                content,
+                timestamp,
+            },
+            collection: if deleted {
+                MemoCollection::Trash
+            } else {
+                MemoCollection::History
+            },
+        });
+    }
+
+    prepared
+}
+
+fn locate_notes_file(source: &Path) -> PathBuf {
+    if !source.is_dir() {
+        return source.to_path_buf();
+    }
+    let nested = source.join(EXPORT_SUBDIRECTORY).join(NOTES_FILE);
+    if nested.is_file() {
+        nested
+    } else {
+        source.join(NOTES_FILE)
+    }
+}
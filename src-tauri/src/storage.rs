@@ -1,4 +1,6 @@
-use upmemo::memo_store::{AtomicFileCommit, CommitAdapter, MemoStoreV2, MEMO_STORE_VERSION};
+use upmemo::memo_store::{
+    AtomicFileCommit, CommitAdapter, MemoStoreV2, StoreEncoding, MEMO_STORE_VERSION,
+};
 use upmemo::models::MemoEntry;
 use upmemo::models::FontConfig;
 use serde::de::DeserializeOwned;
@@ -253,6 +255,7 @@ pub fn migrate_legacy_store_in_dir(data_dir: &Path) -> Result<MigrationOutcome,
         history: entries.history,
         archive: entries.archive,
         trash: entries.trash,
+        encoding: StoreEncoding::Json,
     };
 
     let bytes = serde_json::to_vec_pretty(&store).map_err(|error| {
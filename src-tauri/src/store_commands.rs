@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use tauri::AppHandle;
+use upmemo::memo_store::StoreEncoding;
+
+use crate::memo_store_state::memo_store;
+use crate::note_commands::map_memo_store_error;
+
+#[tauri::command]
+pub fn get_memo_store_encoding(app_handle: AppHandle) -> Result<StoreEncoding, String> {
+    memo_store(&app_handle)?
+        .encoding()
+        .map_err(map_memo_store_error)
+}
+
+#[tauri::command]
+pub fn set_memo_store_encoding(
+    app_handle: AppHandle,
+    encoding: StoreEncoding,
+) -> Result<bool, String> {
+    let repository = memo_store(&app_handle)?;
+    let changed = repository
+        .set_encoding(encoding)
+        .map_err(map_memo_store_error)?;
+    repository.flush().map_err(map_memo_store_error)?;
+    Ok(changed)
+}
+
+#[tauri::command]
+pub fn export_memo_store_json(app_handle: AppHandle, path: String) -> Result<(), String> {
+    let destination = PathBuf::from(path);
+    if destination.as_path() == memo_store(&app_handle)?.path() {
+        return Err("不可覆寫目前使用中的 memo_store.json".to_string());
+    }
+
+    memo_store(&app_handle)?
+        .export_plain_json(&destination)
+        .map_err(map_memo_store_error)
+}
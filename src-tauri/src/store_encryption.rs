@@ -0,0 +1,160 @@
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Manager};
+use upmemo::memo_store::{
+    stage_reseal, AtomicFileCommit, EncryptionError, KdfParams, SealedCommit, StoreKey,
+};
+
+use crate::draft_commands;
+use crate::memo_store_state::{install_memo_store, memo_store, memo_store_path, AppMemoStore};
+use crate::mirror_commands;
+use crate::note_commands::map_memo_store_error;
+use crate::sync_commands;
+use crate::version_history_commands;
+
+static UNLOCK_GATE: Mutex<()> = Mutex::new(());
+
+#[tauri::command]
+pub fn is_memo_store_locked(app_handle: AppHandle) -> bool {
+    app_handle.try_state::<AppMemoStore>().is_none()
+}
+
+#[tauri::command]
+pub fn is_memo_store_encrypted(app_handle: AppHandle) -> Result<bool, String> {
+    Ok(memo_store(&app_handle)?.committer().key().is_some())
+}
+
+#[tauri::command]
+pub async fn unlock_memo_store(app_handle: AppHandle, passphrase: String) -> Result<(), String> {
+    let _gate = UNLOCK_GATE
+        .lock()
+        .map_err(|_| "無法鎖定解鎖流程".to_string())?;
+    if app_handle.try_state::<AppMemoStore>().is_some() {
+        return Ok(());
+    }
+
+    let path = memo_store_path(&app_handle)?;
+    let sealed = fs::read(&path).map_err(|error| format!("無法讀取 memo_store: {error}"))?;
+    let key = StoreKey::derive_for(&passphrase, &sealed).map_err(map_encryption_error)?;
+    let repository = AppMemoStore::open_with(path, SealedCommit::new(AtomicFileCommit, Some(key)))
+        .map_err(map_memo_store_error)?;
+    install_memo_store(&app_handle, repository)
+        .map_err(|error| format!("無法啟用記事儲存: {error}"))
+}
+
+#[tauri::command]
+pub async fn enable_memo_store_encryption(
+    app_handle: AppHandle,
+    passphrase: String,
+) -> Result<(), String> {
+    let repository = memo_store(&app_handle)?;
+    if repository.committer().key().is_some() {
+        return Err("記事已經加密".to_string());
+    }
+
+    let key = derive_new_key(&passphrase)?;
+    rekey(&app_handle, &repository, None, Some(key))?;
+    draft_commands::purge_all_drafts(&app_handle)
+        .map_err(|error| format!("記事已加密，但無法清除未加密的草稿: {error}"))?;
+    sync_commands::withdraw_from_sync(&app_handle)
+        .map_err(|error| format!("記事已加密，但{error}"))?;
+    mirror_commands::withdraw_plain_mirror(&app_handle)
+        .map_err(|error| format!("記事已加密，但{error}"))?;
+    version_history_commands::discard_version_history(
+        &app_handle,
+        "記事已加密，未加密的版本歷史已刪除並停用",
+    )
+    .map(|_| ())
+    .map_err(|error| format!("記事已加密，但{error}"))
+}
+
+#[tauri::command]
+pub async fn change_memo_store_passphrase(
+    app_handle: AppHandle,
+    current_passphrase: String,
+    new_passphrase: String,
+) -> Result<(), String> {
+    let repository = memo_store(&app_handle)?;
+    let current = verified_key(&repository, &current_passphrase)?;
+    let key = derive_new_key(&new_passphrase)?;
+    rekey(&app_handle, &repository, Some(current), Some(key))
+}
+
+#[tauri::command]
+pub async fn disable_memo_store_encryption(
+    app_handle: AppHandle,
+    passphrase: String,
+) -> Result<(), String> {
+    let repository = memo_store(&app_handle)?;
+    let current = verified_key(&repository, &passphrase)?;
+    rekey(&app_handle, &repository, Some(current), None)
+}
+
+fn derive_new_key(passphrase: &str) -> Result<Arc<StoreKey>, String> {
+    if passphrase.is_empty() {
+        return Err("密碼不可為空白".to_string());
+    }
+
+    StoreKey::derive(passphrase, KdfParams::default())
+        .map(Arc::new)
+        .map_err(map_encryption_error)
+}
+
+fn verified_key(repository: &AppMemoStore, passphrase: &str) -> Result<Arc<StoreKey>, String> {
+    let current = repository
+        .committer()
+        .key()
+        .ok_or_else(|| "記事尚未加密".to_string())?;
+    if current
+        .matches_passphrase(passphrase)
+        .map_err(map_encryption_error)?
+    {
+        Ok(current)
+    } else {
+        Err("目前密碼錯誤".to_string())
+    }
+}
+
+fn rekey(
+    app: &AppHandle,
+    repository: &AppMemoStore,
+    previous: Option<Arc<StoreKey>>,
+    next: Option<Arc<StoreKey>>,
+) -> Result<(), String> {
+    let backups = app
+        .path()
+        .app_data_dir()
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))?
+        .join("backups");
+    let swapped = stage_reseal(&backups, previous.as_deref(), next.as_deref())
+        .and_then(|staged| staged.swap_reversibly())
+        .map_err(|error| format!("備份重新加密失敗: {error}"))?;
+
+    let recommitted = repository
+        .recommit_with(
+            |committer| committer.replace_key(next.clone()),
+            |committer, restored| {
+                committer.replace_key(restored);
+            },
+        )
+        .map_err(map_memo_store_error);
+    if let Err(error) = recommitted {
+        if let Err(restore) = swapped.revert() {
+            eprintln!("無法還原重新加密前的備份: {}", restore);
+        }
+        return Err(error);
+    }
+
+    swapped
+        .finish()
+        .map(|_| ())
+        .map_err(|error| format!("備份已重新加密，但無法刪除舊的備份: {error}"))
+}
+
+fn map_encryption_error(error: EncryptionError) -> String {
+    match error {
+        EncryptionError::Decrypt => "密碼錯誤".to_string(),
+        error => format!("記事加密操作失敗: {error}"),
+    }
+}
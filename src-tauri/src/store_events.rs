@@ -0,0 +1,15 @@
+use tauri::{AppHandle, Emitter, Manager};
+use upmemo::memo_store::MemoStoreError;
+
+use crate::memo_store_state::AppMemoStore;
+
+pub const MEMO_STORE_ENTRIES_CHANGED_EVENT: &str = "memo-store-entries-changed";
+
+pub fn forward_store_changes(app: &AppHandle) -> Result<(), MemoStoreError> {
+    let app_handle = app.clone();
+    app.state::<AppMemoStore>().subscribe(move |changes| {
+        if let Err(error) = app_handle.emit(MEMO_STORE_ENTRIES_CHANGED_EVENT, changes) {
+            eprintln!("無法通知 memo_store 項目變更: {}", error);
+        }
+    })
+}
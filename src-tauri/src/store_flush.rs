@@ -0,0 +1,33 @@
+use std::thread;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::draft_commands;
+use crate::memo_store_state::AppMemoStore;
+
+pub const WRITE_BEHIND_WINDOW: Duration = Duration::from_millis(750);
+
+pub fn spawn_write_behind_flusher(app: &AppHandle) {
+    let app_handle = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(WRITE_BEHIND_WINDOW / 2);
+        match app_handle.state::<AppMemoStore>().flush_if_due() {
+            Ok(true) => draft_commands::compact_active_draft(&app_handle),
+            Ok(false) => {}
+            Err(error) => eprintln!("memo_store 延遲寫入失敗: {}", error),
+        }
+    });
+}
+
+pub fn flush_memo_store(app: &AppHandle) {
+    let Some(repository) = app.try_state::<AppMemoStore>() else {
+        return;
+    };
+
+    match repository.flush() {
+        Ok(true) => draft_commands::compact_active_draft(app),
+        Ok(false) => {}
+        Err(error) => eprintln!("memo_store 寫入失敗: {}", error),
+    }
+}
@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::memo_store_state::AppMemoStore;
+
+pub const MEMO_STORE_CHANGED_EVENT: &str = "memo-store-changed";
+
+pub struct StoreWatcher {
+    _watcher: Mutex<RecommendedWatcher>,
+}
+
+pub fn watch_memo_store(app: &AppHandle) -> Result<StoreWatcher, Box<dyn std::error::Error>> {
+    let store_path = app.state::<AppMemoStore>().path().to_path_buf();
+    let directory = store_path
+        .parent()
+        .ok_or("memo_store 路徑沒有上層目錄")?
+        .to_path_buf();
+    fs::create_dir_all(&directory)?;
+
+    let app_handle = app.clone();
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+            Ok(event) if touches_store(&event, &store_path) => reload_and_notify(&app_handle),
+            Ok(_) => {}
+            Err(error) => eprintln!("memo_store 監看失敗: {}", error),
+        })?;
+    watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+
+    Ok(StoreWatcher {
+        _watcher: Mutex::new(watcher),
+    })
+}
+
+fn touches_store(event: &Event, store_path: &Path) -> bool {
+    !event.kind.is_access()
+        && event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == store_path.file_name())
+}
+
+fn reload_and_notify(app: &AppHandle) {
+    let repository = app.state::<AppMemoStore>();
+    match repository.reload_if_changed() {
+        Ok(Some(_)) => {
+            if let Err(error) = app.emit(MEMO_STORE_CHANGED_EVENT, ()) {
+                eprintln!("無法通知 memo_store 變更: {}", error);
+            }
+        }
+        Ok(None) => {}
+        Err(error) => eprintln!("外部修改的 memo_store 無法載入: {}", error),
+    }
+}
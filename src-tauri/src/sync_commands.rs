@@ -0,0 +1,258 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use upmemo::memo_store::AtomicFileCommit;
+use upmemo::memo_sync::{
+    publish_pending, sync_memos, SyncBackendConfig, SyncError, SyncReport, SyncState,
+};
+
+use crate::clock::get_timestamp;
+use crate::memo_store_state::{memo_store, AppMemoStore};
+use crate::note_commands::map_memo_store_error;
+
+pub const MEMO_SYNC_STATUS_EVENT: &str = "memo-sync-status";
+pub const CURRENT_MEMO_SYNCED_EVENT: &str = "current-memo-synced";
+const SYNC_STATE_FILE: &str = "sync_state.json";
+const SYNC_DEBOUNCE: Duration = Duration::from_secs(2);
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Default, Serialize)]
+pub struct SyncStatus {
+    backend: Option<SyncBackendConfig>,
+    device_id: String,
+    syncing: bool,
+    last_sync: Option<u64>,
+    last_report: Option<SyncReport>,
+    last_error: Option<String>,
+}
+
+pub struct MemoSync {
+    state: Mutex<SyncState>,
+    status: Mutex<SyncStatus>,
+}
+
+impl MemoSync {
+    fn state(&self) -> MutexGuard<'_, SyncState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn status(&self) -> MutexGuard<'_, SyncStatus> {
+        self.status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub fn install_memo_sync(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let state = SyncState::load(&state_path(app)?)?;
+    let (trigger, requests) = mpsc::channel();
+
+    if state.backend.is_some() {
+        let _ = trigger.send(());
+    }
+    app.state::<AppMemoStore>().subscribe(move |_| {
+        let _ = trigger.send(());
+    })?;
+
+    app.manage(MemoSync {
+        status: Mutex::new(SyncStatus {
+            backend: state.backend.clone(),
+            device_id: state.device_id.clone(),
+            last_sync: state.last_sync,
+            ..SyncStatus::default()
+        }),
+        state: Mutex::new(state),
+    });
+    spawn_sync_worker(app, requests);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_sync_status(app_handle: AppHandle) -> SyncStatus {
+    app_handle
+        .try_state::<MemoSync>()
+        .map(|sync| sync.status().clone())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn enable_folder_sync(app_handle: AppHandle, folder: String) -> Result<SyncReport, String> {
+    let folder = folder.trim();
+    if folder.is_empty() {
+        return Err("請輸入同步資料夾路徑".to_string());
+    }
+    let repository = memo_store(&app_handle)?;
+    if repository.committer().key().is_some() {
+        return Err("記事已加密，無法啟用同步".to_string());
+    }
+
+    set_backend(
+        &app_handle,
+        Some(SyncBackendConfig::Folder {
+            path: PathBuf::from(folder),
+        }),
+    )?;
+    sync_now(&app_handle)?.ok_or_else(|| "尚未設定同步".to_string())
+}
+
+#[tauri::command]
+pub fn disable_sync(app_handle: AppHandle) -> Result<(), String> {
+    set_backend(&app_handle, None)
+}
+
+#[tauri::command]
+pub fn sync_memos_now(app_handle: AppHandle) -> Result<SyncReport, String> {
+    sync_now(&app_handle)?.ok_or_else(|| "尚未設定同步".to_string())
+}
+
+pub fn withdraw_from_sync(app: &AppHandle) -> Result<(), String> {
+    let Some(sync) = app.try_state::<MemoSync>() else {
+        return Ok(());
+    };
+    let (backend, device) = {
+        let state = sync.state();
+        (state.backend.clone(), state.device_id.clone())
+    };
+    let Some(backend) = backend else {
+        return Ok(());
+    };
+    backend
+        .open()
+        .withdraw(&device)
+        .map_err(|error| format!("無法移除同步資料夾中未加密的記事: {error}"))?;
+    set_backend(app, None)
+}
+
+fn set_backend(app: &AppHandle, backend: Option<SyncBackendConfig>) -> Result<(), String> {
+    let path = state_path(app)?;
+    let sync = app
+        .try_state::<MemoSync>()
+        .ok_or_else(|| "記事已加密，請先輸入密碼解鎖".to_string())?;
+    let mut state = sync.state();
+    let mut updated = state.clone();
+    updated.set_backend(backend);
+    updated
+        .save(&path, &AtomicFileCommit)
+        .map_err(map_sync_error)?;
+    *state = updated;
+
+    let backend = state.backend.clone();
+    let last_sync = state.last_sync;
+    drop(state);
+    update_status(app, |status| {
+        status.backend = backend;
+        status.last_sync = last_sync;
+        status.last_report = None;
+        status.last_error = None;
+    });
+    Ok(())
+}
+
+fn sync_now(app: &AppHandle) -> Result<Option<SyncReport>, String> {
+    let path = state_path(app)?;
+    let Some(sync) = app.try_state::<MemoSync>() else {
+        return Ok(None);
+    };
+    let mut state = sync.state();
+    let Some(backend) = state.backend.as_ref().map(SyncBackendConfig::open) else {
+        return Ok(None);
+    };
+    let repository = memo_store(app)?;
+    if repository.committer().key().is_some() {
+        return Err("記事已加密，無法同步".to_string());
+    }
+
+    update_status(app, |status| status.syncing = true);
+    let mut working = state.clone();
+    let synced = repository
+        .transact(|store| {
+            Ok(sync_memos(
+                store,
+                &mut working,
+                backend.as_ref(),
+                get_timestamp(),
+            ))
+        })
+        .map_err(map_memo_store_error)
+        .and_then(|result| result.map_err(map_sync_error))
+        .and_then(|report| {
+            repository.flush().map_err(map_memo_store_error)?;
+            working
+                .save(&path, &AtomicFileCommit)
+                .map_err(map_sync_error)?;
+            *state = working.clone();
+            Ok(report)
+        });
+    if synced.as_ref().is_ok_and(|report| report.current_replaced) {
+        if let Err(error) = app.emit(CURRENT_MEMO_SYNCED_EVENT, ()) {
+            eprintln!("無法通知目前記事已同步: {}", error);
+        }
+    }
+    let result = synced.and_then(|report| {
+        publish_pending(&mut working, backend.as_ref()).map_err(map_sync_error)?;
+        working
+            .save(&path, &AtomicFileCommit)
+            .map_err(map_sync_error)?;
+        *state = working;
+        Ok(report)
+    });
+    let last_sync = state.last_sync;
+    drop(state);
+
+    update_status(app, |status| {
+        status.syncing = false;
+        status.last_sync = last_sync;
+        match &result {
+            Ok(report) => {
+                status.last_report = Some(report.clone());
+                status.last_error = None;
+            }
+            Err(error) => status.last_error = Some(error.clone()),
+        }
+    });
+    result.map(Some)
+}
+
+fn update_status(app: &AppHandle, update: impl FnOnce(&mut SyncStatus)) {
+    let sync = app.state::<MemoSync>();
+    let status = {
+        let mut status = sync.status();
+        update(&mut status);
+        status.clone()
+    };
+    if let Err(error) = app.emit(MEMO_SYNC_STATUS_EVENT, status) {
+        eprintln!("無法通知同步狀態: {}", error);
+    }
+}
+
+fn spawn_sync_worker(app: &AppHandle, requests: Receiver<()>) {
+    let app_handle = app.clone();
+    thread::spawn(move || loop {
+        match requests.recv_timeout(SYNC_INTERVAL) {
+            Ok(()) => while requests.recv_timeout(SYNC_DEBOUNCE).is_ok() {},
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if let Err(error) = sync_now(&app_handle) {
+            eprintln!("記事同步失敗: {}", error);
+        }
+    });
+}
+
+fn state_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|directory| directory.join(SYNC_STATE_FILE))
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))
+}
+
+fn map_sync_error(error: SyncError) -> String {
+    format!("記事同步失敗: {error}")
+}
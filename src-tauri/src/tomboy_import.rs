@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::memo_import::{
+    append_tags, failure, modified_secs, parse_iso8601, resolve_xml_reference, start_line, tidy,
+    ImportFailureKind, ImportedMemo, PreparedImport,
+};
+use crate::memo_store::MemoCollection;
+use crate::models::MemoEntry;
+
+const NOTE_EXTENSION: &str = "note";
+const NOTEBOOK_TAG_PREFIX: &str = "system:notebook:";
+const SYSTEM_TAG_PREFIX: &str = "system:";
+const TEMPLATE_TAG: &str = "system:template";
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TomboyNote {
+    pub title: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub last_change_date: Option<u64>,
+    pub create_date: Option<u64>,
+}
+
+impl TomboyNote {
+    fn is_template(&self) -> bool {
+        self.tags.iter().any(|tag| tag == TEMPLATE_TAG)
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.tags
+            .iter()
+            .filter_map(|tag| match tag.strip_prefix(NOTEBOOK_TAG_PREFIX) {
+                Some(notebook) => Some(notebook.to_string()),
+                None if tag.starts_with(SYSTEM_TAG_PREFIX) => None,
+                None => Some(tag.clone()),
+            })
+            .collect()
+    }
+}
+
+pub fn prepare_tomboy_import(source: &Path) -> PreparedImport {
+    let mut prepared = PreparedImport::default();
+    let entries = match fs::read_dir(source) {
+        Ok(entries) => entries,
+        Err(error) => {
+            prepared
+                .failures
+                .push(failure(ImportFailureKind::Unreadable, source, error));
+            return prepared;
+        }
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case(NOTE_EXTENSION))
+        })
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        import_note(&path, &mut prepared);
+    }
+
+    prepared
+}
+
+pub fn parse_tomboy_note(xml: &str) -> Result<TomboyNote, quick_xml::Error> {
+    let mut note = TomboyNote::default();
+    let mut reader = Reader::from_str(xml);
+    let mut elements: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_content = false;
+    let mut lists: usize = 0;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => {
+                let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+                if in_content {
+                    match name.as_str() {
+                        "bold" => note.content.push_str("**"),
+                        "italic" => note.content.push('*'),
+                        "strikethrough" => note.content.push_str("~~"),
+                        "list" => lists += 1,
+                        "list-item" => {
+                            start_line(&mut note.content);
+                            note.content.push_str(&"  ".repeat(lists.saturating_sub(1)));
+                            note.content.push_str("- ");
+                        }
+                        _ => {}
+                    }
+                } else if name == "note-content" {
+                    in_content = true;
+                }
+                field.clear();
+                elements.push(name);
+            }
+            Event::End(_) => {
+                let Some(name) = elements.pop() else {
+                    continue;
+                };
+                if in_content {
+                    match name.as_str() {
+                        "bold" => note.content.push_str("**"),
+                        "italic" => note.content.push('*'),
+                        "strikethrough" => note.content.push_str("~~"),
+                        "list" => {
+                            lists = lists.saturating_sub(1);
+                            start_line(&mut note.content);
+                        }
+                        "note-content" => in_content = false,
+                        _ => {}
+                    }
+                    continue;
+                }
+                let value = field.trim();
+                match name.as_str() {
+                    "title" if elements.last().is_some_and(|parent| parent == "note") => {
+                        note.title = value.to_string();
+                    }
+                    "last-change-date" => note.last_change_date = parse_iso8601(value),
+                    "create-date" => note.create_date = parse_iso8601(value),
+                    "tag" if !value.is_empty() => note.tags.push(value.to_string()),
+                    _ => {}
+                }
+                field.clear();
+            }
+            Event::Text(text) => {
+                let text = text.xml_content()?;
+                if in_content {
+                    note.content.push_str(&text);
+                } else {
+                    field.push_str(&text);
+                }
+            }
+            Event::CData(text) => {
+                let text = text.decode()?;
+                if in_content {
+                    note.content.push_str(&text);
+                } else {
+                    field.push_str(&text);
+                }
+            }
+            Event::GeneralRef(reference) => {
+                let text = resolve_xml_reference(&reference)?;
+                if in_content {
+                    note.content.push_str(&text);
+                } else {
+                    field.push_str(&text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    note.content = tidy(&note.content);
+    Ok(note)
+}
+
+fn import_note(path: &Path, prepared: &mut PreparedImport) {
+    let xml = match fs::read_to_string(path) {
+        Ok(xml) => xml,
+        Err(error) => {
+            prepared
+                .failures
+                .push(failure(ImportFailureKind::Unreadable, path, error));
+            return;
+        }
+    };
+    let note = match parse_tomboy_note(&xml) {
+        Ok(note) => note,
+        Err(error) => {
+            prepared
+                .failures
+                .push(failure(ImportFailureKind::InvalidXml, path, error));
+            return;
+        }
+    };
+    if note.is_template() {
+        return;
+    }
+
+    let mut content = note.content.clone();
+    if !note.title.is_empty() && content.lines().next() != Some(note.title.as_str()) {
+        content = format!("{}\n\n{content}", note.title);
+    }
+    append_tags(&mut content, &note.labels());
+    let content = tidy(&content);
+    if content.trim().is_empty() {
+        return;
+    }
+
+    let id = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .filter(|stem| !stem.is_empty())
+        .map_or_else(|| uuid::Uuid::new_v4().to_string(), str::to_string);
+    let timestamp = note
+        .last_change_date
+        .or(note.create_date)
+        .unwrap_or_else(|| modified_secs(path));
+    prepared.memos.push(ImportedMemo {
+        path: path.to_path_buf(),
+        entry: MemoEntry {
+            id,
+            content,
+            timestamp,
+        },
+        collection: MemoCollection::History,
+    });
+}
@@ -2,6 +2,8 @@ use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder};
 use tauri::Manager;
 
+use crate::{app_lock_commands, shutdown, store_flush};
+
 pub fn setup_tray(app: &mut tauri::App) -> tauri::Result<()> {
     let show_item = MenuItem::with_id(app, "show", "顯示便條", true, None::<&str>)?;
     let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
@@ -13,7 +15,7 @@ pub fn setup_tray(app: &mut tauri::App) -> tauri::Result<()> {
         .show_menu_on_left_click(false)
         .on_menu_event(|app, event| match event.id().as_ref() {
             "show" => toggle_main_window(app),
-            "quit" => app.exit(0),
+            "quit" => shutdown::request_shutdown(app),
             _ => {}
         })
         .on_tray_icon_event(|tray, event| {
@@ -56,6 +58,8 @@ pub fn toggle_main_window(app: &tauri::AppHandle) {
     if let Some(main_window) = app.get_webview_window("main") {
         if should_hide {
             let _ = main_window.hide();
+            store_flush::flush_memo_store(app);
+            app_lock_commands::engage_app_lock(app);
         } else {
             let _ = main_window.show();
             let _ = main_window.set_focus();
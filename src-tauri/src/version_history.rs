@@ -0,0 +1,357 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use git2::{BlameOptions, Commit, ErrorCode, Oid, Repository, Signature, Time, Tree};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::memo_store::{CommitAdapter, CommitError, MemoStoreV2};
+use crate::models::{memo_file_name, MemoEntry};
+
+pub const VERSION_HISTORY_DIR: &str = "version_history.git";
+const INDEX_FILE: &str = "index.json";
+const CURRENT_FILE: &str = "current.txt";
+const MEMOS_DIR: &str = "memos";
+const MEMO_EXTENSION: &str = "txt";
+const AUTHOR_NAME: &str = "UpMemo";
+const AUTHOR_EMAIL: &str = "upmemo@localhost";
+const FILE_MODE: i32 = 0o100644;
+const TREE_MODE: i32 = 0o040000;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VersionHistoryConfig {
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct HistoryCommit {
+    pub id: String,
+    pub timestamp: u64,
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoRevisionKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct MemoRevision {
+    pub commit: HistoryCommit,
+    pub kind: MemoRevisionKind,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct BlameLine {
+    pub line: usize,
+    pub text: String,
+    pub commit: String,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum VersionHistoryError {
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+    #[error("memo {id} is not in the latest version")]
+    MemoNotFound { id: String },
+    #[error("version {commit} has a malformed index: {source}")]
+    MalformedIndex {
+        commit: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("failed to read version history config {path}: {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("version history config {path} is malformed: {source}")]
+    MalformedConfig {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("failed to serialize version history data: {0}")]
+    Serialize(#[source] serde_json::Error),
+    #[error(transparent)]
+    Commit(#[from] CommitError),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+struct SnapshotIndex {
+    current_memo_id: Option<String>,
+    history: Vec<IndexedMemo>,
+    archive: Vec<IndexedMemo>,
+    trash: Vec<IndexedMemo>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct IndexedMemo {
+    id: String,
+    timestamp: u64,
+}
+
+pub fn read_version_history_config(
+    path: &Path,
+) -> Result<VersionHistoryConfig, VersionHistoryError> {
+    match fs::read(path) {
+        Ok(bytes) => {
+            serde_json::from_slice(&bytes).map_err(|source| VersionHistoryError::MalformedConfig {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            Ok(VersionHistoryConfig::default())
+        }
+        Err(source) => Err(VersionHistoryError::Read {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+pub fn write_version_history_config(
+    path: &Path,
+    config: VersionHistoryConfig,
+    committer: &impl CommitAdapter,
+) -> Result<(), VersionHistoryError> {
+    let bytes = serde_json::to_vec_pretty(&config).map_err(VersionHistoryError::Serialize)?;
+    committer.commit(path, &bytes)?;
+    Ok(())
+}
+
+pub struct VersionHistory {
+    repository: Repository,
+}
+
+impl VersionHistory {
+    pub fn open(directory: &Path) -> Result<Self, VersionHistoryError> {
+        let repository = match Repository::open_bare(directory) {
+            Ok(repository) => repository,
+            Err(error) if error.code() == ErrorCode::NotFound => Repository::init_bare(directory)?,
+            Err(error) => return Err(error.into()),
+        };
+        Ok(Self { repository })
+    }
+
+    pub fn record(
+        &self,
+        store: &MemoStoreV2,
+        message: &str,
+        timestamp: u64,
+    ) -> Result<Option<HistoryCommit>, VersionHistoryError> {
+        let tree_id = self.write_tree(store)?;
+        let parent = self.head_commit()?;
+        if parent
+            .as_ref()
+            .is_some_and(|parent| parent.tree_id() == tree_id)
+        {
+            return Ok(None);
+        }
+
+        let tree = self.repository.find_tree(tree_id)?;
+        let time = Time::new(i64::try_from(timestamp).unwrap_or(i64::MAX), 0);
+        let signature = Signature::new(AUTHOR_NAME, AUTHOR_EMAIL, &time)?;
+        let parents: Vec<&Commit<'_>> = parent.iter().collect();
+        let id = self.repository.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?;
+        Ok(Some(history_commit(&self.repository.find_commit(id)?)))
+    }
+
+    pub fn log(&self, limit: usize) -> Result<Vec<HistoryCommit>, VersionHistoryError> {
+        self.commits()?
+            .into_iter()
+            .take(limit)
+            .map(|commit| Ok(history_commit(&commit)))
+            .collect()
+    }
+
+    pub fn memo_revisions(&self, id: &str) -> Result<Vec<MemoRevision>, VersionHistoryError> {
+        let path = memo_path(id);
+        let mut revisions = Vec::new();
+        for commit in self.commits()? {
+            let current = blob_id(&commit.tree()?, &path);
+            let previous = match commit.parent(0) {
+                Ok(parent) => blob_id(&parent.tree()?, &path),
+                Err(_) => None,
+            };
+            let kind = match (previous, current) {
+                (None, Some(_)) => MemoRevisionKind::Added,
+                (Some(before), Some(after)) if before != after => MemoRevisionKind::Modified,
+                (Some(_), None) => MemoRevisionKind::Removed,
+                _ => continue,
+            };
+            revisions.push(MemoRevision {
+                commit: history_commit(&commit),
+                kind,
+            });
+        }
+        Ok(revisions)
+    }
+
+    pub fn blame_memo(&self, id: &str) -> Result<Vec<BlameLine>, VersionHistoryError> {
+        let not_found = || VersionHistoryError::MemoNotFound { id: id.to_string() };
+        let path = memo_path(id);
+        let head = self.head_commit()?.ok_or_else(not_found)?;
+        let blob_id = blob_id(&head.tree()?, &path).ok_or_else(not_found)?;
+        let blob = self.repository.find_blob(blob_id)?;
+        let text = String::from_utf8_lossy(blob.content()).into_owned();
+
+        let mut options = BlameOptions::new();
+        options.newest_commit(head.id());
+        let blame = self
+            .repository
+            .blame_file(Path::new(&path), Some(&mut options))?;
+        Ok(text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                let hunk = blame.get_line(index + 1);
+                BlameLine {
+                    line: index + 1,
+                    text: line.to_string(),
+                    commit: hunk
+                        .as_ref()
+                        .map(|hunk| hunk.final_commit_id().to_string())
+                        .unwrap_or_default(),
+                    timestamp: hunk
+                        .as_ref()
+                        .map_or(0, |hunk| seconds(hunk.final_signature().when())),
+                }
+            })
+            .collect())
+    }
+
+    pub fn restore(
+        &self,
+        commit: &str,
+        current: &MemoStoreV2,
+    ) -> Result<MemoStoreV2, VersionHistoryError> {
+        let commit = self.repository.revparse_single(commit)?.peel_to_commit()?;
+        let tree = commit.tree()?;
+        let index: SnapshotIndex = serde_json::from_str(&self.read_text(&tree, INDEX_FILE)?)
+            .map_err(|source| VersionHistoryError::MalformedIndex {
+                commit: commit.id().to_string(),
+                source,
+            })?;
+        let entries = |indexed: Vec<IndexedMemo>| -> Result<Vec<MemoEntry>, VersionHistoryError> {
+            indexed
+                .into_iter()
+                .map(|memo| {
+                    Ok(MemoEntry {
+                        content: self.read_text(&tree, &memo_path(&memo.id))?,
+                        id: memo.id,
+                        timestamp: memo.timestamp,
+                    })
+                })
+                .collect()
+        };
+
+        Ok(MemoStoreV2 {
+            current_memo_id: index.current_memo_id,
+            current_content: self.read_text(&tree, CURRENT_FILE)?,
+            history: entries(index.history)?,
+            archive: entries(index.archive)?,
+            trash: entries(index.trash)?,
+            ..current.clone()
+        })
+    }
+
+    fn write_tree(&self, store: &MemoStoreV2) -> Result<Oid, VersionHistoryError> {
+        let mut memos = self.repository.treebuilder(None)?;
+        for entry in [&store.history, &store.archive, &store.trash]
+            .into_iter()
+            .flatten()
+        {
+            let blob = self.repository.blob(entry.content.as_bytes())?;
+            memos.insert(memo_file_name(&entry.id, MEMO_EXTENSION), blob, FILE_MODE)?;
+        }
+
+        let indexed = |entries: &[MemoEntry]| {
+            entries
+                .iter()
+                .map(|entry| IndexedMemo {
+                    id: entry.id.clone(),
+                    timestamp: entry.timestamp,
+                })
+                .collect()
+        };
+        let index = SnapshotIndex {
+            current_memo_id: store.current_memo_id.clone(),
+            history: indexed(&store.history),
+            archive: indexed(&store.archive),
+            trash: indexed(&store.trash),
+        };
+        let index = serde_json::to_vec_pretty(&index).map_err(VersionHistoryError::Serialize)?;
+
+        let mut root = self.repository.treebuilder(None)?;
+        root.insert(INDEX_FILE, self.repository.blob(&index)?, FILE_MODE)?;
+        root.insert(
+            CURRENT_FILE,
+            self.repository.blob(store.current_content.as_bytes())?,
+            FILE_MODE,
+        )?;
+        root.insert(MEMOS_DIR, memos.write()?, TREE_MODE)?;
+        Ok(root.write()?)
+    }
+
+    fn head_commit(&self) -> Result<Option<Commit<'_>>, VersionHistoryError> {
+        match self.repository.head() {
+            Ok(head) => Ok(Some(head.peel_to_commit()?)),
+            Err(error) if matches!(error.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => {
+                Ok(None)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn commits(&self) -> Result<Vec<Commit<'_>>, VersionHistoryError> {
+        let Some(head) = self.head_commit()? else {
+            return Ok(Vec::new());
+        };
+        let mut walk = self.repository.revwalk()?;
+        walk.push(head.id())?;
+        walk.map(|id| Ok(self.repository.find_commit(id?)?))
+            .collect()
+    }
+
+    fn read_text(&self, tree: &Tree<'_>, path: &str) -> Result<String, VersionHistoryError> {
+        let entry = tree.get_path(Path::new(path))?;
+        let blob = self.repository.find_blob(entry.id())?;
+        Ok(String::from_utf8_lossy(blob.content()).into_owned())
+    }
+}
+
+fn memo_path(id: &str) -> String {
+    format!("{MEMOS_DIR}/{}", memo_file_name(id, MEMO_EXTENSION))
+}
+
+fn blob_id(tree: &Tree<'_>, path: &str) -> Option<Oid> {
+    tree.get_path(Path::new(path)).ok().map(|entry| entry.id())
+}
+
+fn history_commit(commit: &Commit<'_>) -> HistoryCommit {
+    HistoryCommit {
+        id: commit.id().to_string(),
+        timestamp: seconds(commit.time()),
+        message: commit.message().unwrap_or_default().trim_end().to_string(),
+    }
+}
+
+fn seconds(time: Time) -> u64 {
+    u64::try_from(time.seconds()).unwrap_or_default()
+}
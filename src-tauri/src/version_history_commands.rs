@@ -0,0 +1,294 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use upmemo::memo_store::{AtomicFileCommit, StoreChange};
+use upmemo::version_history::{
+    read_version_history_config, write_version_history_config, BlameLine, HistoryCommit,
+    MemoRevision, VersionHistory, VersionHistoryConfig, VersionHistoryError, VERSION_HISTORY_DIR,
+};
+
+use crate::clock::get_timestamp;
+use crate::memo_store_state::{memo_store, AppMemoStore};
+use crate::note_commands::map_memo_store_error;
+
+pub const VERSION_HISTORY_DISCARDED_EVENT: &str = "version-history-discarded";
+const VERSION_HISTORY_CONFIG_FILE: &str = "version_history.json";
+const COALESCE_WINDOW: Duration = Duration::from_secs(2);
+const DEFAULT_LOG_LIMIT: usize = 200;
+
+pub struct VersionHistoryState {
+    history: Mutex<Option<VersionHistory>>,
+}
+
+impl VersionHistoryState {
+    fn history(&self) -> MutexGuard<'_, Option<VersionHistory>> {
+        self.history
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Serialize)]
+pub struct VersionHistoryStatus {
+    enabled: bool,
+    latest: Option<HistoryCommit>,
+}
+
+pub fn install_version_history(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let config = read_version_history_config(&config_path(app)?)?;
+    let history = if config.enabled {
+        Some(VersionHistory::open(&repository_path(app)?)?)
+    } else {
+        None
+    };
+    let (sender, changes) = mpsc::channel();
+    app.state::<AppMemoStore>().subscribe(move |batch| {
+        let _ = sender.send(batch.to_vec());
+    })?;
+
+    app.manage(VersionHistoryState {
+        history: Mutex::new(history),
+    });
+    if config.enabled {
+        record_snapshot(app, "開啟 UpMemo 時的記事");
+    }
+    spawn_history_recorder(app, changes);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_version_history_status(app_handle: AppHandle) -> Result<VersionHistoryStatus, String> {
+    let Some(state) = app_handle.try_state::<VersionHistoryState>() else {
+        return Ok(VersionHistoryStatus {
+            enabled: false,
+            latest: None,
+        });
+    };
+    let history = state.history();
+    let latest = match history.as_ref() {
+        Some(history) => history
+            .log(1)
+            .map_err(map_version_history_error)?
+            .into_iter()
+            .next(),
+        None => None,
+    };
+    Ok(VersionHistoryStatus {
+        enabled: history.is_some(),
+        latest,
+    })
+}
+
+#[tauri::command]
+pub fn enable_version_history(app_handle: AppHandle) -> Result<Option<HistoryCommit>, String> {
+    let repository = memo_store(&app_handle)?;
+    if repository.committer().key().is_some() {
+        return Err("記事已加密，無法啟用版本歷史".to_string());
+    }
+
+    let history =
+        VersionHistory::open(&repository_path(&app_handle)?).map_err(map_version_history_error)?;
+    write_version_history_config(
+        &config_path(&app_handle)?,
+        VersionHistoryConfig { enabled: true },
+        &AtomicFileCommit,
+    )
+    .map_err(map_version_history_error)?;
+
+    let store = repository.snapshot().map_err(map_memo_store_error)?;
+    let commit = history
+        .record(&store, "啟用版本歷史", get_timestamp())
+        .map_err(map_version_history_error)?;
+    *app_handle.state::<VersionHistoryState>().history() = Some(history);
+    Ok(commit)
+}
+
+#[tauri::command]
+pub fn disable_version_history(app_handle: AppHandle) -> Result<(), String> {
+    write_version_history_config(
+        &config_path(&app_handle)?,
+        VersionHistoryConfig { enabled: false },
+        &AtomicFileCommit,
+    )
+    .map_err(map_version_history_error)?;
+    if let Some(state) = app_handle.try_state::<VersionHistoryState>() {
+        *state.history() = None;
+    }
+    Ok(())
+}
+
+pub fn discard_version_history(app: &AppHandle, reason: &str) -> Result<Option<PathBuf>, String> {
+    let repository = repository_path(app)?;
+    disable_version_history(app.clone())?;
+    match fs::remove_dir_all(&repository) {
+        Ok(()) => {}
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(format!("無法刪除版本歷史: {error}")),
+    }
+
+    if let Err(error) = app.emit(VERSION_HISTORY_DISCARDED_EVENT, reason) {
+        eprintln!("無法通知版本歷史已刪除: {}", error);
+    }
+    Ok(Some(repository))
+}
+
+#[tauri::command]
+pub fn list_version_history(
+    app_handle: AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryCommit>, String> {
+    with_history(&app_handle, |history| {
+        history.log(limit.unwrap_or(DEFAULT_LOG_LIMIT))
+    })
+}
+
+#[tauri::command]
+pub fn get_memo_revisions(app_handle: AppHandle, id: String) -> Result<Vec<MemoRevision>, String> {
+    with_history(&app_handle, |history| history.memo_revisions(&id))
+}
+
+#[tauri::command]
+pub fn blame_memo(app_handle: AppHandle, id: String) -> Result<Vec<BlameLine>, String> {
+    with_history(&app_handle, |history| history.blame_memo(&id))
+}
+
+#[tauri::command]
+pub fn restore_version(
+    app_handle: AppHandle,
+    commit: String,
+) -> Result<Option<HistoryCommit>, String> {
+    let repository = memo_store(&app_handle)?;
+    let state = app_handle.state::<VersionHistoryState>();
+    let history = state.history();
+    let history = history
+        .as_ref()
+        .ok_or_else(|| "尚未啟用版本歷史".to_string())?;
+
+    let current = repository.snapshot().map_err(map_memo_store_error)?;
+    let restored = history
+        .restore(&commit, &current)
+        .map_err(map_version_history_error)?;
+    repository
+        .transact(|store| {
+            store.current_memo_id = restored.current_memo_id.clone();
+            store.current_content = restored.current_content.clone();
+            store.history = restored.history.clone();
+            store.archive = restored.archive.clone();
+            store.trash = restored.trash.clone();
+            Ok(())
+        })
+        .map_err(map_memo_store_error)?;
+
+    let store = repository.snapshot().map_err(map_memo_store_error)?;
+    let short = commit.get(..8).unwrap_or(&commit);
+    history
+        .record(&store, &format!("還原至 {short}"), get_timestamp())
+        .map_err(map_version_history_error)
+}
+
+fn with_history<T>(
+    app: &AppHandle,
+    read: impl FnOnce(&VersionHistory) -> Result<T, VersionHistoryError>,
+) -> Result<T, String> {
+    memo_store(app)?;
+    let state = app.state::<VersionHistoryState>();
+    let history = state.history();
+    let history = history
+        .as_ref()
+        .ok_or_else(|| "尚未啟用版本歷史".to_string())?;
+    read(history).map_err(map_version_history_error)
+}
+
+fn spawn_history_recorder(app: &AppHandle, changes: Receiver<Vec<StoreChange>>) {
+    let app_handle = app.clone();
+    thread::spawn(move || {
+        while let Ok(mut batch) = changes.recv() {
+            while let Ok(more) = changes.recv_timeout(COALESCE_WINDOW) {
+                batch.extend(more);
+            }
+            record_snapshot(&app_handle, &describe_changes(&batch));
+        }
+    });
+}
+
+fn record_snapshot(app: &AppHandle, message: &str) {
+    let Some(repository) = app.try_state::<AppMemoStore>() else {
+        return;
+    };
+    if repository.committer().key().is_some() {
+        return;
+    }
+    let state = app.state::<VersionHistoryState>();
+    let history = state.history();
+    let Some(history) = history.as_ref() else {
+        return;
+    };
+
+    let result = repository
+        .snapshot()
+        .map_err(map_memo_store_error)
+        .and_then(|store| {
+            history
+                .record(&store, message, get_timestamp())
+                .map_err(map_version_history_error)
+        });
+    if let Err(error) = result {
+        eprintln!("版本歷史記錄失敗: {}", error);
+    }
+}
+
+fn describe_changes(changes: &[StoreChange]) -> String {
+    let (mut added, mut updated, mut moved, mut deleted) = (0, 0, 0, 0);
+    for change in changes {
+        match change {
+            StoreChange::Added { .. } => added += 1,
+            StoreChange::Updated { .. } => updated += 1,
+            StoreChange::Moved { .. } => moved += 1,
+            StoreChange::Deleted { .. } => deleted += 1,
+        }
+    }
+
+    let parts: Vec<String> = [
+        ("新增", added),
+        ("更新", updated),
+        ("移動", moved),
+        ("刪除", deleted),
+    ]
+    .into_iter()
+    .filter(|(_, count)| *count > 0)
+    .map(|(label, count)| format!("{label} {count} 則"))
+    .collect();
+    if parts.is_empty() {
+        "更新記事".to_string()
+    } else {
+        parts.join("、")
+    }
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    data_dir(app).map(|directory| directory.join(VERSION_HISTORY_CONFIG_FILE))
+}
+
+fn repository_path(app: &AppHandle) -> Result<PathBuf, String> {
+    data_dir(app).map(|directory| directory.join(VERSION_HISTORY_DIR))
+}
+
+fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|error| format!("無法取得應用程式資料目錄: {error}"))
+}
+
+fn map_version_history_error(error: VersionHistoryError) -> String {
+    match error {
+        VersionHistoryError::MemoNotFound { .. } => "最新版本中找不到這則記事".to_string(),
+        error => format!("版本歷史操作失敗: {error}"),
+    }
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::time::{Duration, Instant};
+
+use upmemo::app_lock::{AppLock, AppLockError};
+
+#[test]
+fn pin_is_stored_as_a_salted_hash_and_locks_on_restart() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("app_lock.json");
+    let now = Instant::now();
+    let lock = AppLock::open(&path, now).expect("missing config must open unlocked");
+    let invalid = lock.set_pin(None, "12ab");
+    lock.set_pin(None, "4821").expect("PIN must be set");
+    let stored = fs::read_to_string(&path).expect("config must be written");
+
+    // When
+    let reopened = AppLock::open(&path, now).expect("config must reopen");
+    let refused = reopened.ensure_unlocked(now);
+    let wrong = reopened.unlock("0000", now);
+    reopened
+        .unlock("4821", now)
+        .expect("correct PIN must unlock");
+
+    // Then
+    assert!(matches!(invalid, Err(AppLockError::InvalidPin)));
+    assert!(!stored.contains("4821"));
+    assert!(stored.contains("$argon2id$"));
+    assert!(matches!(refused, Err(AppLockError::Locked)));
+    assert!(matches!(wrong, Err(AppLockError::WrongPin)));
+    assert!(reopened.ensure_unlocked(now).is_ok());
+}
+
+#[test]
+fn lock_engages_after_idle_time_or_explicit_hide() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let start = Instant::now();
+    let lock = AppLock::open(directory.path().join("app_lock.json"), start)
+        .expect("missing config must open unlocked");
+    let without_pin = lock.lock(start);
+    lock.set_pin(None, "4821").expect("PIN must be set");
+    lock.set_idle_timeout(60)
+        .expect("idle timeout must be saved");
+
+    // When
+    lock.touch(start + Duration::from_secs(50));
+    let still_active = lock.expire(start + Duration::from_secs(100));
+    let idle = lock.expire(start + Duration::from_secs(110));
+    lock.unlock("4821", start + Duration::from_secs(120))
+        .expect("correct PIN must unlock");
+    let hidden = lock.lock(start + Duration::from_secs(120));
+    let hidden_again = lock.lock(start + Duration::from_secs(120));
+
+    // Then
+    assert!(!without_pin);
+    assert!(!still_active);
+    assert!(idle);
+    assert!(hidden);
+    assert!(!hidden_again);
+    assert!(lock.status().locked);
+}
+
+#[test]
+fn repeated_wrong_pins_back_off_and_the_counter_survives_a_restart() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("app_lock.json");
+    let start = Instant::now();
+    let lock = AppLock::open(&path, start).expect("missing config must open unlocked");
+    lock.set_pin(None, "4821").expect("PIN must be set");
+    let reopened = AppLock::open(&path, start).expect("config must reopen");
+    let free = (0..3)
+        .map(|_| reopened.unlock("0000", start))
+        .collect::<Vec<_>>();
+
+    // When
+    let throttled = reopened.unlock("4821", start + Duration::from_secs(10));
+    let restarted = AppLock::open(&path, start + Duration::from_secs(40))
+        .expect("config must reopen after a restart");
+    let throttled_after_restart = restarted.unlock("4821", start + Duration::from_secs(40));
+    restarted
+        .unlock("4821", start + Duration::from_secs(71))
+        .expect("correct PIN must unlock once the backoff has passed");
+    let stored = fs::read_to_string(&path).expect("config must be written");
+
+    // Then
+    assert!(free
+        .iter()
+        .all(|attempt| matches!(attempt, Err(AppLockError::WrongPin))));
+    assert!(matches!(
+        throttled,
+        Err(AppLockError::TooManyAttempts {
+            retry_after_secs: 20
+        })
+    ));
+    assert!(matches!(
+        throttled_after_restart,
+        Err(AppLockError::TooManyAttempts {
+            retry_after_secs: 30
+        })
+    ));
+    assert!(restarted
+        .ensure_unlocked(start + Duration::from_secs(71))
+        .is_ok());
+    assert!(stored.contains("\"failed_attempts\": 0"));
+}
+
+#[test]
+fn autosave_is_accepted_briefly_after_the_lock_engages() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let start = Instant::now();
+    let lock = AppLock::open(directory.path().join("app_lock.json"), start)
+        .expect("missing config must open unlocked");
+    lock.set_pin(None, "4821").expect("PIN must be set");
+    lock.unlock("4821", start).expect("correct PIN must unlock");
+    lock.lock(start + Duration::from_secs(10));
+
+    // When
+    let final_save = lock.ensure_writable(start + Duration::from_secs(12));
+    let read = lock.ensure_unlocked(start + Duration::from_secs(12));
+    let late_save = lock.ensure_writable(start + Duration::from_secs(20));
+
+    // Then
+    assert!(final_save.is_ok());
+    assert!(matches!(read, Err(AppLockError::Locked)));
+    assert!(matches!(late_save, Err(AppLockError::Locked)));
+}
@@ -0,0 +1,221 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use upmemo::bundle::{
+    apply_bundle_store, back_up_before_import, read_bundle, restore_bundle_files, write_bundle,
+    BundleError, BundleImportMode, BundleMergeSummary,
+};
+use upmemo::memo_store::{AtomicFileCommit, MemoStoreV2};
+use upmemo::models::MemoEntry;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+#[test]
+fn bundle_round_trips_store_settings_and_attachments_and_rejects_tampering() {
+    // Given
+    let source = tempfile::tempdir().expect("source directory must be created");
+    let target = tempfile::tempdir().expect("target directory must be created");
+    let attachment = format!("attachments/{}.png", "b".repeat(64));
+    fs::write(
+        source.path().join("font_config.json"),
+        b"{\"chinese_font\":\"A\"}",
+    )
+    .expect("font config must be written");
+    fs::write(source.path().join("settings.json"), b"{\"opacity\":80}")
+        .expect("settings must be written");
+    fs::create_dir_all(source.path().join("attachments")).expect("attachments must be created");
+    fs::write(source.path().join(&attachment), b"png-bytes").expect("attachment must be written");
+    let mut store = MemoStoreV2::default();
+    store.history.push(entry("a", "groceries", 10));
+    let bundle_path = source.path().join("backup.upmemo");
+    let tampered_path = source.path().join("tampered.upmemo");
+
+    // When
+    let manifest =
+        write_bundle(&bundle_path, &store, source.path()).expect("bundle must be written");
+    let bundle = read_bundle(&bundle_path).expect("bundle must verify");
+    let mut restored = MemoStoreV2::default();
+    apply_bundle_store(&mut restored, bundle.store, BundleImportMode::Replace);
+    let files = restore_bundle_files(target.path(), &bundle.files, BundleImportMode::Replace)
+        .expect("files must be restored");
+    tamper(&bundle_path, &tampered_path);
+    let tampered = read_bundle(&tampered_path);
+
+    // Then
+    assert_eq!(manifest.files.len(), 4);
+    assert_eq!(restored, store);
+    assert_eq!(files.len(), 3);
+    assert_eq!(
+        fs::read(target.path().join(&attachment)).expect("attachment must be restored"),
+        b"png-bytes"
+    );
+    assert_eq!(
+        fs::read(target.path().join("settings.json")).expect("settings must be restored"),
+        b"{\"opacity\":80}"
+    );
+    assert!(
+        matches!(tampered, Err(BundleError::ChecksumMismatch(ref name)) if name == "memo_store.json"),
+        "tampered bundle must fail verification: {tampered:?}"
+    );
+}
+
+#[test]
+fn merge_keeps_newer_entries_by_id_after_backing_up_the_store() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let mut store = MemoStoreV2 {
+        history: vec![entry("b", "local b", 50), entry("a", "local a", 10)],
+        ..MemoStoreV2::default()
+    };
+    let incoming = MemoStoreV2 {
+        history: vec![entry("c", "remote c", 30), entry("b", "remote b", 40)],
+        archive: vec![entry("a", "remote a", 20)],
+        ..MemoStoreV2::default()
+    };
+
+    // When
+    let backup = back_up_before_import(directory.path(), &store, &AtomicFileCommit)
+        .expect("backup must be written");
+    let summary = apply_bundle_store(&mut store, incoming, BundleImportMode::Merge);
+
+    // Then
+    assert_eq!(
+        summary,
+        BundleMergeSummary {
+            added: 1,
+            updated: 1,
+            unchanged: 1,
+        }
+    );
+    let history: Vec<&str> = store
+        .history
+        .iter()
+        .map(|entry| entry.id.as_str())
+        .collect();
+    assert_eq!(history, vec!["b", "c"]);
+    assert_eq!(store.history[0].content, "local b");
+    assert_eq!(store.archive, vec![entry("a", "remote a", 20)]);
+    let backed_up =
+        fs::read_to_string(backup.join("history.json")).expect("backup history must exist");
+    assert!(backed_up.contains("local a"));
+    assert!(backup.join("manifest.json").is_file());
+}
+
+#[test]
+fn entries_that_are_unlisted_or_larger_than_listed_are_rejected() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let mut store = MemoStoreV2::default();
+    store.history.push(entry("a", "groceries", 10));
+    let bundle_path = directory.path().join("backup.upmemo");
+    write_bundle(&bundle_path, &store, directory.path()).expect("bundle must be written");
+    let unlisted_path = directory.path().join("unlisted.upmemo");
+    let padded_path = directory.path().join("padded.upmemo");
+    let unlisted = format!("attachments/{}.png", "c".repeat(64));
+    rewrite(&bundle_path, &unlisted_path, |name, bytes| {
+        let mut entries = vec![(name.to_string(), bytes)];
+        if name == "memo_store.json" {
+            entries.push((unlisted.clone(), vec![0; 1024 * 1024]));
+        }
+        entries
+    });
+    rewrite(&bundle_path, &padded_path, |name, mut bytes| {
+        if name == "memo_store.json" {
+            bytes.extend(vec![b' '; 1024 * 1024]);
+        }
+        vec![(name.to_string(), bytes)]
+    });
+
+    // When
+    let unlisted_result = read_bundle(&unlisted_path);
+    let padded_result = read_bundle(&padded_path);
+
+    // Then
+    assert!(
+        matches!(unlisted_result, Err(BundleError::UnexpectedFile(ref name)) if *name == unlisted),
+        "unlisted entry must be rejected: {unlisted_result:?}"
+    );
+    assert!(
+        matches!(padded_result, Err(BundleError::ChecksumMismatch(ref name)) if name == "memo_store.json"),
+        "oversized entry must be rejected: {padded_result:?}"
+    );
+}
+
+#[test]
+fn backup_before_import_includes_attachments() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let attachment = format!("attachments/{}.png", "d".repeat(64));
+    fs::create_dir_all(directory.path().join("attachments")).expect("attachments must be created");
+    fs::write(directory.path().join(&attachment), b"png-bytes")
+        .expect("attachment must be written");
+
+    // When
+    let backup =
+        back_up_before_import(directory.path(), &MemoStoreV2::default(), &AtomicFileCommit)
+            .expect("backup must be written");
+
+    // Then
+    assert_eq!(
+        fs::read(backup.join(&attachment)).expect("attachment must be backed up"),
+        b"png-bytes"
+    );
+    let manifest =
+        fs::read_to_string(backup.join("manifest.json")).expect("backup manifest must exist");
+    assert!(manifest.contains(&attachment));
+}
+
+fn tamper(source: &Path, target: &Path) {
+    let mut archive =
+        ZipArchive::new(fs::File::open(source).expect("bundle must open")).expect("zip must open");
+    let mut writer = ZipWriter::new(fs::File::create(target).expect("tampered file must open"));
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).expect("entry must be readable");
+        let name = entry.name().to_string();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).expect("entry must be read");
+        if name == "memo_store.json" {
+            bytes = String::from_utf8(bytes)
+                .expect("store must be UTF-8")
+                .replace("groceries", "groceriez")
+                .into_bytes();
+        }
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .expect("entry must start");
+        writer.write_all(&bytes).expect("entry must be written");
+    }
+    writer.finish().expect("tampered bundle must finish");
+}
+
+fn entry(id: &str, content: &str, timestamp: u64) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp,
+    }
+}
+
+fn rewrite(
+    source: &Path,
+    target: &Path,
+    mut change: impl FnMut(&str, Vec<u8>) -> Vec<(String, Vec<u8>)>,
+) {
+    let mut archive =
+        ZipArchive::new(fs::File::open(source).expect("bundle must open")).expect("zip must open");
+    let mut writer = ZipWriter::new(fs::File::create(target).expect("rewritten file must open"));
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).expect("entry must be readable");
+        let name = entry.name().to_string();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).expect("entry must be read");
+        for (name, bytes) in change(&name, bytes) {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .expect("entry must start");
+            writer.write_all(&bytes).expect("entry must be written");
+        }
+    }
+    writer.finish().expect("rewritten bundle must finish");
+}
@@ -0,0 +1,75 @@
+use upmemo::memo_store::MemoStoreRepository;
+use upmemo::models::MemoEntry;
+
+#[test]
+fn current_memo_id_survives_repository_reopen() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    MemoStoreRepository::open(&path)
+        .expect("fresh store must open")
+        .transact(|store| {
+            store.current_memo_id = Some("memo-open".to_string());
+            store.current_content = "open memo".to_string();
+            Ok(())
+        })
+        .expect("current memo must commit");
+
+    // When
+    let reopened = MemoStoreRepository::open(&path).expect("committed store must reopen");
+
+    // Then
+    let store = reopened.snapshot().expect("snapshot must succeed");
+    assert_eq!(store.current_memo_id.as_deref(), Some("memo-open"));
+    assert_eq!(store.current_content, "open memo");
+}
+
+#[test]
+fn current_memo_id_is_owned_by_the_store_not_app_state() {
+    // Given
+    let models_source = include_str!("../src/models.rs");
+    let main_source = include_str!("../src/main.rs");
+    let note_commands_source = include_str!("../src/note_commands.rs");
+
+    // When
+    let app_state_tracks_memo_id = models_source.contains("current_memo_id");
+
+    // Then
+    assert!(!app_state_tracks_memo_id);
+    assert!(!main_source.contains("current_memo_id: Mutex"));
+    assert!(note_commands_source.contains(".map(|store| store.current_memo_id)"));
+}
+
+#[test]
+fn saving_after_a_restart_updates_the_memo_that_was_open() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    MemoStoreRepository::open(&path)
+        .expect("fresh store must open")
+        .transact(|store| {
+            store.history.push(MemoEntry {
+                id: "memo-open".to_string(),
+                content: "groceries".to_string(),
+                timestamp: 100,
+            });
+            store.current_memo_id = Some("memo-open".to_string());
+            store.current_content = "groceries".to_string();
+            Ok(())
+        })
+        .expect("loaded memo must commit");
+
+    // When
+    let reopened = MemoStoreRepository::open(&path).expect("committed store must reopen");
+    let saved_id = reopened
+        .transact(|store| Ok(store.save_current("groceries and eggs", 200)))
+        .expect("save after restart must commit");
+
+    // Then
+    let store = reopened.snapshot().expect("snapshot must succeed");
+    assert_eq!(saved_id, "memo-open");
+    assert_eq!(store.history.len(), 1);
+    assert_eq!(store.history[0].id, "memo-open");
+    assert_eq!(store.history[0].content, "groceries and eggs");
+    assert_eq!(store.history[0].timestamp, 200);
+}
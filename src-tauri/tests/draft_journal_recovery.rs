@@ -0,0 +1,172 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use upmemo::draft_journal::DraftJournal;
+use upmemo::edit_session::EditSessionToken;
+use upmemo::memo_store::MemoStoreV2;
+use upmemo::models::MemoEntry;
+
+#[test]
+fn recovery_returns_latest_record_per_session() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let journal = DraftJournal::new(directory.path().join("drafts"));
+    let token = EditSessionToken::new();
+    for content in ["h", "he", "hello"] {
+        journal
+            .append(&token, Some("memo-1"), content)
+            .expect("draft must append");
+    }
+
+    // When
+    let drafts = journal.recover().expect("journal must recover");
+
+    // Then
+    assert_eq!(drafts.len(), 1);
+    assert_eq!(drafts[0].token, token);
+    assert_eq!(drafts[0].memo_id.as_deref(), Some("memo-1"));
+    assert_eq!(drafts[0].content, "hello");
+}
+
+#[test]
+fn torn_trailing_record_falls_back_to_previous_complete_record() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let drafts_directory = directory.path().join("drafts");
+    let journal = DraftJournal::new(&drafts_directory);
+    let token = EditSessionToken::new();
+    journal
+        .append(&token, None, "complete")
+        .expect("draft must append");
+    let journal_path = drafts_directory.join(format!("{}.jsonl", token.as_uuid()));
+    OpenOptions::new()
+        .append(true)
+        .open(&journal_path)
+        .and_then(|mut file| file.write_all(br#"{"memo_id":null,"written_at_mil"#))
+        .expect("torn record must be appended");
+
+    // When
+    let drafts = journal.recover().expect("journal must recover");
+
+    // Then
+    assert_eq!(drafts.len(), 1);
+    assert_eq!(drafts[0].content, "complete");
+}
+
+#[test]
+fn draft_is_newer_only_when_it_differs_from_committed_content() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let journal = DraftJournal::new(directory.path().join("drafts"));
+    let committed_token = EditSessionToken::new();
+    let unsaved_token = EditSessionToken::new();
+    journal
+        .append(&committed_token, Some("memo-1"), "saved text")
+        .expect("draft must append");
+    journal
+        .append(&unsaved_token, Some("memo-2"), "typed before crash")
+        .expect("draft must append");
+    let store = MemoStoreV2 {
+        current_memo_id: Some("memo-1".to_string()),
+        current_content: "saved text".to_string(),
+        history: vec![MemoEntry {
+            id: "memo-2".to_string(),
+            content: "older text".to_string(),
+            timestamp: 0,
+        }],
+        ..MemoStoreV2::default()
+    };
+
+    // When
+    let drafts = journal.recover().expect("journal must recover");
+
+    // Then
+    let newer = drafts
+        .iter()
+        .filter(|draft| draft.is_newer_than(&store))
+        .map(|draft| draft.content.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(newer, ["typed before crash"]);
+}
+
+#[test]
+fn draft_without_memo_id_is_compared_with_the_committed_content() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let journal = DraftJournal::new(directory.path().join("drafts"));
+    for content in ["still current", "already saved", "typed before crash"] {
+        journal
+            .append(&EditSessionToken::new(), None, content)
+            .expect("draft must append");
+    }
+    let store = MemoStoreV2 {
+        current_content: "still current".to_string(),
+        history: vec![MemoEntry {
+            id: "memo-1".to_string(),
+            content: "already saved".to_string(),
+            timestamp: 0,
+        }],
+        ..MemoStoreV2::default()
+    };
+
+    // When
+    let drafts = journal.recover().expect("journal must recover");
+
+    // Then
+    let newer = drafts
+        .iter()
+        .filter(|draft| draft.is_newer_than(&store))
+        .map(|draft| draft.content.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(newer, ["typed before crash"]);
+}
+
+#[test]
+fn compact_keeps_only_latest_record_and_discard_removes_journal() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let drafts_directory = directory.path().join("drafts");
+    let journal = DraftJournal::new(&drafts_directory);
+    let token = EditSessionToken::new();
+    for content in ["a", "ab", "abc"] {
+        journal
+            .append(&token, None, content)
+            .expect("draft must append");
+    }
+    let journal_path = drafts_directory.join(format!("{}.jsonl", token.as_uuid()));
+
+    // When
+    journal.compact(&token).expect("journal must compact");
+    let compacted = fs::read_to_string(&journal_path).expect("journal must remain readable");
+    journal.discard(&token).expect("journal must be discarded");
+
+    // Then
+    assert_eq!(compacted.lines().count(), 1);
+    assert!(compacted.contains("\"abc\""));
+    assert!(!journal_path.exists());
+    assert!(journal.recover().expect("journal must recover").is_empty());
+}
+
+#[test]
+fn purge_all_removes_every_journal_including_torn_ones() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let drafts_directory = directory.path().join("drafts");
+    let journal = DraftJournal::new(&drafts_directory);
+    journal
+        .append(&EditSessionToken::new(), Some("memo-1"), "secret plan")
+        .expect("draft must append");
+    let torn = drafts_directory.join(format!("{}.jsonl", EditSessionToken::new().as_uuid()));
+    fs::write(&torn, b"{\"memo_id\":null,\"cont").expect("torn journal must be written");
+    let unrelated = drafts_directory.join("notes.txt");
+    fs::write(&unrelated, b"keep").expect("unrelated file must be written");
+
+    // When
+    let purged = journal.purge_all().expect("journals must be purged");
+
+    // Then
+    assert_eq!(purged.len(), 2);
+    assert!(!torn.exists());
+    assert!(unrelated.exists());
+    assert!(journal.recover().expect("journal must recover").is_empty());
+}
@@ -0,0 +1,157 @@
+use std::fs;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use md5::{Digest, Md5};
+use sha2::Sha256;
+use upmemo::enex_import::prepare_enex_import;
+use upmemo::memo_import::{apply_import, ImportFailureKind};
+use upmemo::memo_store::{AtomicFileCommit, MemoStoreV2};
+
+const IMAGE: &[u8] = b"\x89PNG\r\n\x1a\nmap";
+const TICKET: &[u8] = b"%PDF-1.4 ticket";
+
+fn export(notes: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-export SYSTEM "http://xml.evernote.com/pub/evernote-export4.dtd">
+<en-export export-date="20240101T000000Z" application="Evernote" version="10.0">
+{notes}
+</en-export>"#
+    )
+}
+
+#[test]
+fn notes_convert_enml_resources_notebook_tags_and_dates() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let attachments = directory.path().join("attachments");
+    let image_hash = format!("{:x}", Md5::digest(IMAGE));
+    let notes = format!(
+        r#"<note><title>Trip plan</title><created>20200913T122640Z</created><updated>20200914T122640Z</updated><tag>travel</tag>
+<content><![CDATA[<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd">
+<en-note><div>Pack <b>passport</b> &amp; <i>maps</i></div><div><br/></div>
+<ul><li><div><en-todo checked="true"/>tent</div></li><li><div><en-todo/>stove</div></li></ul>
+<div><a href="https://example.com/route">route</a></div><en-media hash="{image_hash}" type="image/png"/><div>after</div></en-note>]]></content>
+<resource><data encoding="base64">
+{}
+</data><mime>image/png</mime><resource-attributes><file-name>map.png</file-name></resource-attributes></resource>
+<resource><data encoding="base64">{}</data><mime>application/pdf</mime><resource-attributes><file-name>ticket.pdf</file-name></resource-attributes></resource>
+</note>
+<note><title>Second</title><created>20190101T000000Z</created><content><![CDATA[<en-note><div>hello</div></en-note>]]></content></note>"#,
+        STANDARD.encode(IMAGE),
+        STANDARD.encode(TICKET)
+    );
+    let source = directory.path().join("Travel Notebook.enex");
+    fs::write(&source, export(&notes)).expect("enex fixture must be written");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(
+        &mut store,
+        prepare_enex_import(&source, &attachments, &AtomicFileCommit),
+    );
+
+    // Then
+    assert!(report.failures.is_empty(), "{:?}", report.failures);
+    let ticket = format!("{:x}.pdf", Sha256::digest(TICKET));
+    let map = format!("{:x}.png", Sha256::digest(IMAGE));
+    assert_eq!(
+        fs::read(attachments.join(&ticket)).expect("pdf must be extracted"),
+        TICKET
+    );
+    assert_eq!(
+        fs::read(attachments.join(&map)).expect("image must be extracted"),
+        IMAGE
+    );
+    assert_eq!(store.history.len(), 2);
+    assert_eq!(store.history[0].timestamp, 1_600_086_400);
+    assert_eq!(
+        store.history[0].content,
+        format!(
+            "Trip plan\n\nPack **passport** & *maps*\n\n- [x] tent\n- [ ] stove\nroute (https://example.com/route)\n<img data-upmemo-image=\"{map}\">\nafter\n\n📎 ticket.pdf (attachments/{ticket})\n\n#Travel_Notebook #travel"
+        )
+    );
+    assert_eq!(
+        store.history[1].content,
+        "Second\n\nhello\n\n#Travel_Notebook"
+    );
+    assert_eq!(store.history[1].timestamp, 1_546_300_800);
+    assert!(store.history[1].id.starts_with("enex-"));
+}
+
+#[test]
+fn malformed_export_keeps_notes_streamed_before_the_error() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let notes = r#"<note><title>Kept</title><content><![CDATA[<en-note>kept body</en-note>]]></content>
+<resource><data encoding="base64">***not base64***</data><mime>image/png</mime></resource></note>
+<note><title>Lost</title><content>"#;
+    fs::write(
+        directory.path().join("Broken.enex"),
+        export(notes).replace("</en-export>", "</note></en-export>"),
+    )
+    .expect("enex fixture must be written");
+    fs::write(directory.path().join("ignored.txt"), "not enex").expect("text must be written");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(
+        &mut store,
+        prepare_enex_import(
+            directory.path(),
+            &directory.path().join("attachments"),
+            &AtomicFileCommit,
+        ),
+    );
+
+    // Then
+    let kinds: Vec<ImportFailureKind> =
+        report.failures.iter().map(|failure| failure.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ImportFailureKind::InvalidResource,
+            ImportFailureKind::InvalidXml
+        ]
+    );
+    assert_eq!(store.history.len(), 1);
+    assert_eq!(store.history[0].content, "Kept\n\nkept body\n\n#Broken");
+}
+
+#[test]
+fn untitled_notes_without_dates_in_one_export_stay_distinct() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let notes = r#"<note><content><![CDATA[<en-note>first body</en-note>]]></content></note>
+<note><content><![CDATA[<en-note>second body</en-note>]]></content></note>"#;
+    let source = directory.path().join("Inbox.enex");
+    fs::write(&source, export(notes)).expect("enex fixture must be written");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(
+        &mut store,
+        prepare_enex_import(
+            &source,
+            &directory.path().join("attachments"),
+            &AtomicFileCommit,
+        ),
+    );
+
+    // Then
+    assert!(report.failures.is_empty(), "{:?}", report.failures);
+    assert_eq!(store.history.len(), 2);
+    assert_ne!(store.history[0].id, store.history[1].id);
+    let mut contents: Vec<&str> = store
+        .history
+        .iter()
+        .map(|entry| entry.content.as_str())
+        .collect();
+    contents.sort();
+    assert_eq!(
+        contents,
+        vec!["first body\n\n#Inbox", "second body\n\n#Inbox"]
+    );
+}
@@ -0,0 +1,98 @@
+use std::fs;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use upmemo::keep_import::prepare_keep_import;
+use upmemo::memo_import::{apply_import, ImportFailureKind};
+use upmemo::memo_store::{MemoCollection, MemoStoreV2};
+
+#[test]
+fn takeout_json_maps_checklists_labels_attachments_and_flags() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    fs::write(directory.path().join("photo.png"), b"png-bytes").expect("image must be written");
+    fs::write(
+        directory.path().join("Groceries.json"),
+        r#"{"color":"DEFAULT","isTrashed":false,"isPinned":true,"isArchived":false,
+            "title":"Groceries","userEditedTimestampUsec":1600000000123456,
+            "listContent":[{"text":"milk","isChecked":true},{"text":"eggs","isChecked":false}],
+            "labels":[{"name":"Home"},{"name":"Weekly shop"}],
+            "attachments":[{"filePath":"photo.png","mimetype":"image/png"}]}"#,
+    )
+    .expect("json fixture must be written");
+    fs::write(
+        directory.path().join("Groceries.html"),
+        "<html><body><div class=\"title\">ignored duplicate</div></body></html>",
+    )
+    .expect("html twin must be written");
+    fs::write(
+        directory.path().join("Old.json"),
+        r#"{"isArchived":true,"textContent":"archived text","createdTimestampUsec":1500000000000000}"#,
+    )
+    .expect("archived fixture must be written");
+    fs::write(
+        directory.path().join("Gone.json"),
+        r#"{"isTrashed":true,"isArchived":true,"textContent":"trashed text","userEditedTimestampUsec":1400000000000000}"#,
+    )
+    .expect("trashed fixture must be written");
+    fs::write(directory.path().join("Broken.json"), "{not json").expect("broken must be written");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(&mut store, prepare_keep_import(directory.path()));
+
+    // Then
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].kind, ImportFailureKind::InvalidJson);
+    assert_eq!(report.imported.len(), 3);
+    assert_eq!(store.history.len(), 1);
+    assert_eq!(store.history[0].id, "Groceries");
+    assert_eq!(store.history[0].timestamp, 1_600_000_000);
+    assert_eq!(
+        store.history[0].content,
+        format!(
+            "Groceries\n\n- [x] milk\n- [ ] eggs\n\n<img src=\"data:image/png;base64,{}\">\n\n#置頂 #Home #Weekly_shop",
+            STANDARD.encode(b"png-bytes")
+        )
+    );
+    assert_eq!(store.archive.len(), 1);
+    assert_eq!(store.archive[0].content, "archived text");
+    assert_eq!(store.archive[0].timestamp, 1_500_000_000);
+    assert_eq!(store.trash.len(), 1);
+    assert_eq!(store.trash[0].content, "trashed text");
+    assert_eq!(report.imported[0].collection, MemoCollection::Trash);
+}
+
+#[test]
+fn takeout_html_without_json_is_parsed_for_title_body_list_and_labels() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    fs::write(
+        directory.path().join("Trip.html"),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html><head><meta charset="UTF-8"/><title>Trip</title></head><body>
+<div class="note DEFAULT"><div class="heading"><div class="meta-icons"><span class="archived" title="Note archived"></span></div>
+Jan 1, 2020, 10:00:00 AM
+</div>
+<div class="title">Trip &amp; tent</div>
+<div class="content">Bring&nbsp;maps<br/>and snacks</div>
+<div class="content"><div class="listitem checked"><div class="bullet">&#9745;</div><div class="text">tent</div></div><div class="listitem"><div class="bullet">&#9744;</div><div class="text">stove</div></div></div>
+<div class="chips"><span class="chip label"><span class="label-name">Summer</span></span></div>
+</div></body></html>"#,
+    )
+    .expect("html fixture must be written");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(&mut store, prepare_keep_import(directory.path()));
+
+    // Then
+    assert!(report.failures.is_empty(), "{:?}", report.failures);
+    assert_eq!(store.archive.len(), 1);
+    assert_eq!(store.archive[0].id, "Trip");
+    assert_eq!(
+        store.archive[0].content,
+        "Trip & tent\n\nBring maps\nand snacks\n- [x] tent\n- [ ] stove\n\n#Summer"
+    );
+}
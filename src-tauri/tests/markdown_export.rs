@@ -0,0 +1,93 @@
+use std::fs;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use upmemo::markdown_export::{export_markdown_folder, memo_to_markdown, MarkdownMemo, MemoImage};
+use upmemo::memo_store::{AtomicFileCommit, MemoCollection};
+use upmemo::models::MemoEntry;
+
+const PIXEL_PNG: &[u8] = b"\x89PNG\r\n\x1a\nnot-really-a-png";
+
+#[test]
+fn editor_content_converts_links_breaks_and_images_to_markdown() {
+    // Given
+    let content = format!(
+        "Trip *plan*\nsee https://example.com/a_b and <a href=\"https://maps.example\">map</a>\n\n<div class=\"image-container\" contenteditable=\"false\"><img src=\"data:image/png;base64,{}\" class=\"inserted-image resizable\"><div class=\"resize-handle\"></div></div>\n# not a heading",
+        STANDARD.encode(PIXEL_PNG)
+    );
+
+    // When
+    let converted = memo_to_markdown(&content);
+
+    // Then
+    let [MemoImage::Embedded { name, bytes }] = converted.images.as_slice() else {
+        panic!("exactly one embedded image must be extracted: {converted:?}");
+    };
+    assert_eq!(bytes, PIXEL_PNG);
+    assert!(name.ends_with(".png"));
+    assert_eq!(
+        converted.markdown,
+        format!(
+            "Trip \\*plan\\*\\\nsee <https://example.com/a_b> and [map](https://maps.example)\n\n![](assets/{name})\\\n\\# not a heading"
+        )
+    );
+}
+
+#[test]
+fn export_writes_front_matter_unique_filenames_and_skips_private_memos() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let destination = directory.path().join("export");
+    let attachments = directory.path().join("attachments");
+    let reference = format!("{}.png", "a".repeat(64));
+    fs::create_dir_all(&attachments).expect("attachments directory must be created");
+    fs::write(attachments.join(&reference), PIXEL_PNG).expect("attachment must be written");
+    let first = entry("one", "Groceries\nmilk");
+    let second = entry(
+        "two",
+        &format!("Groceries\n<img data-upmemo-image=\"{reference}\">"),
+    );
+    let private = entry("three", "upmemo-private:v1:c2VhbGVk");
+    let memos = [
+        MarkdownMemo {
+            entry: &first,
+            collection: MemoCollection::History,
+        },
+        MarkdownMemo {
+            entry: &second,
+            collection: MemoCollection::Archive,
+        },
+        MarkdownMemo {
+            entry: &private,
+            collection: MemoCollection::History,
+        },
+    ];
+
+    // When
+    let report = export_markdown_folder(&destination, &memos, &attachments, &AtomicFileCommit)
+        .expect("export must succeed");
+    let again = export_markdown_folder(&destination, &memos[..1], &attachments, &AtomicFileCommit)
+        .expect("re-export must succeed");
+
+    // Then
+    assert_eq!(report.skipped_private, vec!["three".to_string()]);
+    assert_eq!(report.memos[0].path, destination.join("Groceries.md"));
+    assert_eq!(report.memos[1].path, destination.join("Groceries (2).md"));
+    assert_eq!(again.memos[0].path, destination.join("Groceries.md"));
+    let archived =
+        fs::read_to_string(&report.memos[1].path).expect("exported memo must be readable");
+    assert!(archived.starts_with("---\nid: two\ntimestamp: 1700000000\ncollection: archive\n---\n"));
+    assert!(archived.contains(&format!("![](assets/{reference})")));
+    assert_eq!(
+        fs::read(destination.join("assets").join(&reference)).expect("image must be exported"),
+        PIXEL_PNG
+    );
+}
+
+fn entry(id: &str, content: &str) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp: 1_700_000_000,
+    }
+}
@@ -0,0 +1,194 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use upmemo::markdown_export::{export_markdown_folder, MarkdownMemo};
+use upmemo::markdown_import::prepare_markdown_import;
+use upmemo::memo_import::{
+    apply_import, ImportFailureKind, ImportedFile, ImportedMemo, PreparedImport,
+};
+use upmemo::memo_store::{AtomicFileCommit, MemoCollection, MemoStoreV2, HISTORY_LIMIT};
+use upmemo::models::MemoEntry;
+
+#[test]
+fn exported_folder_imports_back_with_ids_timestamps_and_images() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let image = STANDARD.encode(b"\x89PNG\r\n\x1a\nfixture");
+    let original = MemoEntry {
+        id: "memo-1".to_string(),
+        content: format!(
+            "Trip *plan*\n1. pack\nsee https://example.com/a_b\n<img src=\"data:image/png;base64,{image}\">"
+        ),
+        timestamp: 1_700_000_000,
+    };
+    export_markdown_folder(
+        directory.path(),
+        &[MarkdownMemo {
+            entry: &original,
+            collection: MemoCollection::Archive,
+        }],
+        &directory.path().join("attachments"),
+        &AtomicFileCommit,
+    )
+    .expect("export must succeed");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(&mut store, prepare_markdown_import(directory.path()));
+
+    // Then
+    assert!(report.failures.is_empty(), "{:?}", report.failures);
+    assert_eq!(store.archive.len(), 1);
+    assert_eq!(store.archive[0].id, "memo-1");
+    assert_eq!(store.archive[0].timestamp, 1_700_000_000);
+    assert_eq!(
+        store.archive[0].content,
+        format!(
+            "Trip *plan*\n1. pack\nsee https://example.com/a_b\n<img src=\"data:image/png;base64,{image}\">"
+        )
+    );
+}
+
+#[test]
+fn plain_files_use_mtimes_and_failures_are_reported_per_file() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let notes = directory.path().join("notes");
+    fs::create_dir_all(&notes).expect("nested directory must be created");
+    let text = notes.join("shopping.txt");
+    fs::write(&text, "milk\neggs\n").expect("text fixture must be written");
+    let modified = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    File::options()
+        .write(true)
+        .open(&text)
+        .and_then(|file| file.set_modified(modified))
+        .expect("mtime must be set");
+    fs::write(
+        directory.path().join("trip.md"),
+        "# Trip\n\n![map](missing.png)\nSee [docs](https://example.com)",
+    )
+    .expect("markdown fixture must be written");
+    fs::write(directory.path().join("broken.md"), b"\xff\xfe\xfd")
+        .expect("binary fixture must be written");
+    fs::write(directory.path().join("photo.png"), b"ignored").expect("image must be written");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(&mut store, prepare_markdown_import(directory.path()));
+
+    // Then
+    let kinds: Vec<ImportFailureKind> =
+        report.failures.iter().map(|failure| failure.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![ImportFailureKind::NotUtf8, ImportFailureKind::MissingImage]
+    );
+    assert_eq!(report.failures[0].path, directory.path().join("broken.md"));
+    assert_eq!(report.imported.len(), 2);
+    let shopping = store
+        .history
+        .iter()
+        .find(|entry| entry.content == "milk\neggs")
+        .expect("text file must be imported verbatim");
+    assert_eq!(shopping.timestamp, 1_600_000_000);
+    assert!(store
+        .history
+        .iter()
+        .any(|entry| entry.content == "Trip\n\nSee docs (https://example.com)"));
+}
+
+#[test]
+fn old_imports_are_placed_by_timestamp_and_the_oldest_spill_into_archive() {
+    // Given
+    let newest = MemoEntry {
+        id: "mine".to_string(),
+        content: "written today".to_string(),
+        timestamp: 10_000,
+    };
+    let mut store = MemoStoreV2 {
+        history: vec![newest.clone()],
+        ..MemoStoreV2::default()
+    };
+    let prepared = PreparedImport {
+        memos: (1..=150)
+            .map(|day| ImportedMemo {
+                path: PathBuf::from(format!("{day}.md")),
+                entry: MemoEntry {
+                    id: format!("old-{day}"),
+                    content: format!("day {day}"),
+                    timestamp: day,
+                },
+                collection: MemoCollection::History,
+            })
+            .collect(),
+        failures: Vec::new(),
+    };
+
+    // When
+    let report = apply_import(&mut store, prepared);
+
+    // Then
+    assert_eq!(report.imported.len(), 150);
+    assert_eq!(store.history.len(), HISTORY_LIMIT);
+    assert_eq!(store.history[0], newest);
+    assert_eq!(store.history[1].id, "old-150");
+    assert_eq!(store.history[HISTORY_LIMIT - 1].id, "old-52");
+    assert_eq!(store.archive.len(), 51);
+    assert_eq!(store.archive[0].id, "old-51");
+    assert_eq!(store.archive[50].id, "old-1");
+}
+
+#[test]
+fn reimport_only_replaces_memos_when_the_incoming_copy_is_newer() {
+    // Given
+    let mut store = MemoStoreV2 {
+        history: vec![
+            MemoEntry {
+                id: "edited".to_string(),
+                content: "edited in app".to_string(),
+                timestamp: 200,
+            },
+            MemoEntry {
+                id: "stale".to_string(),
+                content: "old text".to_string(),
+                timestamp: 100,
+            },
+        ],
+        ..MemoStoreV2::default()
+    };
+    let imported = |id: &str, content: &str, timestamp: u64| ImportedMemo {
+        path: PathBuf::from(format!("{id}.md")),
+        entry: MemoEntry {
+            id: id.to_string(),
+            content: content.to_string(),
+            timestamp,
+        },
+        collection: MemoCollection::History,
+    };
+    let prepared = PreparedImport {
+        memos: vec![
+            imported("edited", "exported earlier", 150),
+            imported("stale", "updated elsewhere", 300),
+        ],
+        failures: Vec::new(),
+    };
+
+    // When
+    let report = apply_import(&mut store, prepared);
+
+    // Then
+    let ids = |files: &[ImportedFile]| -> Vec<String> {
+        files.iter().map(|file| file.id.clone()).collect()
+    };
+    assert_eq!(ids(&report.imported), vec!["stale".to_string()]);
+    assert_eq!(ids(&report.unchanged), vec!["edited".to_string()]);
+    let contents: Vec<&str> = store
+        .history
+        .iter()
+        .map(|entry| entry.content.as_str())
+        .collect();
+    assert_eq!(contents, vec!["updated elsewhere", "edited in app"]);
+}
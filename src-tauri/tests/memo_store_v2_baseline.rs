@@ -1,3 +1,4 @@
+use upmemo::memo_store::{MemoStoreEngine, MemoStoreRepository, SqliteMemoStore};
 use upmemo::models::MemoEntry;
 
 #[test]
@@ -22,3 +23,49 @@ fn legacy_memo_entry_json_round_trip_preserves_shape() {
     assert_eq!(round_trip.content, entry.content);
     assert_eq!(round_trip.timestamp, entry.timestamp);
 }
+
+#[test]
+fn legacy_memo_entry_round_trips_through_every_engine() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let repository = MemoStoreRepository::open(directory.path().join("memo_store.json"))
+        .expect("fresh store must open");
+    let sqlite = SqliteMemoStore::open(directory.path().join("memo_store.sqlite3"))
+        .expect("fresh sqlite store must open");
+
+    // When
+    store_baseline_entry(&repository);
+    store_baseline_entry(&sqlite);
+
+    // Then
+    assert_eq!(history(&repository), vec![baseline_entry()]);
+    assert_eq!(history(&sqlite), vec![baseline_entry()]);
+    drop(sqlite);
+    let reopened_sqlite = SqliteMemoStore::open(directory.path().join("memo_store.sqlite3"))
+        .expect("sqlite store must reopen");
+    assert_eq!(history(&reopened_sqlite), vec![baseline_entry()]);
+}
+
+fn store_baseline_entry(repository: &impl MemoStoreEngine) {
+    repository
+        .transact(|store| {
+            store.history.push(baseline_entry());
+            Ok(())
+        })
+        .expect("baseline entry must commit");
+}
+
+fn history(repository: &impl MemoStoreEngine) -> Vec<MemoEntry> {
+    repository
+        .snapshot()
+        .expect("snapshot must succeed")
+        .history
+}
+
+fn baseline_entry() -> MemoEntry {
+    MemoEntry {
+        id: "memo-1".to_string(),
+        content: "baseline content".to_string(),
+        timestamp: 123,
+    }
+}
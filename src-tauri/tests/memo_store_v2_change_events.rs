@@ -0,0 +1,154 @@
+use std::sync::{Arc, Mutex};
+
+use upmemo::memo_store::{
+    MemoCollection, MemoStoreEngine, MemoStoreRepository, SqliteMemoStore, StoreChange,
+};
+use upmemo::models::MemoEntry;
+
+#[test]
+fn transactions_emit_typed_entry_changes_after_commit() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let repository = MemoStoreRepository::open(&path).expect("fresh store must open");
+    let sqlite = SqliteMemoStore::open(directory.path().join("memo_store.sqlite3"))
+        .expect("fresh sqlite store must open");
+
+    // When
+    let received = record_entry_changes(&repository);
+    let received_from_sqlite = record_entry_changes(&sqlite);
+
+    // Then
+    assert_eq!(received, received_from_sqlite);
+    assert_eq!(
+        received,
+        vec![
+            vec![
+                added(MemoCollection::History, "kept"),
+                added(MemoCollection::History, "trashed"),
+                added(MemoCollection::Archive, "erased"),
+            ],
+            vec![
+                StoreChange::Updated {
+                    collection: MemoCollection::History,
+                    id: "kept".to_string(),
+                },
+                StoreChange::Deleted {
+                    collection: MemoCollection::Archive,
+                    id: "erased".to_string(),
+                },
+            ],
+            vec![StoreChange::Moved {
+                from: MemoCollection::History,
+                to: MemoCollection::Trash,
+                id: "trashed".to_string(),
+            }],
+        ]
+    );
+}
+
+#[test]
+fn failed_transaction_emits_nothing() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let repository = MemoStoreRepository::open(&path).expect("fresh store must open");
+    let sqlite = SqliteMemoStore::open(directory.path().join("memo_store.sqlite3"))
+        .expect("fresh sqlite store must open");
+
+    // When
+    let emitted = count_events_of_failed_move(&repository);
+    let emitted_by_sqlite = count_events_of_failed_move(&sqlite);
+
+    // Then
+    assert_eq!(emitted, 0);
+    assert_eq!(emitted_by_sqlite, 0);
+}
+
+fn count_events_of_failed_move(repository: &impl MemoStoreEngine) -> usize {
+    let received = Arc::new(Mutex::new(0));
+    let sink = Arc::clone(&received);
+    repository
+        .subscribe(move |_| *sink.lock().expect("sink must lock") += 1)
+        .expect("listener must subscribe");
+
+    let result = repository.move_entry("missing", MemoCollection::Trash, MemoCollection::Trash);
+
+    assert!(result.is_err());
+    let emitted = *received.lock().expect("sink must lock");
+    emitted
+}
+
+#[test]
+fn store_change_serializes_with_kind_tag_and_lowercase_collections() {
+    // Given
+    let change = StoreChange::Moved {
+        from: MemoCollection::Archive,
+        to: MemoCollection::History,
+        id: "memo-1".to_string(),
+    };
+
+    // When
+    let json = serde_json::to_value(&change).expect("change must serialize");
+
+    // Then
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "kind": "moved",
+            "from": "archive",
+            "to": "history",
+            "id": "memo-1",
+        })
+    );
+}
+
+fn record_entry_changes(repository: &impl MemoStoreEngine) -> Vec<Vec<StoreChange>> {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&received);
+    repository
+        .subscribe(move |changes| sink.lock().expect("sink must lock").push(changes.to_vec()))
+        .expect("listener must subscribe");
+    repository
+        .transact(|store| {
+            store.history.push(entry("kept", "before"));
+            store.history.push(entry("trashed", "before"));
+            store.archive.push(entry("erased", "before"));
+            Ok(())
+        })
+        .expect("insert must commit");
+    repository
+        .transact(|store| {
+            store.history[0].content = "after".to_string();
+            store.archive.clear();
+            Ok(())
+        })
+        .expect("update must commit");
+    repository
+        .move_entry("trashed", MemoCollection::History, MemoCollection::Trash)
+        .expect("move must commit");
+    repository
+        .transact(|store| {
+            store.current_content = "scratch".to_string();
+            Ok(())
+        })
+        .expect("content-only transaction must commit");
+
+    let received = received.lock().expect("sink must lock");
+    received.clone()
+}
+
+fn added(collection: MemoCollection, id: &str) -> StoreChange {
+    StoreChange::Added {
+        collection,
+        id: id.to_string(),
+    }
+}
+
+fn entry(id: &str, content: &str) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp: 1,
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+
+use upmemo::memo_store::{MemoStoreError, MemoStoreRepository, MemoStoreV2, StoreEncoding};
+use upmemo::models::MemoEntry;
+
+#[test]
+fn zstd_encoding_is_written_and_read_back_transparently() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let repository = MemoStoreRepository::open(&path).expect("missing store must open");
+
+    // When
+    repository
+        .transact(|store| {
+            store.encoding = StoreEncoding::Zstd;
+            store.history = (0..20).map(entry).collect();
+            Ok(())
+        })
+        .expect("compressed commit must succeed");
+    let on_disk = fs::read(&path).expect("compressed store must exist");
+    let reopened = MemoStoreRepository::open(&path).expect("compressed store must reopen");
+
+    // Then
+    assert_eq!(StoreEncoding::detect(&on_disk), StoreEncoding::Zstd);
+    assert!(serde_json::from_slice::<MemoStoreV2>(&on_disk).is_err());
+    let snapshot = reopened.snapshot().expect("snapshot must be readable");
+    assert_eq!(snapshot.encoding, StoreEncoding::Zstd);
+    assert_eq!(snapshot.history.len(), 20);
+    assert_eq!(
+        snapshot,
+        repository.snapshot().expect("snapshot must be readable")
+    );
+}
+
+#[test]
+fn export_and_switch_back_produce_plain_json() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let export_path = directory.path().join("export").join("memo_store.json");
+    let repository = MemoStoreRepository::open(&path).expect("missing store must open");
+    repository
+        .set_encoding(StoreEncoding::Zstd)
+        .expect("encoding must switch to zstd");
+    repository
+        .transact(|store| {
+            store.current_content = "inspect me".to_string();
+            Ok(())
+        })
+        .expect("compressed commit must succeed");
+
+    // When
+    repository
+        .export_plain_json(&export_path)
+        .expect("export must write plain json");
+    let switched = repository
+        .set_encoding(StoreEncoding::Json)
+        .expect("encoding must switch back to json");
+
+    // Then
+    assert!(switched);
+    for written in [&export_path, &path] {
+        let text = fs::read_to_string(written).expect("plain store must be utf-8 text");
+        assert!(!text.contains("\"encoding\""));
+        let parsed: MemoStoreV2 = serde_json::from_str(&text).expect("plain store must parse");
+        assert_eq!(parsed.current_content, "inspect me");
+        assert_eq!(parsed.encoding, StoreEncoding::Json);
+    }
+}
+
+#[test]
+fn corrupt_zstd_frame_is_reported_as_decompress_failure() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    fs::write(&path, [0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x01]).expect("fixture must be written");
+
+    // When
+    let result = MemoStoreRepository::open(&path);
+
+    // Then
+    assert!(matches!(result, Err(MemoStoreError::Decompress { .. })));
+}
+
+fn entry(index: usize) -> MemoEntry {
+    MemoEntry {
+        id: format!("memo-{index}"),
+        content: format!("repeated memo body {index} ").repeat(8),
+        timestamp: index as u64,
+    }
+}
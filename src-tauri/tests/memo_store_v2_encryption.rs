@@ -0,0 +1,293 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use upmemo::memo_store::{
+    is_sealed, reseal_files, stage_reseal, AtomicFileCommit, CommitAdapter, CommitError,
+    EncryptionError, KdfParams, MemoStoreError, MemoStoreRepository, SealedCommit, StoreKey,
+};
+
+#[derive(Debug)]
+struct FailWhenArmed {
+    armed: Arc<AtomicBool>,
+}
+
+impl CommitAdapter for FailWhenArmed {
+    fn commit(&self, target: &Path, bytes: &[u8]) -> Result<(), CommitError> {
+        if self.armed.load(Ordering::SeqCst) {
+            return Err(CommitError::Injected("disk full".to_string()));
+        }
+        AtomicFileCommit.commit(target, bytes)
+    }
+}
+
+#[test]
+fn sealed_store_needs_the_passphrase_to_reopen() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let repository = MemoStoreRepository::open_with(
+        &path,
+        SealedCommit::new(AtomicFileCommit, Some(key("correct horse"))),
+    )
+    .expect("fresh store must open");
+    repository
+        .transact(|store| {
+            store.current_content = "aws secret".to_string();
+            Ok(())
+        })
+        .expect("sealed commit must succeed");
+    let on_disk = fs::read(&path).expect("sealed store must exist");
+
+    // When
+    let without_key = MemoStoreRepository::open(&path);
+    let wrong_passphrase = StoreKey::derive_for("battery staple", &on_disk);
+    let unlocked = StoreKey::derive_for("correct horse", &on_disk)
+        .map(|key| SealedCommit::new(AtomicFileCommit, Some(key)))
+        .map(|committer| MemoStoreRepository::open_with(&path, committer));
+
+    // Then
+    assert!(is_sealed(&on_disk));
+    assert!(!String::from_utf8_lossy(&on_disk).contains("aws secret"));
+    assert!(matches!(
+        without_key,
+        Err(MemoStoreError::Encryption {
+            source: EncryptionError::Locked,
+            ..
+        })
+    ));
+    assert!(matches!(wrong_passphrase, Err(EncryptionError::Decrypt)));
+    let reopened = unlocked
+        .expect("correct passphrase must derive the key")
+        .expect("sealed store must reopen");
+    assert_eq!(
+        reopened
+            .snapshot()
+            .expect("snapshot must succeed")
+            .current_content,
+        "aws secret"
+    );
+}
+
+#[test]
+fn passphrase_change_rewrites_atomically_and_restores_on_failure() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let armed = Arc::new(AtomicBool::new(false));
+    let committer = SealedCommit::new(
+        FailWhenArmed {
+            armed: Arc::clone(&armed),
+        },
+        None,
+    );
+    let repository = MemoStoreRepository::open_with(&path, committer).expect("store must open");
+    repository
+        .transact(|store| {
+            store.current_content = "customer notes".to_string();
+            Ok(())
+        })
+        .expect("plaintext commit must succeed");
+    let first = Arc::new(key("first"));
+
+    // When
+    repository
+        .recommit_with(
+            |committer| committer.replace_key(Some(Arc::clone(&first))),
+            |committer, previous| {
+                committer.replace_key(previous);
+            },
+        )
+        .expect("enabling encryption must rewrite the store");
+    let sealed_with_first = fs::read(&path).expect("sealed store must exist");
+    armed.store(true, Ordering::SeqCst);
+    let failed_change = repository.recommit_with(
+        |committer| committer.replace_key(Some(Arc::new(key("second")))),
+        |committer, previous| {
+            committer.replace_key(previous);
+        },
+    );
+
+    // Then
+    assert!(failed_change.is_err());
+    assert_eq!(
+        fs::read(&path).expect("store must remain readable"),
+        sealed_with_first
+    );
+    let active = repository.committer().key().expect("key must be restored");
+    assert!(Arc::ptr_eq(&active, &first));
+    assert_eq!(
+        first
+            .unseal(&sealed_with_first)
+            .expect("first key must decrypt"),
+        serde_json::to_vec_pretty(&repository.snapshot().expect("snapshot must succeed"))
+            .expect("store must serialize")
+    );
+}
+
+#[test]
+fn backups_are_resealed_with_the_new_key_and_back_to_plaintext() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let backups = directory.path().join("backups");
+    let nested = backups.join("v1").join("v1-migration-1");
+    fs::create_dir_all(&nested).expect("backup fixture directory must be created");
+    fs::write(nested.join("note.txt"), "legacy secret").expect("backup fixture must be written");
+    fs::write(nested.join("manifest.json"), "{}").expect("manifest fixture must be written");
+    let first = key("first");
+    let second = key("second");
+
+    // When
+    let sealed = reseal_files(&backups, None, Some(&first)).expect("backups must be sealed");
+    let rekeyed =
+        reseal_files(&backups, Some(&first), Some(&second)).expect("backups must be rekeyed");
+    let locked = reseal_files(&backups, None, None);
+    let opened = reseal_files(&backups, Some(&second), None).expect("backups must be opened");
+
+    // Then
+    assert_eq!(sealed.len(), 2);
+    assert_eq!(rekeyed.len(), 2);
+    assert!(matches!(
+        locked,
+        Err(CommitError::Encryption(EncryptionError::Locked))
+    ));
+    assert_eq!(opened.len(), 2);
+    assert_eq!(
+        fs::read_to_string(nested.join("note.txt")).expect("backup must be plaintext again"),
+        "legacy secret"
+    );
+}
+
+#[test]
+fn failed_reseal_leaves_every_backup_under_the_previous_key() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let backups = directory.path().join("backups");
+    fs::create_dir_all(&backups).expect("backup directory must be created");
+    let first = key("first");
+    let stranger = key("stranger");
+    fs::write(
+        backups.join("a.json"),
+        first.seal(b"alpha").expect("fixture must seal"),
+    )
+    .expect("first backup must be written");
+    fs::write(
+        backups.join("b.json"),
+        stranger.seal(b"beta").expect("fixture must seal"),
+    )
+    .expect("foreign backup must be written");
+    let second = key("second");
+
+    // When
+    let failed = reseal_files(&backups, Some(&first), Some(&second));
+    let staged = stage_reseal(&backups, Some(&first), Some(&second));
+
+    // Then
+    assert!(matches!(
+        failed,
+        Err(CommitError::Encryption(EncryptionError::Decrypt))
+    ));
+    assert!(staged.is_err());
+    assert_eq!(
+        first
+            .unseal(&fs::read(backups.join("a.json")).expect("backup must remain"))
+            .expect("untouched backup must still open with the previous key"),
+        b"alpha"
+    );
+    assert!(!directory.path().join("backups.resealing").exists());
+}
+
+#[test]
+fn crafted_kdf_parameters_are_rejected_before_key_derivation() {
+    // Given
+    let mut sealed = key("first").seal(b"memo").expect("fixture must seal");
+    let memory_offset = 8 + 16;
+    sealed[memory_offset..memory_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    // When
+    let derived = StoreKey::derive_for("first", &sealed);
+
+    // Then
+    assert!(matches!(derived, Err(EncryptionError::MalformedEnvelope)));
+}
+
+#[test]
+fn plain_text_written_over_a_sealed_store_is_rejected_on_reload() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let repository = MemoStoreRepository::open_with(
+        &path,
+        SealedCommit::new(AtomicFileCommit, Some(key("correct horse"))),
+    )
+    .expect("fresh store must open");
+    repository
+        .transact(|store| {
+            store.current_content = "sealed".to_string();
+            Ok(())
+        })
+        .expect("sealed commit must succeed");
+    fs::write(&path, br#"{"current_content":"planted"}"#).expect("plain text must be written");
+
+    // When
+    let reloaded = repository.reload_if_changed();
+
+    // Then
+    assert!(matches!(
+        reloaded,
+        Err(MemoStoreError::Encryption {
+            source: EncryptionError::NotSealed,
+            ..
+        })
+    ));
+    assert_eq!(
+        repository
+            .snapshot()
+            .expect("snapshot must succeed")
+            .current_content,
+        "sealed"
+    );
+}
+
+#[test]
+fn reverting_a_swapped_reseal_restores_the_backups_under_the_previous_key() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let backups = directory.path().join("backups");
+    fs::create_dir_all(&backups).expect("backup directory must be created");
+    let first = key("first");
+    let second = key("second");
+    fs::write(
+        backups.join("a.json"),
+        first.seal(b"alpha").expect("fixture must seal"),
+    )
+    .expect("backup must be written");
+    let swapped = stage_reseal(&backups, Some(&first), Some(&second))
+        .expect("reseal must be staged")
+        .swap_reversibly()
+        .expect("staged backups must be swapped in");
+    let resealed = fs::read(backups.join("a.json")).expect("resealed backup must exist");
+
+    // When
+    swapped.revert().expect("swap must be reverted");
+
+    // Then
+    assert_eq!(
+        second
+            .unseal(&resealed)
+            .expect("swapped backup must open with the new key"),
+        b"alpha"
+    );
+    assert_eq!(
+        first
+            .unseal(&fs::read(backups.join("a.json")).expect("backup must be restored"))
+            .expect("restored backup must open with the previous key"),
+        b"alpha"
+    );
+    assert!(!directory.path().join("backups.previous").exists());
+}
+
+fn key(passphrase: &str) -> StoreKey {
+    StoreKey::derive(passphrase, KdfParams::new(8, 1, 1)).expect("test key must derive")
+}
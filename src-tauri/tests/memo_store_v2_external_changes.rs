@@ -0,0 +1,154 @@
+use std::fs;
+
+use upmemo::memo_store::{ExternalChangePolicy, MemoStoreError, MemoStoreRepository, MemoStoreV2};
+use upmemo::models::MemoEntry;
+
+#[test]
+fn transaction_rebases_onto_store_written_by_another_repository() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let first = MemoStoreRepository::open(&path).expect("fresh store must open");
+    let second = MemoStoreRepository::open(&path).expect("fresh store must open");
+    first
+        .transact(|store| {
+            store.history.push(entry("from-first"));
+            Ok(())
+        })
+        .expect("first transaction must commit");
+
+    // When
+    second
+        .transact(|store| {
+            store.history.push(entry("from-second"));
+            Ok(())
+        })
+        .expect("second transaction must rebase and commit");
+
+    // Then
+    let parsed: MemoStoreV2 =
+        serde_json::from_slice(&fs::read(&path).expect("store must be readable"))
+            .expect("store must parse");
+    let ids = parsed
+        .history
+        .iter()
+        .map(|memo| memo.id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["from-first", "from-second"]);
+    assert_eq!(
+        second.snapshot().expect("snapshot must succeed").history,
+        parsed.history
+    );
+}
+
+#[test]
+fn reject_policy_returns_typed_error_without_overwriting_external_bytes() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let repository = MemoStoreRepository::open(&path)
+        .expect("fresh store must open")
+        .with_external_change_policy(ExternalChangePolicy::Reject);
+    repository
+        .transact(|store| {
+            store.current_content = "ours".to_string();
+            Ok(())
+        })
+        .expect("initial transaction must commit");
+    let external = MemoStoreV2 {
+        current_content: "restored from backup".to_string(),
+        ..MemoStoreV2::default()
+    };
+    let external_bytes = serde_json::to_vec_pretty(&external).expect("fixture must serialize");
+    fs::write(&path, &external_bytes).expect("external write must succeed");
+
+    // When
+    let result = repository.transact(|store| {
+        store.current_content = "must not overwrite".to_string();
+        Ok(())
+    });
+
+    // Then
+    assert!(matches!(result, Err(MemoStoreError::ExternalChange { .. })));
+    assert_eq!(
+        fs::read(&path).expect("store must be readable"),
+        external_bytes
+    );
+    assert_eq!(repository.reload().expect("reload must succeed"), external);
+}
+
+#[test]
+fn malformed_external_change_fails_transaction_and_keeps_memory() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let repository = MemoStoreRepository::open(&path).expect("fresh store must open");
+    repository
+        .transact(|store| {
+            store.current_content = "committed".to_string();
+            Ok(())
+        })
+        .expect("initial transaction must commit");
+    fs::write(&path, br#"{"version":2,"#).expect("external write must succeed");
+
+    // When
+    let result = repository.transact(|store| {
+        store.current_content = "must not commit".to_string();
+        Ok(())
+    });
+
+    // Then
+    assert!(matches!(result, Err(MemoStoreError::Malformed { .. })));
+    assert_eq!(
+        repository
+            .snapshot()
+            .expect("snapshot must succeed")
+            .current_content,
+        "committed"
+    );
+}
+
+#[test]
+fn reload_if_changed_ignores_own_commits_and_adopts_external_writes() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let repository = MemoStoreRepository::open(&path).expect("fresh store must open");
+    repository
+        .transact(|store| {
+            store.current_content = "ours".to_string();
+            Ok(())
+        })
+        .expect("initial transaction must commit");
+    let own_commit = repository
+        .reload_if_changed()
+        .expect("reload check must succeed");
+    let external = MemoStoreV2 {
+        history: vec![entry("synced")],
+        ..MemoStoreV2::default()
+    };
+    fs::write(
+        &path,
+        serde_json::to_vec_pretty(&external).expect("fixture must serialize"),
+    )
+    .expect("external write must succeed");
+
+    // When
+    let reloaded = repository.reload_if_changed().expect("reload must succeed");
+
+    // Then
+    assert_eq!(own_commit, None);
+    assert_eq!(reloaded, Some(external.clone()));
+    assert_eq!(
+        repository.snapshot().expect("snapshot must succeed"),
+        external
+    );
+}
+
+fn entry(id: &str) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: format!("content-{id}"),
+        timestamp: 1,
+    }
+}
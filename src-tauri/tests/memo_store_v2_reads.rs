@@ -3,8 +3,10 @@ use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use upmemo::memo_store::{
-    AtomicFileCommit, CommitAdapter, CommitError, MemoStoreError, MemoStoreRepository, MemoStoreV2,
+    AtomicFileCommit, CommitAdapter, CommitError, MemoStoreEngine, MemoStoreError,
+    MemoStoreRepository, MemoStoreV2, SqliteMemoStore,
 };
+use upmemo::models::MemoEntry;
 
 #[derive(Debug)]
 struct AlwaysFailCommit;
@@ -40,12 +42,11 @@ fn missing_v2_file_yields_explicit_fresh_default() {
 
     // When
     let repository = MemoStoreRepository::open(&path).expect("missing store must open fresh");
+    let sqlite = sqlite_store(directory.path());
 
     // Then
-    assert_eq!(
-        repository.snapshot().expect("snapshot must succeed"),
-        MemoStoreV2::default()
-    );
+    assert_fresh_default(&repository);
+    assert_fresh_default(&sqlite);
     assert!(
         !path.exists(),
         "opening a fresh store must not commit implicitly"
@@ -53,25 +54,33 @@ fn missing_v2_file_yields_explicit_fresh_default() {
 }
 
 #[test]
-fn valid_v2_json_round_trip_preserves_all_fields() {
+fn valid_v2_round_trip_preserves_all_fields() {
     // Given
     let directory = tempfile::tempdir().expect("temp directory must be created");
     let path = directory.path().join("memo_store.json");
-    let expected = fixture_store("round trip");
+    let expected = MemoStoreV2 {
+        history: vec![entry("memo-1", "history")],
+        archive: vec![entry("memo-2", "archive")],
+        trash: vec![entry("memo-3", "trash")],
+        ..fixture_store("round trip")
+    };
     fs::write(
         &path,
         serde_json::to_vec_pretty(&expected).expect("fixture must serialize"),
     )
     .expect("fixture must be written");
+    replace_store(&sqlite_store(directory.path()), &expected);
 
     // When
     let repository = MemoStoreRepository::open(&path).expect("valid store must open");
+    let sqlite = sqlite_store(directory.path());
 
     // Then
     assert_eq!(
         repository.snapshot().expect("snapshot must succeed"),
         expected
     );
+    assert_eq!(sqlite.snapshot().expect("snapshot must succeed"), expected);
 }
 
 #[test]
@@ -103,18 +112,29 @@ fn unsupported_existing_version_returns_typed_error() {
     let bytes = serde_json::to_vec_pretty(&store).expect("fixture must serialize");
     fs::write(&path, &bytes).expect("fixture must be written");
 
+    let sqlite = sqlite_store(directory.path());
+
     // When
     let result = MemoStoreRepository::open(&path);
+    let sqlite_result = sqlite.transact(|current| {
+        *current = store;
+        Ok(())
+    });
 
     // Then
     assert!(matches!(
         result,
         Err(MemoStoreError::UnsupportedVersion { found: 3 })
     ));
+    assert!(matches!(
+        sqlite_result,
+        Err(MemoStoreError::UnsupportedVersion { found: 3 })
+    ));
     assert_eq!(
         fs::read(&path).expect("fixture must remain readable"),
         bytes
     );
+    assert_fresh_default(&sqlite);
 }
 
 #[test]
@@ -141,12 +161,10 @@ fn atomic_commit_replaces_existing_target_without_temp_artifacts() {
         serde_json::from_slice(&new_bytes).expect("committed bytes must be valid JSON");
     assert_ne!(new_bytes, old_bytes);
     assert_eq!(parsed.current_content, "after");
-    let files = fs::read_dir(directory.path())
-        .expect("store directory must be readable")
-        .collect::<Result<Vec<_>, _>>()
-        .expect("directory entries must be readable");
-    assert_eq!(files.len(), 1);
-    assert_eq!(files[0].file_name(), "memo_store.json");
+    assert_eq!(
+        visible_files(directory.path()),
+        ["memo_store.json", "memo_store.json.lock"]
+    );
 }
 
 #[test]
@@ -222,13 +240,46 @@ fn interrupted_commit_can_retry_without_stale_state_or_temp_files() {
     let parsed: MemoStoreV2 = serde_json::from_slice(&bytes).expect("retried store must parse");
     assert_eq!(parsed.current_content, "retry committed");
     assert_eq!(
-        fs::read_dir(directory.path())
-            .expect("store directory must be readable")
-            .count(),
-        1
+        visible_files(directory.path()),
+        ["memo_store.json", "memo_store.json.lock"]
+    );
+}
+
+fn sqlite_store(directory: &Path) -> SqliteMemoStore {
+    SqliteMemoStore::open(directory.join("memo_store.sqlite3")).expect("sqlite store must open")
+}
+
+fn assert_fresh_default(repository: &impl MemoStoreEngine) {
+    assert_eq!(
+        repository.snapshot().expect("snapshot must succeed"),
+        MemoStoreV2::default()
     );
 }
 
+fn replace_store(repository: &impl MemoStoreEngine, replacement: &MemoStoreV2) {
+    repository
+        .transact(|store| {
+            *store = replacement.clone();
+            Ok(())
+        })
+        .expect("replacement must commit");
+}
+
+fn visible_files(directory: &Path) -> Vec<String> {
+    let mut files = fs::read_dir(directory)
+        .expect("store directory must be readable")
+        .map(|entry| {
+            entry
+                .expect("directory entry must be readable")
+                .file_name()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
 fn fixture_store(content: &str) -> MemoStoreV2 {
     MemoStoreV2 {
         current_memo_id: Some("memo-1".to_string()),
@@ -236,3 +287,11 @@ fn fixture_store(content: &str) -> MemoStoreV2 {
         ..MemoStoreV2::default()
     }
 }
+
+fn entry(id: &str, content: &str) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp: 1,
+    }
+}
@@ -0,0 +1,253 @@
+use std::fs;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use upmemo::memo_store::{MemoCollection, MemoStoreError, MemoStoreV2, SqliteMemoStore};
+use upmemo::models::MemoEntry;
+
+const PIXEL: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+
+#[test]
+fn json_store_imports_once_into_sqlite() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let json_path = directory.path().join("memo_store.json");
+    let expected = MemoStoreV2 {
+        current_memo_id: Some("memo-1".to_string()),
+        current_content: "current".to_string(),
+        history: vec![entry("memo-1", &format!("<img src=\"{PIXEL}\">"))],
+        archive: vec![entry("memo-2", "archived")],
+        trash: vec![entry("memo-3", "trashed")],
+        ..MemoStoreV2::default()
+    };
+    fs::write(
+        &json_path,
+        serde_json::to_vec_pretty(&expected).expect("fixture must serialize"),
+    )
+    .expect("fixture must be written");
+    let sqlite_path = directory.path().join("memo_store.sqlite3");
+    let sqlite = SqliteMemoStore::open(&sqlite_path).expect("fresh sqlite store must open");
+
+    // When
+    let imported = sqlite.import_json(&json_path).expect("import must succeed");
+    let imported_again = sqlite
+        .import_json(&json_path)
+        .expect("repeated import must succeed");
+    drop(sqlite);
+    let reopened = SqliteMemoStore::open(&sqlite_path).expect("sqlite store must reopen");
+
+    // Then
+    assert!(imported);
+    assert!(!imported_again);
+    assert_eq!(
+        reopened.snapshot().expect("snapshot must succeed"),
+        expected
+    );
+}
+
+#[test]
+fn shared_images_are_stored_once_and_collected_when_unreferenced() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.sqlite3");
+    let sqlite = SqliteMemoStore::open(&path).expect("fresh sqlite store must open");
+    let content = format!("<p>a</p><img src=\"{PIXEL}\"><img src=\"{PIXEL}\">");
+
+    // When
+    sqlite
+        .transact(|store| {
+            store.history.push(entry("memo-1", &content));
+            store.history.push(entry("memo-2", &content));
+            Ok(())
+        })
+        .expect("insert must commit");
+    let stored_while_referenced = attachment_count(&path);
+    let stored_content = stored_entry_content(&path, "memo-1");
+    sqlite
+        .move_entry("memo-1", MemoCollection::History, MemoCollection::Trash)
+        .expect("move must commit");
+    sqlite
+        .transact(|store| {
+            store.history.clear();
+            store.trash.clear();
+            Ok(())
+        })
+        .expect("delete must commit");
+
+    // Then
+    assert_eq!(stored_while_referenced, 1);
+    assert!(!stored_content.contains("base64"));
+    assert_eq!(attachment_count(&path), 0);
+}
+
+#[test]
+fn images_round_trip_byte_for_byte() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.sqlite3");
+    let sqlite = SqliteMemoStore::open(&path).expect("fresh sqlite store must open");
+    let contents = [
+        format!("<img src=\"{PIXEL}\">"),
+        "data:image/png;base64,not-base64!".to_string(),
+        "upmemo-attachment:unknown".to_string(),
+        format!("upmemo-attachment:{}", pixel_attachment_sha256()),
+        format!("upmemo-attachment:!<img src=\"{PIXEL}\">upmemo-attachment:"),
+    ];
+
+    // When
+    sqlite
+        .transact(|store| {
+            store.history = contents
+                .iter()
+                .enumerate()
+                .map(|(index, content)| entry(&format!("memo-{index}"), content))
+                .collect();
+            Ok(())
+        })
+        .expect("insert must commit");
+
+    // Then
+    let history = sqlite.snapshot().expect("snapshot must succeed").history;
+    let round_tripped = history
+        .iter()
+        .map(|memo| memo.content.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        round_tripped,
+        contents.iter().map(String::as_str).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn failed_mutation_rolls_back_and_unsupported_versions_are_rejected() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.sqlite3");
+    let sqlite = SqliteMemoStore::open(&path).expect("fresh sqlite store must open");
+
+    // When
+    let failed = sqlite.transact(|store| {
+        store.history.push(entry("memo-1", "discarded"));
+        Err::<(), _>(MemoStoreError::Poisoned)
+    });
+    let unsupported = sqlite.transact(|store| {
+        store.version = 3;
+        Ok(())
+    });
+    let after_failures = sqlite.snapshot().expect("snapshot must succeed");
+    drop(sqlite);
+    connection(&path)
+        .execute(
+            "INSERT INTO store_header (singleton, version, current_content, encoding)
+             VALUES (1, 3, '', 'json')",
+            [],
+        )
+        .expect("fixture insert must succeed");
+    let reopened = SqliteMemoStore::open(&path);
+
+    // Then
+    assert!(failed.is_err());
+    assert_eq!(after_failures, MemoStoreV2::default());
+    assert!(matches!(
+        unsupported,
+        Err(MemoStoreError::UnsupportedVersion { found: 3 })
+    ));
+    assert!(matches!(
+        reopened,
+        Err(MemoStoreError::UnsupportedVersion { found: 3 })
+    ));
+}
+
+#[test]
+fn row_level_writes_match_a_fresh_load_and_external_writes_invalidate_the_cache() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.sqlite3");
+    let sqlite = SqliteMemoStore::open(&path).expect("fresh sqlite store must open");
+    sqlite
+        .transact(|store| {
+            store.history = vec![
+                entry("memo-a", "first"),
+                entry("memo-b", "second"),
+                entry("memo-c", "third"),
+            ];
+            Ok(())
+        })
+        .expect("insert must commit");
+
+    // When
+    sqlite
+        .transact(|store| {
+            store.history.insert(0, entry("memo-d", "newest"));
+            store.history[2].content = format!("second <img src=\"{PIXEL}\">");
+            let archived = store.history.remove(1);
+            store.archive.push(archived);
+            store.history.retain(|memo| memo.id != "memo-c");
+            Ok(())
+        })
+        .expect("mixed change must commit");
+    let cached = sqlite.snapshot().expect("snapshot must succeed");
+    let loaded = SqliteMemoStore::open(&path)
+        .expect("sqlite store must reopen")
+        .snapshot()
+        .expect("snapshot must succeed");
+    connection(&path)
+        .execute(
+            "UPDATE entries SET content = 'changed outside' WHERE id = 'memo-d'",
+            [],
+        )
+        .expect("external update must succeed");
+    let refreshed = sqlite.snapshot().expect("snapshot must succeed");
+
+    // Then
+    assert_eq!(cached, loaded);
+    let history: Vec<&str> = cached.history.iter().map(|memo| memo.id.as_str()).collect();
+    assert_eq!(history, ["memo-d", "memo-b"]);
+    assert_eq!(cached.archive[0].id, "memo-a");
+    assert_eq!(attachment_count(&path), 1);
+    assert_eq!(refreshed.history[0].content, "changed outside");
+}
+
+fn connection(path: &Path) -> rusqlite::Connection {
+    rusqlite::Connection::open(path).expect("fixture connection must open")
+}
+
+fn attachment_count(path: &Path) -> i64 {
+    connection(path)
+        .query_row("SELECT COUNT(*) FROM attachments", [], |row| row.get(0))
+        .expect("attachments must be countable")
+}
+
+fn stored_entry_content(path: &Path, id: &str) -> String {
+    connection(path)
+        .query_row("SELECT content FROM entries WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })
+        .expect("stored entry must exist")
+}
+
+fn entry(id: &str, content: &str) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp: 1,
+    }
+}
+
+fn pixel_attachment_sha256() -> String {
+    let (mime_type, payload) = PIXEL
+        .strip_prefix("data:")
+        .and_then(|uri| uri.split_once(";base64,"))
+        .expect("pixel must be a base64 data uri");
+    let data = STANDARD.decode(payload).expect("pixel must decode");
+    format!(
+        "{:x}",
+        Sha256::new()
+            .chain_update(mime_type)
+            .chain_update([0])
+            .chain_update(data)
+            .finalize()
+    )
+}
@@ -5,7 +5,8 @@ use std::sync::{Arc, Barrier};
 use std::thread;
 
 use upmemo::memo_store::{
-    AtomicFileCommit, CommitAdapter, CommitError, MemoCollection, MemoStoreRepository,
+    AtomicFileCommit, CommitAdapter, CommitError, MemoCollection, MemoStoreEngine,
+    MemoStoreRepository, SqliteMemoStore,
 };
 use upmemo::models::MemoEntry;
 
@@ -25,6 +26,37 @@ impl CommitAdapter for CountingCommit {
 
 #[test]
 fn collection_move_updates_both_collections_with_one_commit() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let commits = Arc::new(AtomicUsize::new(0));
+    let repository = MemoStoreRepository::open_with(
+        &path,
+        CountingCommit {
+            commits: Arc::clone(&commits),
+            inner: AtomicFileCommit,
+        },
+    )
+    .expect("fresh store must open");
+    seed_entry_to_move(&repository);
+    commits.store(0, Ordering::SeqCst);
+    let sqlite = sqlite_store(&directory);
+    seed_entry_to_move(&sqlite);
+
+    // When
+    let moved = move_to_trash(&repository);
+    let moved_in_sqlite = move_to_trash(&sqlite);
+
+    // Then
+    assert!(moved);
+    assert!(moved_in_sqlite);
+    assert_moved_to_trash(&repository);
+    assert_moved_to_trash(&sqlite);
+    assert_eq!(commits.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn unchanged_transactions_skip_the_commit() {
     // Given
     let directory = tempfile::tempdir().expect("temp directory must be created");
     let path = directory.path().join("memo_store.json");
@@ -39,23 +71,42 @@ fn collection_move_updates_both_collections_with_one_commit() {
     .expect("fresh store must open");
     repository
         .transact(|store| {
-            store.history.push(entry("move-me", 1));
+            store.current_content = "same content".to_string();
+            store.history.push(entry("kept", 1));
             Ok(())
         })
         .expect("fixture transaction must commit");
+    let committed_bytes = std::fs::read(&path).expect("committed store must be readable");
     commits.store(0, Ordering::SeqCst);
+    let sqlite = sqlite_store(&directory);
+    sqlite
+        .transact(|store| {
+            store.current_content = "same content".to_string();
+            store.history.push(entry("kept", 1));
+            Ok(())
+        })
+        .expect("fixture transaction must commit");
+    let sqlite_events = Arc::new(AtomicUsize::new(0));
+    let sink = Arc::clone(&sqlite_events);
+    sqlite
+        .subscribe(move |_| {
+            sink.fetch_add(1, Ordering::SeqCst);
+        })
+        .expect("listener must subscribe");
 
     // When
-    let moved = repository
-        .move_entry("move-me", MemoCollection::History, MemoCollection::Trash)
-        .expect("collection move must commit");
+    let moved = repeat_unchanged_save(&repository);
+    let moved_in_sqlite = repeat_unchanged_save(&sqlite);
 
     // Then
-    let store = repository.snapshot().expect("snapshot must succeed");
-    assert!(moved);
-    assert!(store.history.is_empty());
-    assert_eq!(store.trash, vec![entry("move-me", 1)]);
-    assert_eq!(commits.load(Ordering::SeqCst), 1);
+    assert!(!moved);
+    assert!(!moved_in_sqlite);
+    assert_eq!(sqlite_events.load(Ordering::SeqCst), 0);
+    assert_eq!(commits.load(Ordering::SeqCst), 0);
+    assert_eq!(
+        std::fs::read(&path).expect("store must remain readable"),
+        committed_bytes
+    );
 }
 
 #[test]
@@ -64,8 +115,78 @@ fn transaction_enforces_history_and_trash_boundaries() {
     let directory = tempfile::tempdir().expect("temp directory must be created");
     let path = directory.path().join("memo_store.json");
     let repository = MemoStoreRepository::open(&path).expect("fresh store must open");
+    let sqlite = sqlite_store(&directory);
+
+    // When
+    overfill_history_and_trash(&repository);
+    overfill_history_and_trash(&sqlite);
+
+    // Then
+    assert_bounded(&repository);
+    assert_bounded(&sqlite);
+}
+
+#[test]
+fn concurrent_transactions_do_not_lose_updates() {
+    // Given
+    const WORKERS: usize = 24;
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let repository = Arc::new(MemoStoreRepository::open(&path).expect("fresh store must open"));
+    let sqlite = Arc::new(sqlite_store(&directory));
 
     // When
+    transact_concurrently(&repository, WORKERS);
+    transact_concurrently(&sqlite, WORKERS);
+
+    // Then
+    assert_all_workers_recorded(repository.as_ref(), WORKERS);
+    assert_all_workers_recorded(sqlite.as_ref(), WORKERS);
+    let disk = std::fs::read(&path).expect("committed store must be readable");
+    let parsed: upmemo::memo_store::MemoStoreV2 =
+        serde_json::from_slice(&disk).expect("disk store must parse");
+    assert_eq!(parsed.history.len(), WORKERS);
+}
+
+fn sqlite_store(directory: &tempfile::TempDir) -> SqliteMemoStore {
+    SqliteMemoStore::open(directory.path().join("memo_store.sqlite3"))
+        .expect("fresh sqlite store must open")
+}
+
+fn seed_entry_to_move(repository: &impl MemoStoreEngine) {
+    repository
+        .transact(|store| {
+            store.history.push(entry("move-me", 1));
+            Ok(())
+        })
+        .expect("fixture transaction must commit");
+}
+
+fn move_to_trash(repository: &impl MemoStoreEngine) -> bool {
+    repository
+        .move_entry("move-me", MemoCollection::History, MemoCollection::Trash)
+        .expect("collection move must commit")
+}
+
+fn assert_moved_to_trash(repository: &impl MemoStoreEngine) {
+    let store = repository.snapshot().expect("snapshot must succeed");
+    assert!(store.history.is_empty());
+    assert_eq!(store.trash, vec![entry("move-me", 1)]);
+}
+
+fn repeat_unchanged_save(repository: &impl MemoStoreEngine) -> bool {
+    repository
+        .transact(|store| {
+            store.current_content = "same content".to_string();
+            Ok(())
+        })
+        .expect("identical save must succeed");
+    repository
+        .move_entry("missing", MemoCollection::History, MemoCollection::Trash)
+        .expect("missing move must succeed")
+}
+
+fn overfill_history_and_trash(repository: &impl MemoStoreEngine) {
     repository
         .transact(|store| {
             store.history = (0..101)
@@ -77,8 +198,9 @@ fn transaction_enforces_history_and_trash_boundaries() {
             Ok(())
         })
         .expect("bounded transaction must commit");
+}
 
-    // Then
+fn assert_bounded(repository: &impl MemoStoreEngine) {
     let store = repository.snapshot().expect("snapshot must succeed");
     assert_eq!(store.history.len(), 100);
     assert_eq!(store.trash.len(), 50);
@@ -86,19 +208,16 @@ fn transaction_enforces_history_and_trash_boundaries() {
     assert_eq!(store.trash[49].id, "trash-49");
 }
 
-#[test]
-fn concurrent_transactions_do_not_lose_updates() {
-    // Given
-    const WORKERS: usize = 24;
-    let directory = tempfile::tempdir().expect("temp directory must be created");
-    let path = directory.path().join("memo_store.json");
-    let repository = Arc::new(MemoStoreRepository::open(&path).expect("fresh store must open"));
-    let barrier = Arc::new(Barrier::new(WORKERS));
-    let mut workers = Vec::with_capacity(WORKERS);
-    for index in 0..WORKERS {
-        let repository = Arc::clone(&repository);
+fn transact_concurrently<E>(repository: &Arc<E>, workers: usize)
+where
+    E: MemoStoreEngine + 'static,
+{
+    let barrier = Arc::new(Barrier::new(workers));
+    let mut handles = Vec::with_capacity(workers);
+    for index in 0..workers {
+        let repository = Arc::clone(repository);
         let barrier = Arc::clone(&barrier);
-        workers.push(thread::spawn(move || {
+        handles.push(thread::spawn(move || {
             barrier.wait();
             repository.transact(|store| {
                 store.history.push(entry(&format!("memo-{index}"), index));
@@ -107,27 +226,23 @@ fn concurrent_transactions_do_not_lose_updates() {
         }));
     }
 
-    // When
-    for worker in workers {
-        worker
+    for handle in handles {
+        handle
             .join()
             .expect("worker must not panic")
             .expect("worker transaction must commit");
     }
+}
 
-    // Then
+fn assert_all_workers_recorded(repository: &impl MemoStoreEngine, workers: usize) {
     let store = repository.snapshot().expect("snapshot must succeed");
     let ids = store
         .history
         .iter()
         .map(|memo| memo.id.as_str())
         .collect::<HashSet<_>>();
-    assert_eq!(store.history.len(), WORKERS);
-    assert_eq!(ids.len(), WORKERS);
-    let disk = std::fs::read(&path).expect("committed store must be readable");
-    let parsed: upmemo::memo_store::MemoStoreV2 =
-        serde_json::from_slice(&disk).expect("disk store must parse");
-    assert_eq!(parsed.history.len(), WORKERS);
+    assert_eq!(store.history.len(), workers);
+    assert_eq!(ids.len(), workers);
 }
 
 fn entry(id: &str, timestamp: usize) -> MemoEntry {
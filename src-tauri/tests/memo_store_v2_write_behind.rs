@@ -0,0 +1,276 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use upmemo::memo_store::{
+    AtomicFileCommit, CommitAdapter, CommitError, MemoStoreRepository, MemoStoreV2,
+};
+use upmemo::models::MemoEntry;
+
+#[derive(Debug)]
+struct CountingCommit {
+    commits: Arc<AtomicUsize>,
+    inner: AtomicFileCommit,
+}
+
+impl CommitAdapter for CountingCommit {
+    fn commit(&self, target: &Path, bytes: &[u8]) -> Result<(), CommitError> {
+        self.inner.commit(target, bytes)?;
+        self.commits.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn write_behind_coalesces_autosaves_into_one_flush() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let commits = Arc::new(AtomicUsize::new(0));
+    let repository = counting_repository(&path, &commits, Duration::from_secs(60));
+
+    // When
+    for keystroke in ["a", "ab", "abc"] {
+        repository
+            .transact(|store| {
+                store.current_content = keystroke.to_string();
+                Ok(())
+            })
+            .expect("write-behind transaction must apply");
+    }
+    let pending_before_flush = repository
+        .has_pending_writes()
+        .expect("pending state must be readable");
+    let flushed = repository.flush().expect("flush must commit");
+    let flushed_again = repository.flush().expect("second flush must succeed");
+
+    // Then
+    assert!(pending_before_flush);
+    assert!(flushed);
+    assert!(!flushed_again);
+    assert_eq!(commits.load(Ordering::SeqCst), 1);
+    let parsed: MemoStoreV2 =
+        serde_json::from_slice(&fs::read(&path).expect("flushed store must be readable"))
+            .expect("flushed store must parse");
+    assert_eq!(parsed.current_content, "abc");
+}
+
+#[test]
+fn write_behind_mutations_are_visible_before_they_reach_disk() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let commits = Arc::new(AtomicUsize::new(0));
+    let repository = counting_repository(&path, &commits, Duration::from_secs(60));
+
+    // When
+    repository
+        .transact(|store| {
+            store.current_content = "in memory".to_string();
+            Ok(())
+        })
+        .expect("write-behind transaction must apply");
+    let flushed_early = repository.flush_if_due().expect("due check must succeed");
+
+    // Then
+    assert!(!flushed_early);
+    assert_eq!(
+        repository
+            .snapshot()
+            .expect("snapshot must succeed")
+            .current_content,
+        "in memory"
+    );
+    assert!(!path.exists());
+    assert_eq!(commits.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn flush_if_due_commits_once_the_window_has_elapsed() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let commits = Arc::new(AtomicUsize::new(0));
+    let repository = counting_repository(&path, &commits, Duration::ZERO);
+    repository
+        .transact(|store| {
+            store.current_content = "due".to_string();
+            Ok(())
+        })
+        .expect("write-behind transaction must apply");
+
+    // When
+    let flushed = repository.flush_if_due().expect("due flush must commit");
+
+    // Then
+    assert!(flushed);
+    assert_eq!(commits.load(Ordering::SeqCst), 1);
+    assert!(!repository
+        .has_pending_writes()
+        .expect("pending state must be readable"));
+}
+
+#[test]
+fn external_edit_during_write_behind_window_is_merged_on_flush() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let commits = Arc::new(AtomicUsize::new(0));
+    let repository = counting_repository(&path, &commits, Duration::from_secs(60));
+    repository
+        .transact(|store| {
+            store.history.push(entry("shared", "before"));
+            Ok(())
+        })
+        .expect("write-behind transaction must apply");
+    repository.flush().expect("initial flush must commit");
+    repository
+        .transact(|store| {
+            store.current_content = "typing".to_string();
+            store.history[0].content = "edited here".to_string();
+            Ok(())
+        })
+        .expect("pending edit must apply");
+    let other = MemoStoreRepository::open(&path).expect("second repository must open");
+    other
+        .transact(|store| {
+            store.history.insert(0, entry("external", "from elsewhere"));
+            Ok(())
+        })
+        .expect("external edit must commit");
+
+    // When
+    let flushed = repository
+        .flush()
+        .expect("flush must merge the external edit");
+    let later = repository.transact(|store| {
+        store.current_content = "typing more".to_string();
+        Ok(())
+    });
+    let flushed_again = repository.flush().expect("later flush must commit");
+
+    // Then
+    assert!(flushed);
+    assert!(later.is_ok());
+    assert!(flushed_again);
+    let parsed: MemoStoreV2 =
+        serde_json::from_slice(&fs::read(&path).expect("merged store must be readable"))
+            .expect("merged store must parse");
+    assert_eq!(parsed.current_content, "typing more");
+    assert_eq!(
+        parsed.history,
+        vec![
+            entry("external", "from elsewhere"),
+            entry("shared", "edited here")
+        ]
+    );
+}
+
+#[test]
+fn reload_if_changed_rebases_pending_writes_instead_of_failing() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let commits = Arc::new(AtomicUsize::new(0));
+    let repository = counting_repository(&path, &commits, Duration::from_secs(60));
+    repository
+        .transact(|store| {
+            store.current_content = "pending".to_string();
+            Ok(())
+        })
+        .expect("pending edit must apply");
+    MemoStoreRepository::open(&path)
+        .expect("second repository must open")
+        .transact(|store| {
+            store.archive.push(entry("archived", "elsewhere"));
+            Ok(())
+        })
+        .expect("external edit must commit");
+
+    // When
+    let reloaded = repository
+        .reload_if_changed()
+        .expect("reload must rebase pending writes")
+        .expect("external change must be reported");
+
+    // Then
+    assert_eq!(reloaded.current_content, "pending");
+    assert_eq!(reloaded.archive, vec![entry("archived", "elsewhere")]);
+    assert!(!repository
+        .has_pending_writes()
+        .expect("pending state must be readable"));
+    assert_eq!(
+        MemoStoreRepository::open(&path)
+            .expect("merged store must reopen")
+            .snapshot()
+            .expect("snapshot must succeed"),
+        reloaded
+    );
+}
+
+#[test]
+fn reload_commits_pending_writes_on_top_of_the_file() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let path = directory.path().join("memo_store.json");
+    let commits = Arc::new(AtomicUsize::new(0));
+    let repository = counting_repository(&path, &commits, Duration::from_secs(60));
+    repository
+        .transact(|store| {
+            store.current_content = "pending".to_string();
+            Ok(())
+        })
+        .expect("pending edit must apply");
+    MemoStoreRepository::open(&path)
+        .expect("second repository must open")
+        .transact(|store| {
+            store.archive.push(entry("archived", "elsewhere"));
+            Ok(())
+        })
+        .expect("external edit must commit");
+
+    // When
+    let reloaded = repository
+        .reload()
+        .expect("reload must keep pending writes");
+
+    // Then
+    assert_eq!(reloaded.current_content, "pending");
+    assert_eq!(reloaded.archive, vec![entry("archived", "elsewhere")]);
+    assert!(!repository
+        .has_pending_writes()
+        .expect("pending state must be readable"));
+    assert_eq!(
+        MemoStoreRepository::open(&path)
+            .expect("merged store must reopen")
+            .snapshot()
+            .expect("snapshot must succeed"),
+        reloaded
+    );
+}
+
+fn entry(id: &str, content: &str) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp: 1,
+    }
+}
+
+fn counting_repository(
+    path: &Path,
+    commits: &Arc<AtomicUsize>,
+    window: Duration,
+) -> MemoStoreRepository<CountingCommit> {
+    MemoStoreRepository::open_with(
+        path,
+        CountingCommit {
+            commits: Arc::clone(commits),
+            inner: AtomicFileCommit,
+        },
+    )
+    .expect("fresh store must open")
+    .with_write_behind(window)
+}
@@ -0,0 +1,371 @@
+use std::fs;
+use std::path::Path;
+
+use upmemo::memo_store::{AtomicFileCommit, MemoStoreV2};
+use upmemo::memo_sync::{
+    publish_pending, sync_memos, ChangeRecord, FolderBackend, SyncBackend, SyncBackendConfig,
+    SyncError, SyncReport, SyncState,
+};
+use upmemo::models::MemoEntry;
+use upmemo::private_memo::PRIVATE_MEMO_PREFIX;
+
+fn memo(id: &str, content: &str, timestamp: u64) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp,
+    }
+}
+
+struct Device {
+    store: MemoStoreV2,
+    state_path: std::path::PathBuf,
+}
+
+impl Device {
+    fn new(directory: &Path, shared: &Path, history: Vec<MemoEntry>) -> Self {
+        let state_path = directory.join("sync_state.json");
+        let mut state = SyncState::load(&state_path).expect("fresh state must load");
+        state.set_backend(Some(SyncBackendConfig::Folder {
+            path: shared.to_path_buf(),
+        }));
+        state
+            .save(&state_path, &AtomicFileCommit)
+            .expect("state must be saved");
+        Self {
+            store: MemoStoreV2 {
+                history,
+                ..MemoStoreV2::default()
+            },
+            state_path,
+        }
+    }
+
+    fn sync(&mut self, now: u64) -> SyncReport {
+        let mut state = SyncState::load(&self.state_path).expect("state must load");
+        let backend = match state.backend.clone().expect("backend must be configured") {
+            SyncBackendConfig::Folder { path } => FolderBackend::new(path),
+        };
+        let report =
+            sync_memos(&mut self.store, &mut state, &backend, now).expect("sync must succeed");
+        state
+            .save(&self.state_path, &AtomicFileCommit)
+            .expect("state must be saved");
+        publish_pending(&mut state, &backend).expect("pending records must be published");
+        state
+            .save(&self.state_path, &AtomicFileCommit)
+            .expect("state must be saved");
+        report
+    }
+
+    fn contents(&self) -> Vec<(String, String)> {
+        let mut contents: Vec<(String, String)> = self
+            .store
+            .history
+            .iter()
+            .map(|entry| (entry.id.clone(), entry.content.clone()))
+            .collect();
+        contents.sort();
+        contents
+    }
+}
+
+#[test]
+fn edits_and_deletions_flow_between_two_devices_through_a_shared_folder() {
+    // Given
+    let shared = tempfile::tempdir().expect("shared directory must be created");
+    let desktop_dir = tempfile::tempdir().expect("desktop directory must be created");
+    let laptop_dir = tempfile::tempdir().expect("laptop directory must be created");
+    let mut desktop = Device::new(
+        desktop_dir.path(),
+        shared.path(),
+        vec![memo("a", "groceries", 100), memo("b", "old idea", 90)],
+    );
+    let mut laptop = Device::new(laptop_dir.path(), shared.path(), Vec::new());
+
+    // When
+    let first_push = desktop.sync(1_000);
+    let first_pull = laptop.sync(1_100);
+    let edited = laptop
+        .store
+        .history
+        .iter_mut()
+        .find(|entry| entry.id == "a")
+        .expect("laptop must have pulled memo a");
+    edited.content = "groceries and milk".to_string();
+    edited.timestamp = 1_200;
+    let position = laptop
+        .store
+        .history
+        .iter()
+        .position(|entry| entry.id == "b")
+        .expect("laptop must have pulled memo b");
+    let trashed = laptop.store.history.remove(position);
+    laptop.store.trash.push(trashed);
+    let second_push = laptop.sync(1_300);
+    let second_pull = desktop.sync(1_400);
+    let settled = desktop.sync(1_500);
+
+    // Then
+    assert_eq!(first_push.pushed, vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(first_pull.pulled, vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(second_push.pushed, vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(second_pull.pulled, vec!["a".to_string(), "b".to_string()]);
+    assert!(settled.is_empty());
+    assert_eq!(
+        desktop.contents(),
+        vec![("a".to_string(), "groceries and milk".to_string())]
+    );
+    assert_eq!(desktop.store.trash, vec![memo("b", "old idea", 90)]);
+    assert_eq!(desktop.contents(), laptop.contents());
+}
+
+#[test]
+fn concurrent_edits_keep_the_newer_version_and_a_conflict_copy() {
+    // Given
+    let shared = tempfile::tempdir().expect("shared directory must be created");
+    let desktop_dir = tempfile::tempdir().expect("desktop directory must be created");
+    let laptop_dir = tempfile::tempdir().expect("laptop directory must be created");
+    let mut desktop = Device::new(
+        desktop_dir.path(),
+        shared.path(),
+        vec![memo("a", "draft", 100)],
+    );
+    let mut laptop = Device::new(laptop_dir.path(), shared.path(), Vec::new());
+    desktop.sync(1_000);
+    laptop.sync(1_100);
+    desktop.store.history[0] = memo("a", "desktop edit", 2_000);
+    laptop.store.history[0] = memo("a", "laptop edit", 3_000);
+
+    // When
+    desktop.sync(3_100);
+    let conflicted = laptop.sync(3_200);
+    let resolved = desktop.sync(3_300);
+
+    // Then
+    assert_eq!(conflicted.conflicts.len(), 1);
+    let conflict = &conflicted.conflicts[0];
+    assert_eq!(conflict.id, "a");
+    assert_eq!(resolved.pulled.len(), 2);
+    let expected = {
+        let mut expected = vec![
+            ("a".to_string(), "laptop edit".to_string()),
+            (
+                conflict.copy_id.clone(),
+                "desktop edit\n\n#衝突副本".to_string(),
+            ),
+        ];
+        expected.sort();
+        expected
+    };
+    assert_eq!(laptop.contents(), expected);
+    assert_eq!(desktop.contents(), expected);
+}
+
+#[test]
+fn memos_made_private_are_withdrawn_from_the_shared_folder() {
+    // Given
+    let shared = tempfile::tempdir().expect("shared directory must be created");
+    let desktop_dir = tempfile::tempdir().expect("desktop directory must be created");
+    let laptop_dir = tempfile::tempdir().expect("laptop directory must be created");
+    let mut desktop = Device::new(
+        desktop_dir.path(),
+        shared.path(),
+        vec![memo("a", "bank pin 1234", 100), memo("b", "groceries", 90)],
+    );
+    let mut laptop = Device::new(laptop_dir.path(), shared.path(), Vec::new());
+    desktop.sync(1_000);
+    laptop.sync(1_100);
+    desktop.store.history[0].content = format!("{PRIVATE_MEMO_PREFIX}sealed");
+
+    // When
+    let withdrawn = desktop.sync(1_200);
+    let pulled = laptop.sync(1_300);
+    let settled = desktop.sync(1_400);
+
+    // Then
+    assert_eq!(withdrawn.pushed, vec!["a".to_string()]);
+    assert_eq!(pulled.pulled, vec!["a".to_string()]);
+    assert!(settled.pushed.is_empty());
+    assert_eq!(
+        laptop.contents(),
+        vec![("b".to_string(), "groceries".to_string())]
+    );
+    let published = shared_text(shared.path());
+    assert!(!published.contains("bank pin"), "{published}");
+}
+
+#[test]
+fn withdrawing_a_device_removes_only_its_published_records() {
+    // Given
+    let shared = tempfile::tempdir().expect("shared directory must be created");
+    let desktop_dir = tempfile::tempdir().expect("desktop directory must be created");
+    let laptop_dir = tempfile::tempdir().expect("laptop directory must be created");
+    let mut desktop = Device::new(
+        desktop_dir.path(),
+        shared.path(),
+        vec![memo("a", "groceries", 100)],
+    );
+    let mut laptop = Device::new(
+        laptop_dir.path(),
+        shared.path(),
+        vec![memo("b", "trip plan", 90)],
+    );
+    desktop.sync(1_000);
+    laptop.sync(1_100);
+    let device = SyncState::load(&desktop.state_path)
+        .expect("state must load")
+        .device_id;
+
+    // When
+    FolderBackend::new(shared.path())
+        .withdraw(&device)
+        .expect("withdraw must succeed");
+
+    // Then
+    let published = shared_text(shared.path());
+    assert!(!published.contains("groceries"), "{published}");
+    assert!(published.contains("trip plan"), "{published}");
+}
+
+#[test]
+fn pulling_the_open_memo_replaces_the_editor_content() {
+    // Given
+    let shared = tempfile::tempdir().expect("shared directory must be created");
+    let desktop_dir = tempfile::tempdir().expect("desktop directory must be created");
+    let laptop_dir = tempfile::tempdir().expect("laptop directory must be created");
+    let mut desktop = Device::new(
+        desktop_dir.path(),
+        shared.path(),
+        vec![memo("a", "groceries", 100)],
+    );
+    let mut laptop = Device::new(laptop_dir.path(), shared.path(), Vec::new());
+    desktop.sync(1_000);
+    laptop.sync(1_100);
+    laptop.store.current_memo_id = Some("a".to_string());
+    laptop.store.current_content = "groceries".to_string();
+    desktop.store.history[0] = memo("a", "groceries and milk", 2_000);
+
+    // When
+    desktop.sync(2_100);
+    let pulled = laptop.sync(2_200);
+
+    // Then
+    assert!(pulled.current_replaced);
+    assert!(pulled.conflicts.is_empty());
+    assert_eq!(laptop.store.current_content, "groceries and milk");
+    assert_eq!(
+        laptop.contents(),
+        vec![("a".to_string(), "groceries and milk".to_string())]
+    );
+}
+
+#[test]
+fn unsaved_text_in_the_open_memo_conflicts_with_a_remote_edit() {
+    // Given
+    let shared = tempfile::tempdir().expect("shared directory must be created");
+    let desktop_dir = tempfile::tempdir().expect("desktop directory must be created");
+    let laptop_dir = tempfile::tempdir().expect("laptop directory must be created");
+    let mut desktop = Device::new(
+        desktop_dir.path(),
+        shared.path(),
+        vec![memo("a", "groceries", 100)],
+    );
+    let mut laptop = Device::new(laptop_dir.path(), shared.path(), Vec::new());
+    desktop.sync(1_000);
+    laptop.sync(1_100);
+    laptop.store.current_memo_id = Some("a".to_string());
+    laptop.store.current_content = "groceries and eggs".to_string();
+    desktop.store.history[0] = memo("a", "groceries and milk", 2_000);
+
+    // When
+    desktop.sync(2_100);
+    let conflicted = laptop.sync(3_000);
+
+    // Then
+    assert_eq!(conflicted.conflicts.len(), 1);
+    assert!(!conflicted.current_replaced);
+    assert_eq!(laptop.store.current_content, "groceries and eggs");
+    let copy_id = conflicted.conflicts[0].copy_id.clone();
+    let expected = {
+        let mut expected = vec![
+            ("a".to_string(), "groceries and eggs".to_string()),
+            (copy_id, "groceries and milk\n\n#衝突副本".to_string()),
+        ];
+        expected.sort();
+        expected
+    };
+    assert_eq!(laptop.contents(), expected);
+}
+
+#[test]
+fn records_are_kept_pending_until_a_publish_succeeds() {
+    // Given
+    let shared = tempfile::tempdir().expect("shared directory must be created");
+    let desktop_dir = tempfile::tempdir().expect("desktop directory must be created");
+    let desktop = Device::new(
+        desktop_dir.path(),
+        shared.path(),
+        vec![memo("a", "groceries", 100)],
+    );
+    let mut store = desktop.store.clone();
+    let mut state = SyncState::load(&desktop.state_path).expect("state must load");
+    let offline = OfflineBackend(FolderBackend::new(shared.path()));
+    sync_memos(&mut store, &mut state, &offline, 1_000).expect("sync must succeed");
+    state
+        .save(&desktop.state_path, &AtomicFileCommit)
+        .expect("state must be saved");
+
+    // When
+    let failed = publish_pending(&mut state, &offline);
+    let mut reloaded = SyncState::load(&desktop.state_path).expect("state must reload");
+    let pending_after_failure = reloaded.has_pending();
+    let resumed = sync_memos(
+        &mut store,
+        &mut reloaded,
+        &FolderBackend::new(shared.path()),
+        1_100,
+    )
+    .expect("second sync must succeed");
+    publish_pending(&mut reloaded, &FolderBackend::new(shared.path()))
+        .expect("pending records must be published");
+
+    // Then
+    assert!(failed.is_err());
+    assert!(pending_after_failure);
+    assert!(resumed.is_empty());
+    assert!(!reloaded.has_pending());
+    assert!(shared_text(shared.path()).contains("groceries"));
+}
+
+fn shared_text(shared: &Path) -> String {
+    let mut text = String::new();
+    for device in fs::read_dir(shared.join("upmemo-sync")).expect("sync directory must exist") {
+        let device = device.expect("device directory must be readable").path();
+        for record in fs::read_dir(device).expect("device directory must be listable") {
+            let path = record.expect("record must be readable").path();
+            text.push_str(&fs::read_to_string(path).expect("record must be text"));
+        }
+    }
+    text
+}
+
+struct OfflineBackend(FolderBackend);
+
+impl SyncBackend for OfflineBackend {
+    fn publish(&self, _device: &str, _records: &[ChangeRecord]) -> Result<(), SyncError> {
+        Err(SyncError::Io {
+            operation: "publish",
+            path: "offline".into(),
+            source: std::io::Error::other("offline"),
+        })
+    }
+
+    fn fetch(&self, device: &str) -> Result<Vec<ChangeRecord>, SyncError> {
+        self.0.fetch(device)
+    }
+
+    fn withdraw(&self, device: &str) -> Result<(), SyncError> {
+        self.0.withdraw(device)
+    }
+}
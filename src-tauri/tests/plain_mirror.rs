@@ -0,0 +1,224 @@
+use std::fs;
+use std::path::Path;
+
+use upmemo::memo_store::{AtomicFileCommit, MemoStoreV2};
+use upmemo::models::MemoEntry;
+use upmemo::plain_mirror::{
+    plan_plain_mirror, remove_plain_mirror, sync_plain_mirror, MirrorFormat, MirrorOptions,
+    PlainMirrorError,
+};
+
+fn memo(id: &str, content: &str) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp: 1_600_000_000,
+    }
+}
+
+fn options(folder: &Path, format: MirrorFormat, now: u64) -> MirrorOptions<'_> {
+    MirrorOptions {
+        folder,
+        format,
+        attachments: folder,
+        now,
+    }
+}
+
+#[test]
+fn markdown_mirror_writes_memos_and_pulls_external_edits_deletions_and_new_files() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let folder = directory.path().join("mirror");
+    let mut store = MemoStoreV2 {
+        history: vec![memo("a", "Groceries\nmilk"), memo("b", "Old idea")],
+        ..MemoStoreV2::default()
+    };
+    let first = sync_plain_mirror(
+        &mut store,
+        &options(&folder, MirrorFormat::Markdown, 1),
+        &AtomicFileCommit,
+    )
+    .expect("initial mirror must succeed");
+    let groceries = folder.join("Groceries.md");
+    let written = fs::read_to_string(&groceries).expect("memo file must be written");
+    fs::write(&groceries, written.replace("milk", "milk  \neggs"))
+        .expect("external edit must be written");
+    fs::remove_file(folder.join("Old idea.md")).expect("file must be deleted");
+    fs::write(folder.join("Fresh.md"), "Fresh thought").expect("new file must be written");
+
+    // When
+    let report = sync_plain_mirror(
+        &mut store,
+        &options(&folder, MirrorFormat::Markdown, 1_700_000_000),
+        &AtomicFileCommit,
+    )
+    .expect("second mirror must succeed");
+    let settled = sync_plain_mirror(
+        &mut store,
+        &options(&folder, MirrorFormat::Markdown, 1_700_000_001),
+        &AtomicFileCommit,
+    )
+    .expect("third mirror must succeed");
+
+    // Then
+    assert_eq!(first.written.len(), 2);
+    assert!(written.starts_with("---\nid: a\n"), "{written}");
+    assert_eq!(report.updated, vec!["a".to_string()]);
+    assert_eq!(report.trashed, vec!["b".to_string()]);
+    assert_eq!(report.added.len(), 1);
+    assert!(report.conflicts.is_empty());
+    assert!(settled.is_empty(), "{settled:?}");
+    let contents: Vec<(&str, u64)> = store
+        .history
+        .iter()
+        .map(|entry| (entry.content.as_str(), entry.timestamp))
+        .collect();
+    assert_eq!(
+        contents,
+        vec![
+            ("Fresh thought", 1_700_000_000),
+            ("Groceries\nmilk\neggs", 1_700_000_000)
+        ]
+    );
+    assert_eq!(store.trash[0].id, "b");
+    let fresh = fs::read_to_string(folder.join("Fresh.md")).expect("adopted file must remain");
+    assert!(
+        fresh.contains(&format!("id: {}", report.added[0])),
+        "{fresh}"
+    );
+}
+
+#[test]
+fn html_mirror_keeps_the_store_version_and_copies_the_file_when_both_sides_changed() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let folder = directory.path().to_path_buf();
+    let mut store = MemoStoreV2 {
+        history: vec![memo("plan", "Plan\na < b")],
+        ..MemoStoreV2::default()
+    };
+    sync_plain_mirror(
+        &mut store,
+        &options(&folder, MirrorFormat::Html, 1),
+        &AtomicFileCommit,
+    )
+    .expect("initial mirror must succeed");
+    let path = folder.join("Plan.html");
+    let written = fs::read_to_string(&path).expect("memo file must be written");
+    fs::write(&path, written.replace("a &lt; b", "<p>edited outside</p>"))
+        .expect("external edit must be written");
+    store.history[0].content = "Plan\nedited inside".to_string();
+
+    // When
+    let report = sync_plain_mirror(
+        &mut store,
+        &options(&folder, MirrorFormat::Html, 1_700_000_000),
+        &AtomicFileCommit,
+    )
+    .expect("conflicting mirror must succeed");
+
+    // Then
+    assert!(
+        written.contains("<meta name=\"upmemo-id\" content=\"plan\">"),
+        "{written}"
+    );
+    assert!(written.contains("Plan<br>\na &lt; b"), "{written}");
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.conflicts[0].id, "plan");
+    assert_eq!(report.conflicts[0].path, path);
+    let copy = &store.history[0];
+    assert_eq!(copy.id, report.conflicts[0].copy_id);
+    assert_eq!(copy.content, "Plan\nedited outside\n");
+    assert_eq!(store.history[1].content, "Plan\nedited inside");
+    let rewritten = fs::read_to_string(&path).expect("memo file must be rewritten");
+    assert!(rewritten.contains("edited inside"), "{rewritten}");
+    assert!(folder.join("Plan (2).html").exists());
+}
+
+#[test]
+fn mirror_refuses_a_folder_that_already_holds_the_users_own_notes() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let folder = directory.path().to_path_buf();
+    let notes = folder.join("Journal.md");
+    fs::write(&notes, "# Journal\n\n**kept as is**").expect("user note must be written");
+    let mut store = MemoStoreV2 {
+        history: vec![memo("a", "Groceries")],
+        ..MemoStoreV2::default()
+    };
+
+    // When
+    let result = sync_plain_mirror(
+        &mut store,
+        &options(&folder, MirrorFormat::Markdown, 1),
+        &AtomicFileCommit,
+    );
+
+    // Then
+    assert!(
+        matches!(result, Err(PlainMirrorError::FolderInUse { .. })),
+        "{result:?}"
+    );
+    assert_eq!(
+        fs::read_to_string(&notes).expect("user note must remain"),
+        "# Journal\n\n**kept as is**"
+    );
+    assert_eq!(store.history.len(), 1);
+    assert!(!folder.join("Groceries.md").exists());
+    assert!(!folder.join(".upmemo-mirror.json").exists());
+}
+
+#[test]
+fn planning_leaves_the_folder_untouched_until_the_plan_is_applied() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let folder = directory.path().join("mirror");
+    let mut store = MemoStoreV2 {
+        history: vec![memo("a", "Groceries\nmilk")],
+        ..MemoStoreV2::default()
+    };
+
+    // When
+    let plan = plan_plain_mirror(&mut store, &options(&folder, MirrorFormat::Markdown, 1))
+        .expect("mirror must be planned");
+    let untouched = !folder.exists();
+    let report = plan
+        .apply(&AtomicFileCommit)
+        .expect("mirror plan must be applied");
+
+    // Then
+    assert!(untouched);
+    assert_eq!(report.written, vec![folder.join("Groceries.md")]);
+    assert!(folder.join(".upmemo-mirror.json").exists());
+}
+
+#[test]
+fn removing_the_mirror_deletes_only_the_files_it_wrote() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let folder = directory.path().join("mirror");
+    let mut store = MemoStoreV2 {
+        history: vec![memo("a", "Groceries\nmilk"), memo("b", "Old idea")],
+        ..MemoStoreV2::default()
+    };
+    sync_plain_mirror(
+        &mut store,
+        &options(&folder, MirrorFormat::Markdown, 1),
+        &AtomicFileCommit,
+    )
+    .expect("mirror must succeed");
+    fs::write(folder.join("notes.txt"), "kept").expect("unrelated file must be written");
+
+    // When
+    let removed = remove_plain_mirror(&folder).expect("mirror must be removed");
+    let again = remove_plain_mirror(&folder).expect("removing twice must succeed");
+
+    // Then
+    assert_eq!(removed.len(), 2);
+    assert!(again.is_empty());
+    assert!(!folder.join("Groceries.md").exists());
+    assert!(!folder.join("Old idea.md").exists());
+    assert!(!folder.join(".upmemo-mirror.json").exists());
+    assert!(folder.join("notes.txt").exists());
+}
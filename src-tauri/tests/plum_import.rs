@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+use upmemo::memo_import::{apply_import, ImportFailureKind};
+use upmemo::memo_store::MemoStoreV2;
+use upmemo::plum_import::prepare_plum_import;
+
+const NOTE_SCHEMA: &str = "CREATE TABLE Note (
+    Text TEXT, WindowPosition TEXT, IsOpen INTEGER, IsAlwaysOnTop INTEGER,
+    CreationNoteIdAnchor TEXT, Theme TEXT, IsFutureNote INTEGER, RemoteId TEXT,
+    ChangeKey TEXT, LastServerVersion TEXT, RemoteSchemaVersion INTEGER,
+    IsRemoteDataInvalid INTEGER, PendingInsightsScan INTEGER, Type TEXT,
+    Id TEXT PRIMARY KEY, ParentId TEXT, CreatedAt INTEGER, DeletedAt INTEGER,
+    UpdatedAt INTEGER
+)";
+
+type FixtureNote<'a> = (&'a str, &'a str, &'a str, Option<i64>, Option<i64>);
+
+#[test]
+fn exported_database_imports_formatted_notes_with_ticks_and_theme_colors() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let database = directory.path().join("plum.sqlite");
+    write_fixture(
+        &database,
+        &[
+            (
+                "note-1",
+                "\\id=2d3b Shopping \\b milk\\b0  and \\i eggs\\i0\n\\id=9f1c C:\\Temp stays",
+                "Pink",
+                Some(638_000_000_000_000_000),
+                None,
+            ),
+            (
+                "note-2",
+                "\\id=77aa Old idea",
+                "Charcoal",
+                Some(637_000_000_000_000_000),
+                Some(637_100_000_000_000_000),
+            ),
+            ("note-3", "\\id=0000 ", "Yellow", None, None),
+        ],
+    );
+    let original = fs::read(&database).expect("fixture must be readable");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(&mut store, prepare_plum_import(&database));
+
+    // Then
+    assert!(report.failures.is_empty(), "{:?}", report.failures);
+    assert_eq!(report.imported.len(), 2);
+    assert_eq!(store.history.len(), 1);
+    assert_eq!(store.history[0].id, "note-1");
+    assert_eq!(
+        store.history[0].content,
+        "Shopping **milk** and *eggs*\nC:\\Temp stays\n\n#粉紅色"
+    );
+    assert_eq!(store.history[0].timestamp, 1_664_403_200);
+    assert_eq!(store.trash.len(), 1);
+    assert_eq!(store.trash[0].content, "Old idea\n\n#炭黑色");
+    assert_eq!(
+        fs::read(&database).expect("fixture must stay readable"),
+        original
+    );
+}
+
+#[test]
+fn files_without_a_note_table_are_reported_as_invalid_databases() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let empty = directory.path().join("empty.sqlite");
+    Connection::open(&empty)
+        .and_then(|connection| connection.execute_batch("CREATE TABLE Other (Id TEXT)"))
+        .expect("empty database must be created");
+    let garbage = directory.path().join("garbage.sqlite");
+    fs::write(&garbage, b"not a database at all, just text").expect("garbage must be written");
+
+    // When
+    let missing_table = prepare_plum_import(&empty);
+    let not_sqlite = prepare_plum_import(&garbage);
+    let missing_file = prepare_plum_import(&directory.path().join("absent.sqlite"));
+
+    // Then
+    assert!(missing_table.memos.is_empty());
+    assert_eq!(
+        missing_table.failures[0].kind,
+        ImportFailureKind::InvalidDatabase
+    );
+    assert_eq!(
+        not_sqlite.failures[0].kind,
+        ImportFailureKind::InvalidDatabase
+    );
+    assert_eq!(missing_file.failures[0].kind, ImportFailureKind::Unreadable);
+}
+
+fn write_fixture(path: &Path, notes: &[FixtureNote<'_>]) {
+    let connection = Connection::open(path).expect("fixture database must open");
+    connection
+        .execute_batch(NOTE_SCHEMA)
+        .expect("fixture schema must be created");
+    for (id, text, theme, updated_at, deleted_at) in notes {
+        connection
+            .execute(
+                "INSERT INTO Note (Id, Text, Theme, CreatedAt, UpdatedAt, DeletedAt)
+                 VALUES (?1, ?2, ?3, ?4, ?4, ?5)",
+                params![id, text, theme, updated_at, deleted_at],
+            )
+            .expect("fixture note must be inserted");
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+use upmemo::memo_store::{EncryptionError, KdfParams, StoreKey};
+use upmemo::models::MemoEntry;
+use upmemo::private_memo::{
+    is_private, key_for, open_content, seal_content, PrivateMemoSessions, PRIVATE_MEMO_PREFIX,
+};
+
+#[test]
+fn private_content_needs_its_own_password() {
+    // Given
+    let key = key("diary");
+    let sealed = seal_content(&key, "bank pin 1234").expect("content must seal");
+
+    // When
+    let wrong = key_for("store passphrase", &sealed);
+    let reopened = key_for("diary", &sealed).expect("memo password must derive the key");
+
+    // Then
+    assert!(is_private(&sealed));
+    assert!(!sealed.contains("bank pin"));
+    assert!(matches!(wrong, Err(EncryptionError::Decrypt)));
+    assert_eq!(
+        open_content(&reopened, &sealed).expect("content must open"),
+        "bank pin 1234"
+    );
+}
+
+#[test]
+fn unlocked_memos_relock_after_idle_timeout() {
+    // Given
+    let start = Instant::now();
+    let sessions = PrivateMemoSessions::new(Duration::from_secs(60));
+    let key = key("diary");
+    let entry = MemoEntry {
+        id: "1700000000".to_string(),
+        content: seal_content(&key, "bank pin 1234").expect("content must seal"),
+        timestamp: 1_700_000_000,
+    };
+    let locked_preview = sessions.reveal(entry.clone(), start);
+    sessions.unlock(&entry.id, key, start);
+
+    // When
+    let unlocked_preview = sessions.reveal(entry.clone(), start + Duration::from_secs(59));
+    let still_active = sessions.expire(start + Duration::from_secs(59));
+    let expired = sessions.expire(start + Duration::from_secs(60));
+    let relocked_preview = sessions.reveal(entry.clone(), start + Duration::from_secs(200));
+
+    // Then
+    assert_eq!(locked_preview.content, PRIVATE_MEMO_PREFIX);
+    assert_eq!(unlocked_preview.content, "bank pin 1234");
+    assert!(still_active.is_empty());
+    assert_eq!(expired, vec![entry.id.clone()]);
+    assert_eq!(relocked_preview.content, PRIVATE_MEMO_PREFIX);
+    assert!(sessions.key(&entry.id, start).is_none());
+}
+
+#[test]
+fn expiring_sessions_are_announced_once_until_the_key_is_used_again() {
+    // Given
+    let start = Instant::now();
+    let sessions = PrivateMemoSessions::new(Duration::from_secs(300));
+    sessions.unlock("1700000000", key("diary"), start);
+    let warning = Duration::from_secs(60);
+
+    // When
+    let early = sessions.expiring(start + Duration::from_secs(200), warning);
+    let announced = sessions.expiring(start + Duration::from_secs(250), warning);
+    let repeated = sessions.expiring(start + Duration::from_secs(260), warning);
+    let kept = sessions.key("1700000000", start + Duration::from_secs(270));
+    let quiet_after_use = sessions.expiring(start + Duration::from_secs(300), warning);
+    let announced_again = sessions.expiring(start + Duration::from_secs(520), warning);
+
+    // Then
+    assert!(early.is_empty());
+    assert_eq!(announced, vec!["1700000000".to_string()]);
+    assert!(repeated.is_empty());
+    assert!(kept.is_some());
+    assert!(quiet_after_use.is_empty());
+    assert_eq!(announced_again, vec!["1700000000".to_string()]);
+}
+
+fn key(password: &str) -> StoreKey {
+    StoreKey::derive(password, KdfParams::new(8, 1, 1)).expect("test key must derive")
+}
@@ -0,0 +1,150 @@
+use std::fs;
+use std::path::Path;
+
+use upmemo::draft_journal::DraftJournal;
+use upmemo::edit_session::EditSessionToken;
+use upmemo::memo_store::AtomicFileCommit;
+use upmemo::models::MemoEntry;
+use upmemo::shred::{shred_backups, shred_temporary_files, MemoRemnant, ShredAction};
+
+#[test]
+fn shredding_rewrites_shared_snapshots_and_prunes_single_memo_ones() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let backups = directory.path().join("backups");
+    let shared = backups.join("v1").join("v1-migration-100");
+    let lone = backups.join("v1").join("v1-migration-200");
+    write_snapshot(
+        &shared,
+        &[
+            entry("secret", "password: hunter2"),
+            entry("keep", "groceries"),
+        ],
+        "password: hunter2",
+    );
+    write_snapshot(&lone, &[entry("secret", "password: hunter2")], "");
+    let contents = vec!["password: hunter2".to_string()];
+    let remnant = MemoRemnant {
+        id: "secret",
+        contents: &contents,
+    };
+
+    // When
+    let touched =
+        shred_backups(&backups, &remnant, &AtomicFileCommit).expect("backups must be shredded");
+
+    // Then
+    let history = fs::read_to_string(shared.join("history.json")).expect("history must remain");
+    let manifest = fs::read_to_string(shared.join("manifest.json")).expect("manifest must remain");
+    assert!(!history.contains("hunter2"));
+    assert!(history.contains("groceries"));
+    assert_eq!(
+        fs::read_to_string(shared.join("note.txt")).expect("note must remain"),
+        ""
+    );
+    assert!(manifest.contains(&format!("\"bytes\": {}", history.len())));
+    assert!(!lone.exists());
+    assert!(touched
+        .iter()
+        .any(|file| file.path == shared.join("history.json")
+            && file.action == ShredAction::Rewritten));
+    assert!(touched
+        .iter()
+        .any(|file| file.path == lone.join("history.json") && file.action == ShredAction::Deleted));
+}
+
+#[test]
+fn stale_temporary_files_and_drafts_of_the_memo_are_removed() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let stale = directory.path().join(".tmpA1b2C3");
+    let unrelated = directory.path().join(".tmpZ9y8X7");
+    fs::write(&stale, "{\"current_content\": \"password: hunter2\"}")
+        .expect("stale temp fixture must be written");
+    fs::write(&unrelated, "groceries").expect("unrelated temp fixture must be written");
+    let journal = DraftJournal::new(directory.path().join("drafts"));
+    journal
+        .append(
+            &EditSessionToken::new(),
+            Some("secret"),
+            "password: hunter2",
+        )
+        .expect("secret draft must be written");
+    journal
+        .append(&EditSessionToken::new(), Some("keep"), "groceries")
+        .expect("unrelated draft must be written");
+    let contents = vec!["password: hunter2".to_string()];
+    let remnant = MemoRemnant {
+        id: "secret",
+        contents: &contents,
+    };
+
+    // When
+    let temporary = shred_temporary_files(directory.path(), &remnant, &AtomicFileCommit)
+        .expect("temporary files must be scanned");
+    let drafts = journal
+        .purge_memo("secret", &contents)
+        .expect("drafts must be purged");
+
+    // Then
+    assert_eq!(temporary.len(), 1);
+    assert_eq!(temporary[0].path, stale);
+    assert!(!stale.exists());
+    assert!(unrelated.exists());
+    assert_eq!(drafts.len(), 1);
+    let remaining = journal.recover().expect("drafts must be readable");
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].memo_id.as_deref(), Some("keep"));
+}
+
+#[test]
+fn drafts_typed_before_the_first_save_are_purged_by_content() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let journal = DraftJournal::new(directory.path().join("drafts"));
+    journal
+        .append(&EditSessionToken::new(), None, "milk\n")
+        .expect("unsaved copy of the memo must be written");
+    journal
+        .append(&EditSessionToken::new(), None, "buy milk and eggs")
+        .expect("unrelated unsaved draft must be written");
+    let contents = vec!["milk".to_string()];
+
+    // When
+    let drafts = journal
+        .purge_memo("secret", &contents)
+        .expect("drafts must be purged");
+
+    // Then
+    assert_eq!(drafts.len(), 1);
+    let remaining = journal.recover().expect("drafts must be readable");
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].content, "buy milk and eggs");
+}
+
+fn write_snapshot(directory: &Path, history: &[MemoEntry], note: &str) {
+    fs::create_dir_all(directory).expect("snapshot directory must be created");
+    let history = serde_json::to_vec_pretty(history).expect("history must serialize");
+    fs::write(directory.join("history.json"), &history).expect("history must be written");
+    fs::write(directory.join("note.txt"), note).expect("note must be written");
+    let manifest = serde_json::json!({
+        "migrated_at_unix": 100,
+        "files": [
+            { "filename": "history.json", "bytes": history.len(), "sha256": "stale" },
+            { "filename": "note.txt", "bytes": note.len(), "sha256": "stale" },
+        ],
+    });
+    fs::write(
+        directory.join("manifest.json"),
+        serde_json::to_vec_pretty(&manifest).expect("manifest must serialize"),
+    )
+    .expect("manifest must be written");
+}
+
+fn entry(id: &str, content: &str) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp: 100,
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use upmemo::shutdown_state::{ShutdownState, SHUTDOWN_FLUSH_TIMEOUT};
+
+#[test]
+fn exit_requests_wait_for_the_shutdown_handshake() {
+    // Given
+    let main_source = include_str!("../src/main.rs");
+
+    // When
+    let handles_exit_requested = main_source.contains("tauri::RunEvent::ExitRequested");
+
+    // Then
+    assert!(handles_exit_requested);
+    assert!(main_source.contains("api.prevent_exit()"));
+    assert!(main_source.contains("shutdown::request_shutdown(app_handle)"));
+    assert!(main_source.contains("shutdown::acknowledge_autosave_flush"));
+}
+
+#[test]
+fn tray_quit_goes_through_the_shutdown_handshake() {
+    // Given
+    let tray_source = include_str!("../src/tray.rs");
+
+    // When
+    let quits_through_handshake =
+        tray_source.contains("\"quit\" => shutdown::request_shutdown(app)");
+
+    // Then
+    assert!(quits_through_handshake);
+    assert!(!tray_source.contains("app.exit(0)"));
+}
+
+#[test]
+fn handshake_flushes_the_repository_before_exiting() {
+    // Given
+    let shutdown_source = include_str!("../src/shutdown.rs");
+    let state_source = include_str!("../src/shutdown_state.rs");
+
+    // When
+    let flush_position = shutdown_source.find("store_flush::flush_memo_store(&app_handle)");
+    let exit_position = shutdown_source.find("app_handle.exit(0)");
+
+    // Then
+    assert!(flush_position.is_some());
+    assert!(exit_position.is_some());
+    assert!(flush_position < exit_position);
+    assert!(state_source.contains("wait_timeout_while"));
+}
+
+#[test]
+fn acknowledged_flush_ends_the_wait_and_completes_once() {
+    // Given
+    let state = Arc::new(ShutdownState::default());
+    let began = state.begin();
+    let began_again = state.begin();
+    let flushing = state.is_flushing();
+    let frontend = Arc::clone(&state);
+    let acknowledger = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        frontend.acknowledge();
+    });
+
+    // When
+    let started = Instant::now();
+    let acknowledged = state.wait_for_acknowledgement(SHUTDOWN_FLUSH_TIMEOUT);
+    let waited = started.elapsed();
+    acknowledger
+        .join()
+        .expect("acknowledging thread must finish");
+    let complete_before = state.is_complete();
+    state.complete();
+
+    // Then
+    assert!(began);
+    assert!(!began_again);
+    assert!(flushing);
+    assert!(acknowledged);
+    assert!(waited < SHUTDOWN_FLUSH_TIMEOUT);
+    assert!(!complete_before);
+    assert!(state.is_complete());
+    assert!(!state.is_flushing());
+}
+
+#[test]
+fn unacknowledged_flush_gives_up_after_the_timeout() {
+    // Given
+    let state = ShutdownState::default();
+    state.begin();
+
+    // When
+    let started = Instant::now();
+    let acknowledged = state.wait_for_acknowledgement(SHUTDOWN_FLUSH_TIMEOUT);
+    let waited = started.elapsed();
+
+    // Then
+    assert_eq!(SHUTDOWN_FLUSH_TIMEOUT, Duration::from_millis(1500));
+    assert!(!acknowledged);
+    assert!(waited >= SHUTDOWN_FLUSH_TIMEOUT);
+    assert!(!state.is_complete());
+}
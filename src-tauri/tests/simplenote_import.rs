@@ -0,0 +1,99 @@
+use std::fs;
+
+use upmemo::memo_import::{apply_import, preview_import};
+use upmemo::memo_store::{MemoCollection, MemoStoreV2};
+use upmemo::models::MemoEntry;
+use upmemo::simplenote_import::prepare_simplenote_import;
+
+const EXPORT: &str = r##"{
+  "activeNotes": [
+    {"id": "a1", "content": "Reading list\r\nDune", "creationDate": "2019-01-01T00:00:00.000Z",
+     "lastModified": "2020-09-13T12:26:40.000Z", "tags": ["books", "to read"], "pinned": true},
+    {"id": "b2", "content": "# Plan\n\n- **pack**\n- go", "markdown": true,
+     "lastModified": "2020-09-13T12:26:41.000Z"}
+  ],
+  "trashedNotes": [
+    {"id": "c3", "content": "old draft", "lastModified": "2018-01-01T00:00:00.000Z", "deleted": true}
+  ]
+}"##;
+
+#[test]
+fn export_maps_tags_pinned_markdown_and_trashed_notes() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    fs::create_dir_all(directory.path().join("source")).expect("source directory must exist");
+    fs::write(directory.path().join("source/notes.json"), EXPORT)
+        .expect("export fixture must be written");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(&mut store, prepare_simplenote_import(directory.path()));
+
+    // Then
+    assert!(report.failures.is_empty(), "{:?}", report.failures);
+    let history: Vec<(&str, &str, u64)> = store
+        .history
+        .iter()
+        .map(|entry| (entry.id.as_str(), entry.content.as_str(), entry.timestamp))
+        .collect();
+    assert_eq!(
+        history,
+        vec![
+            ("b2", "Plan\n\n- pack\n- go", 1_600_000_001),
+            (
+                "a1",
+                "Reading list\nDune\n\n#置頂 #books #to_read",
+                1_600_000_000
+            ),
+        ]
+    );
+    assert_eq!(store.trash.len(), 1);
+    assert_eq!(store.trash[0].id, "c3");
+    assert_eq!(store.trash[0].timestamp, 1_514_764_800);
+}
+
+#[test]
+fn preview_lists_what_would_change_without_touching_the_store() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let export = directory.path().join("notes.json");
+    fs::write(&export, EXPORT).expect("export fixture must be written");
+    let store = MemoStoreV2 {
+        archive: vec![MemoEntry {
+            id: "c3".to_string(),
+            content: "local copy".to_string(),
+            timestamp: 1,
+        }],
+        ..MemoStoreV2::default()
+    };
+    let before = store.clone();
+    let prepared = prepare_simplenote_import(&export);
+
+    // When
+    let preview = preview_import(&store, &prepared);
+
+    // Then
+    assert_eq!(store, before);
+    let summary: Vec<(&str, &str, MemoCollection, bool)> = preview
+        .memos
+        .iter()
+        .map(|memo| {
+            (
+                memo.id.as_str(),
+                memo.title.as_str(),
+                memo.collection,
+                memo.replaces_existing,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("a1", "Reading list", MemoCollection::History, false),
+            ("b2", "Plan", MemoCollection::History, false),
+            ("c3", "old draft", MemoCollection::Trash, true),
+        ]
+    );
+    let missing = prepare_simplenote_import(&directory.path().join("absent"));
+    assert_eq!(missing.failures.len(), 1);
+}
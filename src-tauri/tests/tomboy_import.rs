@@ -0,0 +1,92 @@
+use std::fs;
+
+use upmemo::memo_import::{apply_import, ImportFailureKind};
+use upmemo::memo_store::{MemoCollection, MemoStoreV2};
+use upmemo::tomboy_import::prepare_tomboy_import;
+
+const NOTE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<note version="0.3" xmlns:link="http://beatniksoftware.com/tomboy/link" xmlns:size="http://beatniksoftware.com/tomboy/size" xmlns="http://beatniksoftware.com/tomboy">
+  <title>Trip &amp; plans</title>
+  <text xml:space="preserve"><note-content version="0.1">Trip &amp; plans
+
+Pack <bold>passport</bold> and <italic>maps</italic>, see <link:internal>Packing</link:internal>
+<list><list-item dir="ltr">tent
+</list-item><list-item dir="ltr">stove
+<list><list-item dir="ltr">fuel</list-item></list></list-item></list>Done</note-content></text>
+  <last-change-date>2010-01-27T12:00:00.0000000+01:00</last-change-date>
+  <last-metadata-change-date>2010-01-28T12:00:00.0000000+01:00</last-metadata-change-date>
+  <create-date>2009-12-01T08:30:00.0000000-05:00</create-date>
+  <tags>
+    <tag>system:notebook:Summer Holidays</tag>
+    <tag>camping</tag>
+    <tag>system:pinned</tag>
+  </tags>
+</note>
+"#;
+
+#[test]
+fn notes_keep_title_formatting_lists_notebooks_and_change_dates() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    fs::write(
+        directory
+            .path()
+            .join("3c1d2a58-1f3a-4c21-9b1b-6f1e8f9b0a11.note"),
+        NOTE,
+    )
+    .expect("note fixture must be written");
+    fs::write(
+        directory.path().join("template.note"),
+        NOTE.replace("<tag>camping</tag>", "<tag>system:template</tag>"),
+    )
+    .expect("template fixture must be written");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(&mut store, prepare_tomboy_import(directory.path()));
+
+    // Then
+    assert!(report.failures.is_empty(), "{:?}", report.failures);
+    assert_eq!(report.imported.len(), 1);
+    assert_eq!(report.imported[0].collection, MemoCollection::History);
+    assert_eq!(store.history.len(), 1);
+    assert_eq!(store.history[0].id, "3c1d2a58-1f3a-4c21-9b1b-6f1e8f9b0a11");
+    assert_eq!(store.history[0].timestamp, 1_264_590_000);
+    assert_eq!(
+        store.history[0].content,
+        "Trip & plans\n\nPack **passport** and *maps*, see Packing\n- tent\n- stove\n  - fuel\nDone\n\n#Summer_Holidays #camping"
+    );
+}
+
+#[test]
+fn untitled_bodies_get_their_title_and_malformed_notes_are_reported() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    fs::write(
+        directory.path().join("plain.note"),
+        r#"<note version="0.3"><title>Groceries</title><text><note-content>milk</note-content></text><create-date>2009-12-01T08:30:00Z</create-date></note>"#,
+    )
+    .expect("note fixture must be written");
+    fs::write(
+        directory.path().join("broken.note"),
+        "<note><title>Broken</title><text></note>",
+    )
+    .expect("broken fixture must be written");
+    fs::write(directory.path().join("readme.txt"), "ignored").expect("text must be written");
+    let mut store = MemoStoreV2::default();
+
+    // When
+    let report = apply_import(&mut store, prepare_tomboy_import(directory.path()));
+
+    // Then
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].kind, ImportFailureKind::InvalidXml);
+    assert_eq!(
+        report.failures[0].path,
+        directory.path().join("broken.note")
+    );
+    assert_eq!(store.history.len(), 1);
+    assert_eq!(store.history[0].id, "plain");
+    assert_eq!(store.history[0].content, "Groceries\n\nmilk");
+    assert_eq!(store.history[0].timestamp, 1_259_656_200);
+}
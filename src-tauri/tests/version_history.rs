@@ -0,0 +1,128 @@
+use upmemo::memo_store::MemoStoreV2;
+use upmemo::models::MemoEntry;
+use upmemo::version_history::{MemoRevisionKind, VersionHistory};
+
+fn memo(id: &str, content: &str, timestamp: u64) -> MemoEntry {
+    MemoEntry {
+        id: id.to_string(),
+        content: content.to_string(),
+        timestamp,
+    }
+}
+
+#[test]
+fn recorded_changes_become_commits_with_memo_revisions_and_blame() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let history = VersionHistory::open(&directory.path().join("history.git"))
+        .expect("repository must be initialized");
+    let mut store = MemoStoreV2 {
+        history: vec![memo("a", "line one\nline two", 100)],
+        ..MemoStoreV2::default()
+    };
+
+    // When
+    let first = history
+        .record(&store, "first", 100)
+        .expect("first snapshot must be recorded");
+    let unchanged = history
+        .record(&store, "same", 150)
+        .expect("unchanged snapshot must be checked");
+    store.history[0].content = "line one\nline 2".to_string();
+    store.history.insert(0, memo("b", "temporary", 200));
+    history
+        .record(&store, "second", 200)
+        .expect("second snapshot must be recorded");
+    store.history.remove(0);
+    history
+        .record(&store, "third", 300)
+        .expect("third snapshot must be recorded");
+
+    // Then
+    assert!(first.is_some());
+    assert!(unchanged.is_none());
+    let log: Vec<(String, u64)> = history
+        .log(10)
+        .expect("log must be read")
+        .into_iter()
+        .map(|commit| (commit.message, commit.timestamp))
+        .collect();
+    assert_eq!(
+        log,
+        vec![
+            ("third".to_string(), 300),
+            ("second".to_string(), 200),
+            ("first".to_string(), 100)
+        ]
+    );
+    let revisions = |id: &str| -> Vec<(MemoRevisionKind, u64)> {
+        history
+            .memo_revisions(id)
+            .expect("revisions must be read")
+            .into_iter()
+            .map(|revision| (revision.kind, revision.commit.timestamp))
+            .collect()
+    };
+    assert_eq!(
+        revisions("a"),
+        vec![
+            (MemoRevisionKind::Modified, 200),
+            (MemoRevisionKind::Added, 100)
+        ]
+    );
+    assert_eq!(
+        revisions("b"),
+        vec![
+            (MemoRevisionKind::Removed, 300),
+            (MemoRevisionKind::Added, 200)
+        ]
+    );
+    let blame: Vec<(String, u64)> = history
+        .blame_memo("a")
+        .expect("blame must be computed")
+        .into_iter()
+        .map(|line| (line.text, line.timestamp))
+        .collect();
+    assert_eq!(
+        blame,
+        vec![("line one".to_string(), 100), ("line 2".to_string(), 200)]
+    );
+    assert!(history.blame_memo("b").is_err());
+}
+
+#[test]
+fn restore_rebuilds_every_collection_from_an_earlier_commit() {
+    // Given
+    let directory = tempfile::tempdir().expect("temp directory must be created");
+    let history = VersionHistory::open(directory.path()).expect("repository must be initialized");
+    let original = MemoStoreV2 {
+        current_memo_id: Some("Trip plan/2024".to_string()),
+        current_content: "draft".to_string(),
+        history: vec![
+            memo("Trip plan/2024", "pack tent", 10),
+            memo("plain-id", "second", 5),
+        ],
+        archive: vec![memo("old", "archived", 3)],
+        trash: vec![memo("gone", "deleted", 1)],
+        ..MemoStoreV2::default()
+    };
+    let commit = history
+        .record(&original, "original", 10)
+        .expect("original must be recorded")
+        .expect("original must create a commit");
+    let mut edited = original.clone();
+    edited.history.clear();
+    edited.current_content = "changed".to_string();
+    history
+        .record(&edited, "edited", 20)
+        .expect("edit must be recorded");
+
+    // When
+    let restored = history
+        .restore(&commit.id[..8], &edited)
+        .expect("short commit id must restore");
+
+    // Then
+    assert_eq!(restored, original);
+    assert!(history.restore("not-a-commit", &edited).is_err());
+}
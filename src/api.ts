@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { MemoEntry, FontConfig } from "./types";
+import type { AppLockStatus, BlameLine, BundleImportMode, BundleImportReport, BundleManifest, MarkdownExportReport, ImportPreview, ImportReport, MemoCollectionName, MemoEntry, PlainMirrorConfig, PlainMirrorFormat, PlainMirrorReport, SavedNote, ShredReport, FontConfig, HistoryCommit, MemoRevision, MemoStoreEncoding, RecoveredDraft, SyncReport, SyncStatus, VersionHistoryStatus } from "./types";
 
 // 後端 API 調用
 
@@ -12,14 +12,39 @@ export async function saveNote(content: string): Promise<void> {
   await invoke("save_note", { content });
 }
 
-export async function saveNoteToHistory(content: string): Promise<void> {
-  await invoke("save_note_to_history", { content });
+export async function saveNoteToHistory(content: string, autoLock: boolean): Promise<SavedNote> {
+  return await invoke<SavedNote>("save_note_to_history", { content, autoLock });
+}
+
+export async function declineSecretAutoLock(id: string): Promise<void> {
+  await invoke("decline_secret_auto_lock", { id });
 }
 
 export async function createNewMemo(): Promise<string> {
   return await invoke<string>("create_new_memo");
 }
 
+export async function getCurrentMemoId(): Promise<string | null> {
+  return await invoke<string | null>("get_current_memo_id");
+}
+
+export async function acknowledgeAutosaveFlush(): Promise<void> {
+  await invoke("acknowledge_autosave_flush");
+}
+
+// 草稿日誌
+export async function recordDraft(content: string): Promise<void> {
+  await invoke("record_draft", { content });
+}
+
+export async function getRecoverableDraft(): Promise<RecoveredDraft | null> {
+  return await invoke<RecoveredDraft | null>("get_recoverable_draft");
+}
+
+export async function resolveRecoveredDrafts(recover: boolean): Promise<string | null> {
+  return await invoke<string | null>("resolve_recovered_drafts", { recover });
+}
+
 // 歷史記錄操作
 export async function getHistory(): Promise<MemoEntry[]> {
   return await invoke<MemoEntry[]>("get_history");
@@ -63,10 +88,105 @@ export async function permanentlyDeleteTrashItem(id: string): Promise<void> {
   await invoke("permanently_delete_trash_item", { id });
 }
 
+export async function shredMemo(id: string): Promise<ShredReport> {
+  return await invoke<ShredReport>("shred_memo", { id });
+}
+
 export async function emptyTrash(): Promise<void> {
   await invoke("empty_trash");
 }
 
+// 儲存格式
+export async function getMemoStoreEncoding(): Promise<MemoStoreEncoding> {
+  return await invoke<MemoStoreEncoding>("get_memo_store_encoding");
+}
+
+export async function setMemoStoreEncoding(encoding: MemoStoreEncoding): Promise<boolean> {
+  return await invoke<boolean>("set_memo_store_encoding", { encoding });
+}
+
+export async function exportMemoStoreJson(path: string): Promise<void> {
+  await invoke("export_memo_store_json", { path });
+}
+
+// PIN 鎖定
+export async function getAppLockStatus(): Promise<AppLockStatus> {
+  return await invoke<AppLockStatus>("get_app_lock_status");
+}
+
+export async function reportAppActivity(): Promise<void> {
+  await invoke("report_app_activity");
+}
+
+export async function lockApp(): Promise<void> {
+  await invoke("lock_app");
+}
+
+export async function unlockApp(pin: string): Promise<void> {
+  await invoke("unlock_app", { pin });
+}
+
+export async function setAppLockPin(currentPin: string | null, newPin: string): Promise<void> {
+  await invoke("set_app_lock_pin", { currentPin, newPin });
+}
+
+export async function disableAppLock(pin: string): Promise<void> {
+  await invoke("disable_app_lock", { pin });
+}
+
+export async function setAppLockIdleTimeout(idleTimeoutSecs: number): Promise<void> {
+  await invoke("set_app_lock_idle_timeout", { idleTimeoutSecs });
+}
+
+// 私人記事
+export async function lockMemo(id: string, password: string, content?: string): Promise<void> {
+  await invoke("lock_memo", { id, password, content: content ?? null });
+}
+
+export async function unlockMemo(id: string, password: string): Promise<void> {
+  await invoke("unlock_memo", { id, password });
+}
+
+export async function relockMemo(id: string): Promise<void> {
+  await invoke("relock_memo", { id });
+}
+
+export async function keepPrivateMemosUnlocked(ids: string[]): Promise<string[]> {
+  return await invoke<string[]>("keep_private_memos_unlocked", { ids });
+}
+
+export async function removeMemoLock(id: string, password: string): Promise<void> {
+  await invoke("remove_memo_lock", { id, password });
+}
+
+// 記事加密
+export async function isMemoStoreLocked(): Promise<boolean> {
+  return await invoke<boolean>("is_memo_store_locked");
+}
+
+export async function isMemoStoreEncrypted(): Promise<boolean> {
+  return await invoke<boolean>("is_memo_store_encrypted");
+}
+
+export async function unlockMemoStore(passphrase: string): Promise<void> {
+  await invoke("unlock_memo_store", { passphrase });
+}
+
+export async function enableMemoStoreEncryption(passphrase: string): Promise<void> {
+  await invoke("enable_memo_store_encryption", { passphrase });
+}
+
+export async function changeMemoStorePassphrase(
+  currentPassphrase: string,
+  newPassphrase: string,
+): Promise<void> {
+  await invoke("change_memo_store_passphrase", { currentPassphrase, newPassphrase });
+}
+
+export async function disableMemoStoreEncryption(passphrase: string): Promise<void> {
+  await invoke("disable_memo_store_encryption", { passphrase });
+}
+
 // 字體操作
 export async function getSystemFonts(): Promise<string[]> {
   return await invoke<string[]>("get_system_fonts");
@@ -92,3 +212,116 @@ export async function setPreviewImageData(data: string): Promise<void> {
 export async function takePreviewImageData(): Promise<string> {
   return await invoke<string>("take_preview_image_data");
 }
+
+// Markdown 匯出
+export async function exportMarkdown(
+  path: string,
+  collections: MemoCollectionName[],
+): Promise<MarkdownExportReport> {
+  return await invoke<MarkdownExportReport>("export_markdown", { path, collections });
+}
+
+export async function importMarkdown(path: string): Promise<ImportReport> {
+  return await invoke<ImportReport>("import_markdown", { path });
+}
+
+// 從其他筆記程式匯入
+export async function importTomboy(path: string): Promise<ImportReport> {
+  return await invoke<ImportReport>("import_tomboy", { path });
+}
+
+export async function importPlumDatabase(path: string): Promise<ImportReport> {
+  return await invoke<ImportReport>("import_plum_database", { path });
+}
+
+export async function previewKeepImport(path: string): Promise<ImportPreview> {
+  return await invoke<ImportPreview>("preview_keep_import", { path });
+}
+
+export async function importKeep(path: string): Promise<ImportReport> {
+  return await invoke<ImportReport>("import_keep", { path });
+}
+
+export async function previewSimplenoteImport(path: string): Promise<ImportPreview> {
+  return await invoke<ImportPreview>("preview_simplenote_import", { path });
+}
+
+export async function importSimplenote(path: string): Promise<ImportReport> {
+  return await invoke<ImportReport>("import_simplenote", { path });
+}
+
+export async function importEnex(path: string): Promise<ImportReport> {
+  return await invoke<ImportReport>("import_enex", { path });
+}
+
+// 鏡像資料夾
+export async function getPlainMirror(): Promise<PlainMirrorConfig | null> {
+  return await invoke<PlainMirrorConfig | null>("get_plain_mirror");
+}
+
+export async function enablePlainMirror(folder: string, format: PlainMirrorFormat): Promise<PlainMirrorReport> {
+  return await invoke<PlainMirrorReport>("enable_plain_mirror", { folder, format });
+}
+
+export async function disablePlainMirror(): Promise<void> {
+  await invoke("disable_plain_mirror");
+}
+
+export async function syncPlainMirrorNow(): Promise<PlainMirrorReport> {
+  return await invoke<PlainMirrorReport>("sync_plain_mirror_now");
+}
+
+// 版本歷史
+export async function getVersionHistoryStatus(): Promise<VersionHistoryStatus> {
+  return await invoke<VersionHistoryStatus>("get_version_history_status");
+}
+
+export async function enableVersionHistory(): Promise<HistoryCommit | null> {
+  return await invoke<HistoryCommit | null>("enable_version_history");
+}
+
+export async function disableVersionHistory(): Promise<void> {
+  await invoke("disable_version_history");
+}
+
+export async function listVersionHistory(limit?: number): Promise<HistoryCommit[]> {
+  return await invoke<HistoryCommit[]>("list_version_history", { limit });
+}
+
+export async function getMemoRevisions(id: string): Promise<MemoRevision[]> {
+  return await invoke<MemoRevision[]>("get_memo_revisions", { id });
+}
+
+export async function blameMemo(id: string): Promise<BlameLine[]> {
+  return await invoke<BlameLine[]>("blame_memo", { id });
+}
+
+export async function restoreVersion(commit: string): Promise<HistoryCommit | null> {
+  return await invoke<HistoryCommit | null>("restore_version", { commit });
+}
+
+// 裝置同步
+export async function getSyncStatus(): Promise<SyncStatus> {
+  return await invoke<SyncStatus>("get_sync_status");
+}
+
+export async function enableFolderSync(folder: string): Promise<SyncReport> {
+  return await invoke<SyncReport>("enable_folder_sync", { folder });
+}
+
+export async function disableSync(): Promise<void> {
+  await invoke("disable_sync");
+}
+
+export async function syncMemosNow(): Promise<SyncReport> {
+  return await invoke<SyncReport>("sync_memos_now");
+}
+
+// .upmemo 完整備份
+export async function exportBundle(path: string): Promise<BundleManifest> {
+  return await invoke<BundleManifest>("export_bundle", { path });
+}
+
+export async function importBundle(path: string, mode: BundleImportMode): Promise<BundleImportReport> {
+  return await invoke<BundleImportReport>("import_bundle", { path, mode });
+}
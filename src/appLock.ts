@@ -0,0 +1,165 @@
+import { listen } from "@tauri-apps/api/event";
+import {
+  disableAppLock,
+  getAppLockStatus,
+  reportAppActivity,
+  setAppLockIdleTimeout,
+  setAppLockPin,
+  unlockApp,
+} from "./api";
+import {
+  appLockError,
+  appLockIdleSelect,
+  appLockOverlay,
+  appLockPinInput,
+  appLockStatus,
+  appUnlockBtn,
+  disableAppLockBtn,
+  noteDisplay,
+  setAppPinBtn,
+} from "./dom";
+import { logError, logInfo } from "./logger";
+
+const APP_LOCKED_EVENT = "app-locked";
+const ACTIVITY_REPORT_INTERVAL_MS = 30_000;
+
+let lastActivityReport = 0;
+let pendingUnlock: (() => void) | null = null;
+
+function showLockOverlay(): void {
+  noteDisplay.innerHTML = "";
+  appLockOverlay?.classList.remove("hidden");
+  if (appLockError) appLockError.textContent = "";
+  if (appLockPinInput) {
+    appLockPinInput.value = "";
+    appLockPinInput.focus();
+  }
+}
+
+async function submitPin(): Promise<void> {
+  try {
+    await unlockApp(appLockPinInput?.value ?? "");
+    appLockOverlay?.classList.add("hidden");
+    logInfo("UpMemo 已解鎖");
+    pendingUnlock?.();
+    pendingUnlock = null;
+  } catch (error) {
+    logError("PIN 解鎖失敗:", error);
+    if (appLockError) appLockError.textContent = `${error}`;
+    if (appLockPinInput) appLockPinInput.value = "";
+  }
+}
+
+function waitForUnlock(): Promise<void> {
+  showLockOverlay();
+  return new Promise((resolve) => {
+    pendingUnlock = resolve;
+  });
+}
+
+// 啟動時若已設定 PIN，等待使用者解鎖
+export async function ensureAppUnlocked(): Promise<void> {
+  const status = await getAppLockStatus();
+  if (status.locked) {
+    await waitForUnlock();
+  }
+}
+
+function reportActivity(): void {
+  const now = Date.now();
+  if (now - lastActivityReport < ACTIVITY_REPORT_INTERVAL_MS) return;
+
+  lastActivityReport = now;
+  reportAppActivity().catch((error: unknown) => {
+    logError("回報使用中狀態失敗:", error);
+  });
+}
+
+async function refreshAppLockSettings(): Promise<void> {
+  const status = await getAppLockStatus();
+  if (appLockStatus) {
+    appLockStatus.textContent = status.enabled ? "已啟用" : "未啟用";
+  }
+  if (setAppPinBtn) {
+    setAppPinBtn.textContent = status.enabled ? "變更 PIN" : "設定 PIN";
+  }
+  if (disableAppLockBtn) {
+    disableAppLockBtn.disabled = !status.enabled;
+  }
+  if (appLockIdleSelect) {
+    appLockIdleSelect.value = String(status.idle_timeout_secs);
+  }
+}
+
+async function changePin(): Promise<void> {
+  const status = await getAppLockStatus();
+  const currentPin = status.enabled ? window.prompt("請輸入目前的 PIN") : null;
+  if (status.enabled && currentPin === null) return;
+
+  const newPin = window.prompt("請輸入新的 PIN（4 到 12 位數字）");
+  if (newPin === null) return;
+  if (window.prompt("請再次輸入新的 PIN") !== newPin) {
+    window.alert("兩次輸入的 PIN 不一致");
+    return;
+  }
+
+  await setAppLockPin(currentPin, newPin);
+  logInfo("PIN 已更新");
+}
+
+async function disablePin(): Promise<void> {
+  const pin = window.prompt("請輸入目前的 PIN 以停用鎖定");
+  if (pin === null) return;
+
+  await disableAppLock(pin);
+  logInfo("已停用 PIN 鎖定");
+}
+
+async function runAppLockAction(action: () => Promise<void>): Promise<void> {
+  try {
+    await action();
+  } catch (error) {
+    logError("PIN 鎖定設定失敗:", error);
+    window.alert(`${error}`);
+  }
+
+  await refreshAppLockSettings().catch((error: unknown) => {
+    logError("讀取 PIN 鎖定狀態失敗:", error);
+  });
+}
+
+// 後端鎖定後清空畫面，解鎖後重新載入內容
+export async function setupAppLockListeners(onUnlocked: () => Promise<void>): Promise<void> {
+  appUnlockBtn?.addEventListener("click", () => {
+    void submitPin();
+  });
+
+  appLockPinInput?.addEventListener("keydown", (event) => {
+    if (event.key === "Enter") {
+      void submitPin();
+    }
+  });
+
+  setAppPinBtn?.addEventListener("click", () => {
+    void runAppLockAction(changePin);
+  });
+
+  disableAppLockBtn?.addEventListener("click", () => {
+    void runAppLockAction(disablePin);
+  });
+
+  appLockIdleSelect?.addEventListener("change", () => {
+    void runAppLockAction(() => setAppLockIdleTimeout(Number(appLockIdleSelect.value)));
+  });
+
+  for (const eventName of ["keydown", "mousedown", "input"]) {
+    document.addEventListener(eventName, reportActivity, { passive: true });
+  }
+
+  await listen(APP_LOCKED_EVENT, () => {
+    logInfo("UpMemo 已鎖定");
+    void waitForUnlock().then(onUnlocked);
+  });
+
+  await refreshAppLockSettings();
+}
@@ -3,6 +3,7 @@ import { getArchive, restoreFromArchive as restoreFromArchiveAPI, permanentlyDel
 import { archivePanel, archiveList, archiveBtn, closeArchiveBtn } from "./dom";
 import { escapeHtml, formatTimestamp } from "./utils";
 import { logError, logInfo } from "./logger";
+import { previewText } from "./privateMemo";
 
 // 顯示封存面板
 export async function showArchive(): Promise<void> {
@@ -32,7 +33,7 @@ function renderArchive(archive: MemoEntry[]): void {
   archiveList.innerHTML = archive
     .map((item) => {
       const timeStr = formatTimestamp(item.timestamp);
-      const preview = item.content.substring(0, 60) + (item.content.length > 60 ? "..." : "");
+      const preview = previewText(item.content);
 
       return `
         <div class="archive-item" data-id="${item.id}">
@@ -0,0 +1,62 @@
+import { exportBundle, importBundle } from "./api";
+import { bundlePathInput, exportBundleBtn, importBundleMergeBtn, importBundleReplaceBtn } from "./dom";
+import { logError, logInfo } from "./logger";
+import type { BundleImportMode } from "./types";
+
+function bundlePath(): string | null {
+  const path = bundlePathInput?.value.trim() ?? "";
+  if (!path) {
+    window.alert("請輸入備份檔路徑");
+    return null;
+  }
+  return path;
+}
+
+async function runExport(): Promise<void> {
+  const path = bundlePath();
+  if (!path) return;
+
+  try {
+    const manifest = await exportBundle(path);
+    logInfo("完整備份已匯出:", manifest.files.length);
+    window.alert(`已匯出 ${manifest.files.length} 個檔案`);
+  } catch (error) {
+    logError("完整備份匯出失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+async function runImport(mode: BundleImportMode): Promise<void> {
+  const path = bundlePath();
+  if (!path) return;
+
+  if (mode === "replace" && !window.confirm("取代匯入會以備份檔覆蓋目前所有記事與設定，確定要繼續嗎？")) {
+    return;
+  }
+
+  try {
+    const report = await importBundle(path, mode);
+    logInfo("完整備份已匯入:", report.summary);
+    const { added, updated, unchanged } = report.summary;
+    window.alert(
+      `匯入完成：新增 ${added}、更新 ${updated}、略過 ${unchanged}\n原資料已備份至 ${report.backup_path}`,
+    );
+    // 重新載入以套用還原的設定與字體
+    window.location.reload();
+  } catch (error) {
+    logError("完整備份匯入失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+export function setupBundleListeners(): void {
+  exportBundleBtn?.addEventListener("click", () => {
+    void runExport();
+  });
+  importBundleMergeBtn?.addEventListener("click", () => {
+    void runImport("merge");
+  });
+  importBundleReplaceBtn?.addEventListener("click", () => {
+    void runImport("replace");
+  });
+}
@@ -50,3 +50,46 @@ export const fontSizeSlider = getOptionalElement("font-size", HTMLInputElement);
 export const fontSizeValue = getOptionalElement("font-size-value", HTMLSpanElement);
 export const opacitySlider = getOptionalElement("opacity", HTMLInputElement);
 export const opacityValue = getOptionalElement("opacity-value", HTMLSpanElement);
+export const encryptionStatus = getOptionalElement("encryption-status", HTMLSpanElement);
+export const toggleEncryptionBtn = getOptionalElement("toggle-encryption-btn", HTMLButtonElement);
+export const changePassphraseBtn = getOptionalElement("change-passphrase-btn", HTMLButtonElement);
+export const appLockOverlay = getOptionalElement("app-lock-overlay", HTMLDivElement);
+export const appLockPinInput = getOptionalElement("app-lock-pin", HTMLInputElement);
+export const appUnlockBtn = getOptionalElement("app-unlock-btn", HTMLButtonElement);
+export const appLockError = getOptionalElement("app-lock-error", HTMLElement);
+export const appLockStatus = getOptionalElement("app-lock-status", HTMLSpanElement);
+export const setAppPinBtn = getOptionalElement("set-app-pin-btn", HTMLButtonElement);
+export const disableAppLockBtn = getOptionalElement("disable-app-lock-btn", HTMLButtonElement);
+export const appLockIdleSelect = getOptionalElement("app-lock-idle", HTMLSelectElement);
+export const markdownFolderInput = getOptionalElement("markdown-folder", HTMLInputElement);
+export const markdownIncludeHistory = getOptionalElement("markdown-include-history", HTMLInputElement);
+export const markdownIncludeArchive = getOptionalElement("markdown-include-archive", HTMLInputElement);
+export const exportMarkdownBtn = getOptionalElement("export-markdown-btn", HTMLButtonElement);
+export const importMarkdownBtn = getOptionalElement("import-markdown-btn", HTMLButtonElement);
+export const bundlePathInput = getOptionalElement("bundle-path", HTMLInputElement);
+export const exportBundleBtn = getOptionalElement("export-bundle-btn", HTMLButtonElement);
+export const importBundleMergeBtn = getOptionalElement("import-bundle-merge-btn", HTMLButtonElement);
+export const importBundleReplaceBtn = getOptionalElement("import-bundle-replace-btn", HTMLButtonElement);
+export const externalImportSourceSelect = getOptionalElement("external-import-source", HTMLSelectElement);
+export const externalImportPathInput = getOptionalElement("external-import-path", HTMLInputElement);
+export const externalImportBtn = getOptionalElement("external-import-btn", HTMLButtonElement);
+export const plainMirrorFormatSelect = getOptionalElement("plain-mirror-format", HTMLSelectElement);
+export const plainMirrorFolderInput = getOptionalElement("plain-mirror-folder", HTMLInputElement);
+export const enablePlainMirrorBtn = getOptionalElement("enable-plain-mirror-btn", HTMLButtonElement);
+export const syncPlainMirrorBtn = getOptionalElement("sync-plain-mirror-btn", HTMLButtonElement);
+export const disablePlainMirrorBtn = getOptionalElement("disable-plain-mirror-btn", HTMLButtonElement);
+export const plainMirrorStatus = getOptionalElement("plain-mirror-status", HTMLElement);
+export const enableVersionHistoryBtn = getOptionalElement("enable-version-history-btn", HTMLButtonElement);
+export const disableVersionHistoryBtn = getOptionalElement("disable-version-history-btn", HTMLButtonElement);
+export const memoRevisionsBtn = getOptionalElement("memo-revisions-btn", HTMLButtonElement);
+export const versionHistoryList = getOptionalElement("version-history-list", HTMLSelectElement);
+export const refreshVersionHistoryBtn = getOptionalElement("refresh-version-history-btn", HTMLButtonElement);
+export const restoreVersionBtn = getOptionalElement("restore-version-btn", HTMLButtonElement);
+export const versionHistoryStatus = getOptionalElement("version-history-status", HTMLElement);
+export const syncFolderInput = getOptionalElement("sync-folder", HTMLInputElement);
+export const enableSyncBtn = getOptionalElement("enable-sync-btn", HTMLButtonElement);
+export const syncNowBtn = getOptionalElement("sync-now-btn", HTMLButtonElement);
+export const disableSyncBtn = getOptionalElement("disable-sync-btn", HTMLButtonElement);
+export const syncStatusText = getOptionalElement("sync-status", HTMLElement);
+export const secretWarning = getOptionalElement("secret-warning", HTMLDivElement);
+export const autoLockSecretsCheckbox = getOptionalElement("auto-lock-secrets", HTMLInputElement);
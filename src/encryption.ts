@@ -0,0 +1,109 @@
+import {
+  changeMemoStorePassphrase,
+  disableMemoStoreEncryption,
+  enableMemoStoreEncryption,
+  isMemoStoreEncrypted,
+  isMemoStoreLocked,
+  unlockMemoStore,
+} from "./api";
+import { changePassphraseBtn, encryptionStatus, toggleEncryptionBtn } from "./dom";
+import { logError, logInfo } from "./logger";
+
+// 記事已加密時要求輸入密碼，取消則維持鎖定
+export async function unlockMemoStoreIfNeeded(): Promise<boolean> {
+  if (!(await isMemoStoreLocked())) {
+    return true;
+  }
+
+  let message = "記事已加密，請輸入密碼";
+  for (;;) {
+    const passphrase = window.prompt(message);
+    if (passphrase === null) {
+      return false;
+    }
+
+    try {
+      await unlockMemoStore(passphrase);
+      logInfo("記事已解鎖");
+      return true;
+    } catch (error) {
+      logError("記事解鎖失敗:", error);
+      message = `${error}，請重新輸入密碼`;
+    }
+  }
+}
+
+async function refreshEncryptionStatus(): Promise<void> {
+  const encrypted = await isMemoStoreEncrypted();
+  if (encryptionStatus) {
+    encryptionStatus.textContent = encrypted ? "已加密" : "未加密";
+  }
+  if (toggleEncryptionBtn) {
+    toggleEncryptionBtn.textContent = encrypted ? "停用加密" : "啟用加密";
+  }
+  if (changePassphraseBtn) {
+    changePassphraseBtn.disabled = !encrypted;
+  }
+}
+
+function promptNewPassphrase(): string | null {
+  const passphrase = window.prompt("請輸入新密碼");
+  if (passphrase === null) return null;
+
+  if (window.prompt("請再次輸入新密碼") !== passphrase) {
+    window.alert("兩次輸入的密碼不一致");
+    return null;
+  }
+
+  return passphrase;
+}
+
+async function toggleEncryption(): Promise<void> {
+  if (await isMemoStoreEncrypted()) {
+    const passphrase = window.prompt("請輸入目前密碼以停用加密");
+    if (passphrase === null) return;
+    await disableMemoStoreEncryption(passphrase);
+    logInfo("已停用記事加密");
+  } else {
+    const passphrase = promptNewPassphrase();
+    if (passphrase === null) return;
+    await enableMemoStoreEncryption(passphrase);
+    logInfo("已啟用記事加密");
+  }
+}
+
+async function changePassphrase(): Promise<void> {
+  const currentPassphrase = window.prompt("請輸入目前密碼");
+  if (currentPassphrase === null) return;
+
+  const newPassphrase = promptNewPassphrase();
+  if (newPassphrase === null) return;
+
+  await changeMemoStorePassphrase(currentPassphrase, newPassphrase);
+  logInfo("已變更記事密碼");
+}
+
+async function runEncryptionAction(action: () => Promise<void>): Promise<void> {
+  try {
+    await action();
+  } catch (error) {
+    logError("記事加密設定失敗:", error);
+    window.alert(`${error}`);
+  }
+
+  await refreshEncryptionStatus().catch((error: unknown) => {
+    logError("讀取加密狀態失敗:", error);
+  });
+}
+
+export async function setupEncryptionListeners(): Promise<void> {
+  toggleEncryptionBtn?.addEventListener("click", () => {
+    void runEncryptionAction(toggleEncryption);
+  });
+
+  changePassphraseBtn?.addEventListener("click", () => {
+    void runEncryptionAction(changePassphrase);
+  });
+
+  await refreshEncryptionStatus();
+}
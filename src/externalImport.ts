@@ -0,0 +1,104 @@
+import {
+  importEnex,
+  importKeep,
+  importPlumDatabase,
+  importSimplenote,
+  importTomboy,
+  previewKeepImport,
+  previewSimplenoteImport,
+} from "./api";
+import { externalImportBtn, externalImportPathInput, externalImportSourceSelect } from "./dom";
+import { logError, logInfo } from "./logger";
+import type { ImportPreview, ImportReport, MemoCollectionName } from "./types";
+
+interface Importer {
+  preview?: (path: string) => Promise<ImportPreview>;
+  run: (path: string) => Promise<ImportReport>;
+}
+
+const importers: Record<string, Importer> = {
+  tomboy: { run: importTomboy },
+  plum: { run: importPlumDatabase },
+  keep: { preview: previewKeepImport, run: importKeep },
+  simplenote: { preview: previewSimplenoteImport, run: importSimplenote },
+  enex: { run: importEnex },
+};
+
+const PREVIEW_TITLE_LIMIT = 10;
+
+const collectionLabels: Record<MemoCollectionName, string> = {
+  history: "歷史記錄",
+  archive: "封存",
+  trash: "垃圾桶",
+};
+
+function describePreview(preview: ImportPreview): string {
+  const counts = new Map<MemoCollectionName, number>();
+  for (const memo of preview.memos) {
+    counts.set(memo.collection, (counts.get(memo.collection) ?? 0) + 1);
+  }
+  const breakdown = [...counts].map(([collection, count]) => `${collectionLabels[collection]} ${count}`).join("、");
+  const replaced = preview.memos.filter((memo) => memo.replaces_existing).length;
+  const kept = preview.memos.filter((memo) => memo.keeps_existing).length;
+
+  const lines = [`將匯入 ${preview.memos.length} 則記事${breakdown ? `（${breakdown}）` : ""}`];
+  if (replaced > 0) {
+    lines.push(`其中 ${replaced} 則會取代現有記事`);
+  }
+  if (kept > 0) {
+    lines.push(`其中 ${kept} 則在 UpMemo 中已有較新的版本，將略過`);
+  }
+  for (const memo of preview.memos.slice(0, PREVIEW_TITLE_LIMIT)) {
+    lines.push(`• ${memo.title}`);
+  }
+  if (preview.memos.length > PREVIEW_TITLE_LIMIT) {
+    lines.push(`…另外 ${preview.memos.length - PREVIEW_TITLE_LIMIT} 則`);
+  }
+  for (const failure of preview.failures) {
+    lines.push(`✗ ${failure.path}：${failure.source}`);
+  }
+  return lines.join("\n");
+}
+
+async function runImport(): Promise<void> {
+  const path = externalImportPathInput?.value.trim() ?? "";
+  const importer = importers[externalImportSourceSelect?.value ?? ""];
+  if (!path || !importer) {
+    window.alert("請選擇來源並輸入路徑");
+    return;
+  }
+
+  try {
+    if (importer.preview) {
+      const preview = await importer.preview(path);
+      logInfo("外部匯入預覽:", preview.memos.length);
+      if (preview.memos.length === 0) {
+        window.alert(describePreview(preview));
+        return;
+      }
+      if (!window.confirm(`${describePreview(preview)}\n\n確定要匯入嗎？`)) {
+        return;
+      }
+    }
+
+    const report = await importer.run(path);
+    logInfo("外部匯入完成:", report.imported.length);
+    const lines = [`已匯入 ${report.imported.length} 則記事`];
+    if (report.unchanged.length > 0) {
+      lines.push(`已略過 ${report.unchanged.length} 則（UpMemo 中已有較新的版本）`);
+    }
+    for (const failure of report.failures) {
+      lines.push(`✗ ${failure.path}：${failure.source}`);
+    }
+    window.alert(lines.join("\n"));
+  } catch (error) {
+    logError("外部匯入失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+export function setupExternalImportListeners(): void {
+  externalImportBtn?.addEventListener("click", () => {
+    void runImport();
+  });
+}
@@ -5,6 +5,7 @@ import { escapeHtml, formatTimestamp } from "./utils";
 import { saveNote } from "./api";
 import { linkifyText } from "./linkify";
 import { logError, logInfo } from "./logger";
+import { isLockedContent, previewText, promptLockMemo, promptUnlockMemo } from "./privateMemo";
 
 // 顯示歷史記錄面板
 export async function showHistory(): Promise<void> {
@@ -34,10 +35,12 @@ function renderHistory(history: MemoEntry[]): void {
   historyList.innerHTML = history
     .map((item) => {
       const timeStr = formatTimestamp(item.timestamp);
-      const preview = item.content.substring(0, 60) + (item.content.length > 60 ? "..." : "");
+      const preview = previewText(item.content);
+      const locked = isLockedContent(item.content);
 
       return `
-        <div class="history-item" data-id="${item.id}">
+        <div class="history-item" data-id="${item.id}" data-locked="${locked}">
+          <button class="history-item-lock" data-id="${item.id}" title="設為私人記事"${locked ? " hidden" : ""}>🔒</button>
           <button class="history-item-archive" data-id="${item.id}" title="封存">📦</button>
           <button class="history-item-delete" data-id="${item.id}" title="刪除">×</button>
           <div class="history-item-time">${timeStr}</div>
@@ -55,13 +58,27 @@ function renderHistory(history: MemoEntry[]): void {
       if (
         target instanceof HTMLElement &&
         (target.classList.contains("history-item-archive") ||
-          target.classList.contains("history-item-delete"))
+          target.classList.contains("history-item-delete") ||
+          target.classList.contains("history-item-lock"))
       ) {
         return;
       }
       const id = item.dataset["id"];
-      if (id) {
-        await loadHistoryItem(id);
+      if (!id) return;
+      if (item.dataset["locked"] === "true" && !(await promptUnlockMemo(id))) {
+        return;
+      }
+      await loadHistoryItem(id);
+    });
+  });
+
+  // 綁定私人記事按鈕事件
+  document.querySelectorAll<HTMLElement>(".history-item-lock").forEach((btn) => {
+    btn.addEventListener("click", async (e) => {
+      e.stopPropagation();
+      const id = btn.dataset["id"];
+      if (id && (await promptLockMemo(id)) === "locked") {
+        await showHistory();
       }
     });
   });
@@ -1,6 +1,16 @@
-import { loadNote, saveNote, saveNoteToHistory, createNewMemo } from "./api";
+import { listen } from "@tauri-apps/api/event";
+import {
+  loadNote,
+  saveNote,
+  saveNoteToHistory,
+  createNewMemo,
+  acknowledgeAutosaveFlush,
+  recordDraft,
+  getRecoverableDraft,
+  resolveRecoveredDrafts,
+} from "./api";
 import { noteDisplay, newMemoBtn } from "./dom";
-import { initStore } from "./storage";
+import { initStore, loadAutoLockSecrets } from "./storage";
 import { initTheme, setupColorListeners } from "./theme";
 import { initFontSystem, setupFontListeners } from "./font";
 import { initShortcut } from "./shortcut";
@@ -8,6 +18,17 @@ import { setupHistoryListeners } from "./history";
 import { setupArchiveListeners } from "./archive";
 import { setupTrashListeners } from "./trash";
 import { setupSettingsListeners } from "./settings";
+import { refreshOpenPanels, setupStoreChangeListeners } from "./storeEvents";
+import { setupEncryptionListeners, unlockMemoStoreIfNeeded } from "./encryption";
+import { setupPrivateMemoListeners } from "./privateMemo";
+import { ensureAppUnlocked, setupAppLockListeners } from "./appLock";
+import { handleSavedNote, hideSecretWarning, setupSecretWarningListeners } from "./secretWarning";
+import { setupMarkdownFolderListeners } from "./markdownFolder";
+import { setupBundleListeners } from "./bundle";
+import { setupExternalImportListeners } from "./externalImport";
+import { setupPlainMirrorListeners } from "./plainMirror";
+import { setupVersionHistoryListeners } from "./versionHistory";
+import { setupMemoSyncListeners } from "./memoSync";
 import {
   extractPlainText,
   handleLinkClick,
@@ -39,6 +60,23 @@ async function initializeApp() {
     logError("Store 初始化失敗:", error);
   }
 
+  // 記事已加密時先要求解鎖，否則無法讀取任何內容
+  try {
+    if (!(await unlockMemoStoreIfNeeded())) {
+      logInfo("記事仍為鎖定狀態，停止初始化");
+      return;
+    }
+  } catch (error) {
+    logError("記事解鎖失敗:", error);
+    return;
+  }
+
+  try {
+    await ensureAppUnlocked();
+  } catch (error) {
+    logError("PIN 鎖定檢查失敗:", error);
+  }
+
   // 載入筆記內容（只有主視窗，已停用多視窗功能）
   try {
     const content = await loadNote();
@@ -49,6 +87,21 @@ async function initializeApp() {
     logError("載入筆記失敗:", error);
   }
 
+  // 檢查上次異常結束時留下的草稿
+  try {
+    const draft = await getRecoverableDraft();
+    if (draft) {
+      const recover = window.confirm("發現上次未儲存的內容，是否復原？");
+      const restored = await resolveRecoveredDrafts(recover);
+      if (restored !== null) {
+        noteDisplay.innerHTML = linkifyText(restored);
+        logInfo("已復原草稿內容");
+      }
+    }
+  } catch (error) {
+    logError("草稿復原失敗:", error);
+  }
+
   // 監聽輸入事件，自動轉換網址並儲存
   let saveTimeout: number | null = null;
   let linkifyTimeout: number | null = null;
@@ -59,9 +112,10 @@ async function initializeApp() {
 
       // 主視窗：儲存到 note.txt 和歷史
       if (plainText.trim()) {
-        await saveNoteToHistory(plainText);
+        await handleSavedNote(await saveNoteToHistory(plainText, loadAutoLockSecrets()), plainText);
       } else {
         await saveNote(plainText);
+        hideSecretWarning();
       }
     } catch (error) {
       logError("自動儲存失敗:", error);
@@ -78,6 +132,11 @@ async function initializeApp() {
     // 提取純文本內容
     const plainText = extractPlainText(noteDisplay);
 
+    // 立即寫入草稿日誌，避免在延遲儲存前遺失內容
+    recordDraft(plainText).catch((error: unknown) => {
+      logError("草稿日誌寫入失敗:", error);
+    });
+
     // 延遲轉換網址（避免頻繁更新干擾輸入）
     if (!skipLinkify) {
       if (linkifyTimeout !== null) {
@@ -111,6 +170,24 @@ async function initializeApp() {
   });
   logInfo("已註冊 note display 輸入監聽器");
 
+  // 結束前由後端要求立即儲存尚未送出的內容
+  try {
+    await listen("flush-pending-autosave", async () => {
+      try {
+        if (saveTimeout !== null) {
+          clearTimeout(saveTimeout);
+          saveTimeout = null;
+          await saveCurrentNote(extractPlainText(noteDisplay));
+        }
+      } finally {
+        await acknowledgeAutosaveFlush();
+      }
+    });
+    logInfo("已註冊結束前儲存監聽器");
+  } catch (error) {
+    logError("結束前儲存監聽器設置失敗:", error);
+  }
+
   // 處理連結點擊事件
   noteDisplay.addEventListener("click", handleLinkClick);
 
@@ -188,6 +265,90 @@ async function initializeApp() {
     logError("設定監聽器設置失敗:", error);
   }
 
+  try {
+    logInfo("設置加密設定監聽器...");
+    await setupEncryptionListeners();
+    logInfo("加密設定監聽器設置完成");
+  } catch (error) {
+    logError("加密設定監聽器設置失敗:", error);
+  }
+
+  try {
+    logInfo("設置 PIN 鎖定監聽器...");
+    await setupAppLockListeners(async () => {
+      noteDisplay.innerHTML = linkifyText(await loadNote());
+    });
+    logInfo("PIN 鎖定監聽器設置完成");
+  } catch (error) {
+    logError("PIN 鎖定監聽器設置失敗:", error);
+  }
+
+  try {
+    logInfo("設置私人記事監聽器...");
+    await setupPrivateMemoListeners(refreshOpenPanels);
+    logInfo("私人記事監聽器設置完成");
+  } catch (error) {
+    logError("私人記事監聽器設置失敗:", error);
+  }
+
+  try {
+    logInfo("設置機密偵測監聽器...");
+    setupSecretWarningListeners();
+    logInfo("機密偵測監聽器設置完成");
+  } catch (error) {
+    logError("機密偵測監聽器設置失敗:", error);
+  }
+
+  try {
+    logInfo("設置 Markdown 資料夾監聽器...");
+    setupMarkdownFolderListeners();
+    logInfo("Markdown 資料夾監聽器設置完成");
+  } catch (error) {
+    logError("Markdown 資料夾監聽器設置失敗:", error);
+  }
+
+  try {
+    logInfo("設置完整備份監聽器...");
+    setupBundleListeners();
+    logInfo("完整備份監聽器設置完成");
+  } catch (error) {
+    logError("完整備份監聽器設置失敗:", error);
+  }
+
+  try {
+    logInfo("設置外部匯入監聽器...");
+    setupExternalImportListeners();
+    logInfo("外部匯入監聽器設置完成");
+  } catch (error) {
+    logError("外部匯入監聽器設置失敗:", error);
+  }
+
+  try {
+    logInfo("設置鏡像資料夾監聽器...");
+    await setupPlainMirrorListeners();
+    logInfo("鏡像資料夾監聽器設置完成");
+  } catch (error) {
+    logError("鏡像資料夾監聽器設置失敗:", error);
+  }
+
+  try {
+    logInfo("設置版本歷史監聽器...");
+    await setupVersionHistoryListeners();
+    logInfo("版本歷史監聽器設置完成");
+  } catch (error) {
+    logError("版本歷史監聽器設置失敗:", error);
+  }
+
+  try {
+    logInfo("設置裝置同步監聽器...");
+    await setupMemoSyncListeners(async () => {
+      noteDisplay.innerHTML = linkifyText(await loadNote());
+    });
+    logInfo("裝置同步監聽器設置完成");
+  } catch (error) {
+    logError("裝置同步監聽器設置失敗:", error);
+  }
+
   try {
     logInfo("設置圖片插入監聽器...");
     setupImageListeners();
@@ -196,6 +357,14 @@ async function initializeApp() {
     logError("圖片插入監聽器設置失敗:", error);
   }
 
+  try {
+    logInfo("設置 memo_store 變更監聽器...");
+    await setupStoreChangeListeners();
+    logInfo("memo_store 變更監聽器設置完成");
+  } catch (error) {
+    logError("memo_store 變更監聽器設置失敗:", error);
+  }
+
   logInfo("所有監聽器設置完成");
 
   // 新增便條按鈕事件（在主視窗清空並開始新便條）
@@ -204,6 +373,7 @@ async function initializeApp() {
       // 創建新的便條（清空主視窗內容）
       const newId = await createNewMemo();
       noteDisplay.innerHTML = "";
+      hideSecretWarning();
       logInfo(`已建立新便條，ID: ${newId}`);
     } catch (error) {
       logError("建立新便條失敗:", error);
@@ -0,0 +1,75 @@
+import { exportMarkdown, importMarkdown } from "./api";
+import {
+  exportMarkdownBtn,
+  importMarkdownBtn,
+  markdownFolderInput,
+  markdownIncludeArchive,
+  markdownIncludeHistory,
+} from "./dom";
+import { logError, logInfo } from "./logger";
+import type { MemoCollectionName } from "./types";
+
+function selectedCollections(): MemoCollectionName[] {
+  const collections: MemoCollectionName[] = [];
+  if (markdownIncludeHistory?.checked) collections.push("history");
+  if (markdownIncludeArchive?.checked) collections.push("archive");
+  return collections;
+}
+
+async function runExport(): Promise<void> {
+  const path = markdownFolderInput?.value.trim() ?? "";
+  const collections = selectedCollections();
+  if (!path || collections.length === 0) {
+    window.alert("請輸入資料夾路徑並至少選擇一個來源");
+    return;
+  }
+
+  try {
+    const report = await exportMarkdown(path, collections);
+    logInfo("Markdown 匯出完成:", report.memos.length);
+    const lines = [`已匯出 ${report.memos.length} 則記事、${report.images.length} 張圖片`];
+    if (report.skipped_private.length > 0) {
+      lines.push(`略過 ${report.skipped_private.length} 則私人記事`);
+    }
+    if (report.missing_images.length > 0) {
+      lines.push(`${report.missing_images.length} 張圖片找不到原始檔`);
+    }
+    window.alert(lines.join("\n"));
+  } catch (error) {
+    logError("Markdown 匯出失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+async function runImport(): Promise<void> {
+  const path = markdownFolderInput?.value.trim() ?? "";
+  if (!path) {
+    window.alert("請輸入資料夾路徑");
+    return;
+  }
+
+  try {
+    const report = await importMarkdown(path);
+    logInfo("Markdown 匯入完成:", report.imported.length);
+    const lines = [`已匯入 ${report.imported.length} 則記事`];
+    if (report.unchanged.length > 0) {
+      lines.push(`已略過 ${report.unchanged.length} 則（UpMemo 中已有較新的版本）`);
+    }
+    for (const failure of report.failures) {
+      lines.push(`✗ ${failure.path}：${failure.source}`);
+    }
+    window.alert(lines.join("\n"));
+  } catch (error) {
+    logError("Markdown 匯入失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+export function setupMarkdownFolderListeners(): void {
+  exportMarkdownBtn?.addEventListener("click", () => {
+    void runExport();
+  });
+  importMarkdownBtn?.addEventListener("click", () => {
+    void runImport();
+  });
+}
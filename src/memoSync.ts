@@ -0,0 +1,89 @@
+import { listen } from "@tauri-apps/api/event";
+import { disableSync, enableFolderSync, getSyncStatus, syncMemosNow } from "./api";
+import { disableSyncBtn, enableSyncBtn, syncFolderInput, syncNowBtn, syncStatusText } from "./dom";
+import { logError, logInfo } from "./logger";
+import type { SyncReport, SyncStatus } from "./types";
+import { formatTimestamp } from "./utils";
+
+const MEMO_SYNC_STATUS_EVENT = "memo-sync-status";
+const CURRENT_MEMO_SYNCED_EVENT = "current-memo-synced";
+
+function showStatus(status: SyncStatus): void {
+  if (!syncStatusText) return;
+  if (!status.backend) {
+    syncStatusText.textContent = "同步未啟用";
+    return;
+  }
+
+  const parts = [`同步中：${status.backend.path}`];
+  if (status.syncing) {
+    parts.push("正在同步…");
+  } else if (status.last_sync) {
+    parts.push(`上次同步：${formatTimestamp(status.last_sync)}`);
+  }
+  if (status.last_report) {
+    parts.push(`送出 ${status.last_report.pushed.length}、收到 ${status.last_report.pulled.length}`);
+    if (status.last_report.conflicts.length > 0) {
+      parts.push(`衝突副本 ${status.last_report.conflicts.length}`);
+    }
+  }
+  if (status.last_error) {
+    parts.push(`⚠ ${status.last_error}`);
+  }
+  syncStatusText.textContent = parts.join("｜");
+}
+
+async function runSyncAction(action: () => Promise<SyncReport>): Promise<void> {
+  try {
+    const report = await action();
+    logInfo("記事同步完成:", report.pushed.length, report.pulled.length);
+    showStatus(await getSyncStatus());
+    if (report.conflicts.length > 0) {
+      window.alert(`有 ${report.conflicts.length} 則記事在兩台裝置上都有修改，較舊的版本已另存為「#衝突副本」記事`);
+    }
+  } catch (error) {
+    logError("記事同步失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+export async function setupMemoSyncListeners(onCurrentMemoSynced: () => Promise<void>): Promise<void> {
+  enableSyncBtn?.addEventListener("click", () => {
+    const folder = syncFolderInput?.value.trim() ?? "";
+    if (!folder) {
+      window.alert("請輸入同步資料夾路徑");
+      return;
+    }
+    void runSyncAction(() => enableFolderSync(folder));
+  });
+
+  syncNowBtn?.addEventListener("click", () => {
+    void runSyncAction(syncMemosNow);
+  });
+
+  disableSyncBtn?.addEventListener("click", async () => {
+    try {
+      await disableSync();
+      showStatus(await getSyncStatus());
+    } catch (error) {
+      logError("停用同步失敗:", error);
+      window.alert(`${error}`);
+    }
+  });
+
+  await listen<SyncStatus>(MEMO_SYNC_STATUS_EVENT, (event) => {
+    showStatus(event.payload);
+  });
+
+  // 其他裝置修改了目前開啟的記事，重新載入編輯區
+  await listen(CURRENT_MEMO_SYNCED_EVENT, () => {
+    logInfo("目前記事已由同步更新");
+    void onCurrentMemoSynced();
+  });
+
+  const status = await getSyncStatus();
+  if (status.backend && syncFolderInput) {
+    syncFolderInput.value = status.backend.path;
+  }
+  showStatus(status);
+}
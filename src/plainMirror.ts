@@ -0,0 +1,93 @@
+import { listen } from "@tauri-apps/api/event";
+import { disablePlainMirror, enablePlainMirror, getPlainMirror, syncPlainMirrorNow } from "./api";
+import {
+  disablePlainMirrorBtn,
+  enablePlainMirrorBtn,
+  plainMirrorFolderInput,
+  plainMirrorFormatSelect,
+  plainMirrorStatus,
+  syncPlainMirrorBtn,
+} from "./dom";
+import { logError, logInfo } from "./logger";
+import type { PlainMirrorConfig, PlainMirrorFormat, PlainMirrorReport } from "./types";
+
+const PLAIN_MIRROR_SYNCED_EVENT = "plain-mirror-synced";
+
+function showStatus(config: PlainMirrorConfig | null, report?: PlainMirrorReport): void {
+  if (!plainMirrorStatus) return;
+  if (!config) {
+    plainMirrorStatus.textContent = "鏡像未啟用";
+    return;
+  }
+
+  const parts = [`鏡像中：${config.folder}`];
+  if (report) {
+    parts.push(`寫出 ${report.written.length}、更新 ${report.updated.length}、新增 ${report.added.length}`);
+    if (report.conflicts.length > 0) {
+      parts.push(`衝突副本 ${report.conflicts.length}`);
+    }
+  }
+  plainMirrorStatus.textContent = parts.join("｜");
+}
+
+function describeProblems(report: PlainMirrorReport): string | null {
+  const lines = report.conflicts.map((conflict) => `⚠ ${conflict.path} 兩邊都有修改，已另存為新記事`);
+  for (const failure of report.failures) {
+    lines.push(`✗ ${failure.path}：${failure.source}`);
+  }
+  return lines.length > 0 ? lines.join("\n") : null;
+}
+
+async function runMirrorAction(action: () => Promise<PlainMirrorReport>): Promise<void> {
+  try {
+    const report = await action();
+    logInfo("鏡像資料夾同步完成:", report.written.length);
+    showStatus(await getPlainMirror(), report);
+    const problems = describeProblems(report);
+    if (problems) window.alert(problems);
+  } catch (error) {
+    logError("鏡像資料夾同步失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+export async function setupPlainMirrorListeners(): Promise<void> {
+  enablePlainMirrorBtn?.addEventListener("click", () => {
+    const folder = plainMirrorFolderInput?.value.trim() ?? "";
+    if (!folder) {
+      window.alert("請輸入鏡像資料夾路徑");
+      return;
+    }
+    const format = (plainMirrorFormatSelect?.value ?? "markdown") as PlainMirrorFormat;
+    void runMirrorAction(() => enablePlainMirror(folder, format));
+  });
+
+  syncPlainMirrorBtn?.addEventListener("click", () => {
+    void runMirrorAction(syncPlainMirrorNow);
+  });
+
+  disablePlainMirrorBtn?.addEventListener("click", async () => {
+    try {
+      await disablePlainMirror();
+      showStatus(null);
+    } catch (error) {
+      logError("停用鏡像資料夾失敗:", error);
+      window.alert(`${error}`);
+    }
+  });
+
+  await listen<PlainMirrorReport>(PLAIN_MIRROR_SYNCED_EVENT, (event) => {
+    logInfo("鏡像資料夾已自動同步:", event.payload.written.length);
+    void getPlainMirror().then((config) => showStatus(config, event.payload));
+    if (event.payload.conflicts.length > 0) {
+      window.alert(describeProblems(event.payload) ?? "");
+    }
+  });
+
+  const config = await getPlainMirror();
+  if (config) {
+    if (plainMirrorFolderInput) plainMirrorFolderInput.value = config.folder;
+    if (plainMirrorFormatSelect) plainMirrorFormatSelect.value = config.format;
+  }
+  showStatus(config);
+}
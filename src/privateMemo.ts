@@ -0,0 +1,89 @@
+import { listen } from "@tauri-apps/api/event";
+import { keepPrivateMemosUnlocked, lockMemo, unlockMemo } from "./api";
+import { noteDisplay } from "./dom";
+import { logError, logInfo } from "./logger";
+
+const PRIVATE_MEMO_PREFIX = "upmemo-private:v1:";
+const PRIVATE_MEMO_RELOCKED_EVENT = "private-memo-relocked";
+const PRIVATE_MEMO_EXPIRING_EVENT = "private-memo-expiring";
+const LOCKED_PREVIEW = "🔒 私人記事";
+
+export type LockPromptResult = "locked" | "cancelled" | "failed";
+
+interface RelockedMemos {
+  ids: string[];
+  detached: boolean;
+}
+
+export function isLockedContent(content: string): boolean {
+  return content.startsWith(PRIVATE_MEMO_PREFIX);
+}
+
+// 清單預覽：未解鎖的私人記事不顯示內容
+export function previewText(content: string): string {
+  if (isLockedContent(content)) return LOCKED_PREVIEW;
+
+  return content.substring(0, 60) + (content.length > 60 ? "..." : "");
+}
+
+export async function promptUnlockMemo(id: string): Promise<boolean> {
+  const password = window.prompt("此為私人記事，請輸入密碼");
+  if (password === null) return false;
+
+  try {
+    await unlockMemo(id, password);
+    logInfo("私人記事已解鎖");
+    return true;
+  } catch (error) {
+    logError("私人記事解鎖失敗:", error);
+    window.alert(`${error}`);
+    return false;
+  }
+}
+
+export async function promptLockMemo(id: string, content?: string): Promise<LockPromptResult> {
+  const password = window.prompt("請設定此記事的密碼");
+  if (password === null) return "cancelled";
+
+  const confirmation = window.prompt("請再次輸入密碼");
+  if (confirmation === null) return "cancelled";
+  if (confirmation !== password) {
+    window.alert("兩次輸入的密碼不一致");
+    return "failed";
+  }
+
+  try {
+    await lockMemo(id, password, content);
+    logInfo("已設為私人記事");
+    return "locked";
+  } catch (error) {
+    logError("設為私人記事失敗:", error);
+    window.alert(`${error}`);
+    return "failed";
+  }
+}
+
+// 私人記事重新上鎖時，若正在編輯則清空編輯區
+export async function setupPrivateMemoListeners(onRelocked: () => Promise<void>): Promise<void> {
+  await listen<RelockedMemos>(PRIVATE_MEMO_RELOCKED_EVENT, (event) => {
+    logInfo("私人記事已重新上鎖:", event.payload.ids.length);
+    if (event.payload.detached) {
+      noteDisplay.innerHTML = "";
+    }
+    void onRelocked();
+  });
+
+  // 閒置即將逾時：先詢問是否保持解鎖，避免編輯中的內容無法儲存
+  await listen<string[]>(PRIVATE_MEMO_EXPIRING_EVENT, async (event) => {
+    if (!window.confirm("私人記事即將因閒置重新上鎖，要繼續保持解鎖嗎？")) return;
+
+    try {
+      const kept = await keepPrivateMemosUnlocked(event.payload);
+      if (kept.length < event.payload.length) {
+        window.alert("私人記事已重新上鎖，請重新解鎖");
+      }
+    } catch (error) {
+      logError("延長私人記事解鎖失敗:", error);
+    }
+  });
+}
@@ -0,0 +1,62 @@
+import { declineSecretAutoLock, saveNoteToHistory } from "./api";
+import { autoLockSecretsCheckbox, secretWarning } from "./dom";
+import { logError, logInfo } from "./logger";
+import { promptLockMemo } from "./privateMemo";
+import { loadAutoLockSecrets, saveAutoLockSecrets } from "./storage";
+import type { SavedNote, SecretKind } from "./types";
+
+const SECRET_KIND_LABELS: Record<SecretKind, string> = {
+  aws_access_key: "AWS 存取金鑰",
+  private_key_block: "私鑰",
+  jwt: "JWT",
+  high_entropy_token: "疑似 Token",
+};
+
+export function hideSecretWarning(): void {
+  secretWarning?.classList.add("hidden");
+}
+
+// 儲存後依掃描結果顯示警告，並視設定提示設為私人記事
+export async function handleSavedNote(saved: SavedNote, content: string): Promise<void> {
+  if (!secretWarning) return;
+
+  if (saved.secret_warnings.length === 0) {
+    hideSecretWarning();
+    return;
+  }
+
+  const findings = saved.secret_warnings
+    .map((finding) => `${SECRET_KIND_LABELS[finding.kind]}（${finding.excerpt}）`)
+    .join("、");
+  secretWarning.textContent = `⚠️ 此記事可能含有機密：${findings}`;
+  secretWarning.classList.remove("hidden");
+  logInfo("偵測到機密內容:", saved.secret_warnings.length);
+
+  const memoId = saved.memo_id;
+  if (!memoId || !saved.lock_pending) return;
+
+  // 內容尚未寫入，設定密碼後直接以密文儲存；輸入錯誤則重新詢問，明確取消才記住選擇並照常儲存
+  let result = await promptLockMemo(memoId, content);
+  while (result === "failed") {
+    result = await promptLockMemo(memoId, content);
+  }
+  if (result === "locked") {
+    hideSecretWarning();
+    return;
+  }
+  await declineSecretAutoLock(memoId);
+  await handleSavedNote(await saveNoteToHistory(content, false), content);
+}
+
+export function setupSecretWarningListeners(): void {
+  if (!autoLockSecretsCheckbox) return;
+
+  autoLockSecretsCheckbox.checked = loadAutoLockSecrets();
+  autoLockSecretsCheckbox.addEventListener("change", async () => {
+    try {
+      await saveAutoLockSecrets(autoLockSecretsCheckbox.checked);
+    } catch (error) {
+      logError("儲存機密偵測設定失敗:", error);
+    }
+  });
+}
@@ -11,7 +11,8 @@ let cache = {
   shortcut: { ...DEFAULT_SHORTCUT },
   colorTheme: "yellow",
   fontSize: 14,
-  opacity: 100
+  opacity: 100,
+  autoLockSecrets: false
 };
 
 // 初始化 store 並載入所有設定到緩存
@@ -33,6 +34,9 @@ export async function initStore(): Promise<void> {
     const opacity = await store.get<number>("opacity");
     if (opacity) cache.opacity = opacity;
 
+    const autoLockSecrets = await store.get<boolean>("autoLockSecrets");
+    if (autoLockSecrets !== undefined && autoLockSecrets !== null) cache.autoLockSecrets = autoLockSecrets;
+
     logInfo("Store 初始化完成，設定已載入");
   } catch (error) {
     logError("載入設定失敗:", error);
@@ -86,3 +90,15 @@ export async function saveOpacity(opacity: number): Promise<void> {
   await store.set("opacity", opacity);
   await store.save();
 }
+
+// 偵測到機密時自動設為私人記事 - 同步讀取（從緩存）
+export function loadAutoLockSecrets(): boolean {
+  return cache.autoLockSecrets;
+}
+
+// 偵測到機密時自動設為私人記事 - 保存（異步）
+export async function saveAutoLockSecrets(enabled: boolean): Promise<void> {
+  cache.autoLockSecrets = enabled;
+  await store.set("autoLockSecrets", enabled);
+  await store.save();
+}
@@ -0,0 +1,34 @@
+import { listen } from "@tauri-apps/api/event";
+import { archivePanel, historyPanel, trashPanel } from "./dom";
+import { showHistory } from "./history";
+import { showArchive } from "./archive";
+import { showTrash } from "./trash";
+import { logInfo } from "./logger";
+import type { StoreChange } from "./types";
+
+const MEMO_STORE_CHANGED_EVENT = "memo-store-changed";
+const MEMO_STORE_ENTRIES_CHANGED_EVENT = "memo-store-entries-changed";
+
+function isPanelOpen(panel: HTMLElement | null): boolean {
+  return panel !== null && !panel.classList.contains("hidden");
+}
+
+// 重新整理目前開啟的清單面板
+export async function refreshOpenPanels(): Promise<void> {
+  if (isPanelOpen(historyPanel)) await showHistory();
+  if (isPanelOpen(archivePanel)) await showArchive();
+  if (isPanelOpen(trashPanel)) await showTrash();
+}
+
+// 監聽後端 memo_store 的外部變更
+export async function setupStoreChangeListeners(): Promise<void> {
+  await listen(MEMO_STORE_CHANGED_EVENT, () => {
+    logInfo("偵測到 memo_store 外部變更，重新整理面板");
+    void refreshOpenPanels();
+  });
+
+  await listen<StoreChange[]>(MEMO_STORE_ENTRIES_CHANGED_EVENT, (event) => {
+    logInfo("memo_store 項目變更:", event.payload.length);
+    void refreshOpenPanels();
+  });
+}
@@ -1,8 +1,9 @@
 import type { MemoEntry } from "./types";
-import { getTrash, restoreFromTrash as restoreFromTrashAPI, permanentlyDeleteTrashItem as permanentlyDeleteTrashItemAPI, emptyTrash as emptyTrashAPI } from "./api";
+import { getTrash, restoreFromTrash as restoreFromTrashAPI, permanentlyDeleteTrashItem as permanentlyDeleteTrashItemAPI, emptyTrash as emptyTrashAPI, shredMemo } from "./api";
 import { trashPanel, trashList, trashBtn, closeTrashBtn, emptyTrashBtn } from "./dom";
 import { escapeHtml, formatTimestamp } from "./utils";
 import { logError, logInfo } from "./logger";
+import { previewText } from "./privateMemo";
 
 // 顯示垃圾桶面板
 export async function showTrash(): Promise<void> {
@@ -32,13 +33,14 @@ function renderTrash(trash: MemoEntry[]): void {
   trashList.innerHTML = trash
     .map((item) => {
       const timeStr = formatTimestamp(item.timestamp);
-      const preview = item.content.substring(0, 60) + (item.content.length > 60 ? "..." : "");
+      const preview = previewText(item.content);
 
       return `
         <div class="trash-item" data-id="${item.id}">
           <div class="trash-item-actions">
             <button class="trash-item-restore" data-id="${item.id}" title="還原">↶</button>
             <button class="trash-item-delete-permanently" data-id="${item.id}" title="永久刪除">×</button>
+            <button class="trash-item-shred" data-id="${item.id}" title="徹底銷毀（含備份）">🔥</button>
           </div>
           <div class="trash-item-time">${timeStr}</div>
           <div class="trash-item-preview">${escapeHtml(preview)}</div>
@@ -58,6 +60,17 @@ function renderTrash(trash: MemoEntry[]): void {
     });
   });
 
+  // 綁定徹底銷毀按鈕事件
+  document.querySelectorAll<HTMLElement>(".trash-item-shred").forEach((btn) => {
+    btn.addEventListener("click", async (e) => {
+      e.stopPropagation();
+      const id = btn.dataset["id"];
+      if (id && window.confirm("將從記事與所有備份中徹底刪除，無法復原。確定要繼續嗎？")) {
+        await shredTrashItem(id);
+      }
+    });
+  });
+
   // 綁定永久刪除按鈕事件 - 使用雙擊確認機制
   document.querySelectorAll<HTMLElement>(".trash-item-delete-permanently").forEach((btn) => {
     let clickCount = 0;
@@ -94,6 +107,19 @@ function renderTrash(trash: MemoEntry[]): void {
   });
 }
 
+// 徹底銷毀：一併清除備份與暫存檔，並列出受影響的檔案
+async function shredTrashItem(id: string): Promise<void> {
+  try {
+    const report = await shredMemo(id);
+    await showTrash();
+    const files = report.files.map((file) => `${file.action === "deleted" ? "刪除" : "改寫"}：${file.path}`);
+    logInfo("已徹底銷毀，受影響檔案:", files.length);
+    window.alert(files.length > 0 ? `已處理 ${files.length} 個檔案：\n${files.join("\n")}` : "沒有找到需要處理的檔案");
+  } catch (error) {
+    logError("徹底銷毀失敗:", error);
+  }
+}
+
 // 從垃圾桶還原
 async function restoreFromTrash(id: string): Promise<void> {
   try {
@@ -26,3 +26,161 @@ export interface MemoEntry {
   content: string;
   timestamp: number;
 }
+
+// 可復原的草稿
+export interface RecoveredDraft {
+  memo_id: string | null;
+  written_at_millis: number;
+  content: string;
+}
+
+// memo_store 項目變更事件
+export type MemoCollectionName = "history" | "archive" | "trash";
+
+export type MemoStoreEncoding = "json" | "zstd";
+
+export interface ShredReport {
+  removed_from_store: boolean;
+  files: { path: string; action: "rewritten" | "deleted" }[];
+}
+
+export type SecretKind = "aws_access_key" | "private_key_block" | "jwt" | "high_entropy_token";
+
+export interface SecretFinding {
+  kind: SecretKind;
+  excerpt: string;
+}
+
+export interface SavedNote {
+  memo_id: string | null;
+  secret_warnings: SecretFinding[];
+  lock_pending: boolean;
+}
+
+export interface MarkdownExportReport {
+  memos: { id: string; collection: MemoCollectionName; path: string }[];
+  images: string[];
+  skipped_private: string[];
+  missing_images: string[];
+}
+
+export type ImportFailureKind =
+  | "unreadable"
+  | "not_utf8"
+  | "missing_image"
+  | "unsupported_image"
+  | "invalid_xml"
+  | "invalid_database"
+  | "invalid_json"
+  | "invalid_resource"
+  | "attachment_not_written";
+
+export interface ImportFailure {
+  kind: ImportFailureKind;
+  path: string;
+  source: string;
+}
+
+export interface ImportReport {
+  imported: { path: string; id: string; collection: MemoCollectionName }[];
+  unchanged: { path: string; id: string; collection: MemoCollectionName }[];
+  failures: ImportFailure[];
+}
+
+export interface ImportPreview {
+  memos: {
+    path: string;
+    id: string;
+    title: string;
+    timestamp: number;
+    collection: MemoCollectionName;
+    replaces_existing: boolean;
+    keeps_existing: boolean;
+  }[];
+  failures: ImportFailure[];
+}
+
+export type PlainMirrorFormat = "markdown" | "html";
+
+export interface PlainMirrorConfig {
+  folder: string;
+  format: PlainMirrorFormat;
+}
+
+export interface PlainMirrorReport {
+  written: string[];
+  removed: string[];
+  updated: string[];
+  added: string[];
+  trashed: string[];
+  conflicts: { id: string; copy_id: string; path: string }[];
+  failures: ImportFailure[];
+}
+
+export interface HistoryCommit {
+  id: string;
+  timestamp: number;
+  message: string;
+}
+
+export interface VersionHistoryStatus {
+  enabled: boolean;
+  latest: HistoryCommit | null;
+}
+
+export interface MemoRevision {
+  commit: HistoryCommit;
+  kind: "added" | "modified" | "removed";
+}
+
+export interface BlameLine {
+  line: number;
+  text: string;
+  commit: string;
+  timestamp: number;
+}
+
+export type SyncBackendConfig = { kind: "folder"; path: string };
+
+export interface SyncReport {
+  pushed: string[];
+  pulled: string[];
+  conflicts: { id: string; copy_id: string }[];
+  skipped_private: number;
+  current_replaced: boolean;
+}
+
+export interface SyncStatus {
+  backend: SyncBackendConfig | null;
+  device_id: string;
+  syncing: boolean;
+  last_sync: number | null;
+  last_report: SyncReport | null;
+  last_error: string | null;
+}
+
+export type BundleImportMode = "replace" | "merge";
+
+export interface BundleManifest {
+  format_version: number;
+  exported_at_unix: number;
+  files: { filename: string; bytes: number; sha256: string }[];
+}
+
+export interface BundleImportReport {
+  backup_path: string;
+  summary: { added: number; updated: number; unchanged: number };
+  restored_files: string[];
+}
+
+export interface AppLockStatus {
+  enabled: boolean;
+  locked: boolean;
+  idle_timeout_secs: number;
+}
+
+export type StoreChange =
+  | { kind: "added"; collection: MemoCollectionName; id: string }
+  | { kind: "updated"; collection: MemoCollectionName; id: string }
+  | { kind: "moved"; from: MemoCollectionName; to: MemoCollectionName; id: string }
+  | { kind: "deleted"; collection: MemoCollectionName; id: string };
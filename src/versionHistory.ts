@@ -0,0 +1,136 @@
+import { listen } from "@tauri-apps/api/event";
+import {
+  blameMemo,
+  disableVersionHistory,
+  enableVersionHistory,
+  getCurrentMemoId,
+  getMemoRevisions,
+  getVersionHistoryStatus,
+  listVersionHistory,
+  restoreVersion,
+} from "./api";
+import {
+  disableVersionHistoryBtn,
+  enableVersionHistoryBtn,
+  memoRevisionsBtn,
+  refreshVersionHistoryBtn,
+  restoreVersionBtn,
+  versionHistoryList,
+  versionHistoryStatus,
+} from "./dom";
+import { logError, logInfo } from "./logger";
+import type { MemoRevision } from "./types";
+import { formatTimestamp } from "./utils";
+
+const VERSION_HISTORY_DISCARDED_EVENT = "version-history-discarded";
+
+const revisionLabels: Record<MemoRevision["kind"], string> = {
+  added: "新增",
+  modified: "修改",
+  removed: "移除",
+};
+
+async function refreshVersionHistory(): Promise<void> {
+  const status = await getVersionHistoryStatus();
+  if (versionHistoryStatus) {
+    versionHistoryStatus.textContent = status.enabled
+      ? `版本歷史已啟用${status.latest ? `｜最新：${formatTimestamp(status.latest.timestamp)} ${status.latest.message}` : ""}`
+      : "版本歷史未啟用";
+  }
+  if (!versionHistoryList) return;
+
+  versionHistoryList.replaceChildren();
+  if (!status.enabled) return;
+  for (const commit of await listVersionHistory()) {
+    const option = document.createElement("option");
+    option.value = commit.id;
+    option.textContent = `${formatTimestamp(commit.timestamp)} ${commit.message} (${commit.id.slice(0, 7)})`;
+    versionHistoryList.append(option);
+  }
+}
+
+async function runHistoryAction(action: () => Promise<unknown>): Promise<void> {
+  try {
+    await action();
+    await refreshVersionHistory();
+  } catch (error) {
+    logError("版本歷史操作失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+async function restoreSelectedVersion(): Promise<void> {
+  const commit = versionHistoryList?.value ?? "";
+  if (!commit) {
+    window.alert("請先選擇要還原的版本");
+    return;
+  }
+  if (!window.confirm("還原會以所選版本取代目前所有記事（目前狀態仍保留在版本歷史中），確定要繼續嗎？")) {
+    return;
+  }
+
+  try {
+    await restoreVersion(commit);
+    logInfo("已還原版本:", commit);
+    window.location.reload();
+  } catch (error) {
+    logError("還原版本失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+async function showCurrentMemoRevisions(): Promise<void> {
+  try {
+    const id = await getCurrentMemoId();
+    if (!id) {
+      window.alert("目前的記事尚未儲存到歷史記錄");
+      return;
+    }
+
+    const revisions = await getMemoRevisions(id);
+    const lines = revisions.map(
+      (revision) => `${formatTimestamp(revision.commit.timestamp)} ${revisionLabels[revision.kind]}：${revision.commit.message}`,
+    );
+    const blame = await blameMemo(id).catch(() => []);
+    if (blame.length > 0) {
+      lines.push("", "各行最後修改時間：");
+      for (const line of blame) {
+        lines.push(`${formatTimestamp(line.timestamp)} │ ${line.text}`);
+      }
+    }
+    window.alert(lines.length > 0 ? lines.join("\n") : "版本歷史中沒有這則記事");
+  } catch (error) {
+    logError("讀取記事變更紀錄失敗:", error);
+    window.alert(`${error}`);
+  }
+}
+
+export async function setupVersionHistoryListeners(): Promise<void> {
+  enableVersionHistoryBtn?.addEventListener("click", () => {
+    void runHistoryAction(enableVersionHistory);
+  });
+
+  disableVersionHistoryBtn?.addEventListener("click", () => {
+    void runHistoryAction(disableVersionHistory);
+  });
+
+  refreshVersionHistoryBtn?.addEventListener("click", () => {
+    void runHistoryAction(async () => {});
+  });
+
+  restoreVersionBtn?.addEventListener("click", () => {
+    void restoreSelectedVersion();
+  });
+
+  memoRevisionsBtn?.addEventListener("click", () => {
+    void showCurrentMemoRevisions();
+  });
+
+  await listen<string>(VERSION_HISTORY_DISCARDED_EVENT, (event) => {
+    logInfo("版本歷史已刪除:", event.payload);
+    window.alert(event.payload);
+    void refreshVersionHistory();
+  });
+
+  await refreshVersionHistory();
+}